@@ -0,0 +1,78 @@
+//! Renderiza un cuadro de la escena sin ventana y lo guarda como PNG.
+//
+// Usa solo el pipeline por software (mallas, renderer, framebuffer y shaders), sin raylib,
+// de modo que sirve para validar el renderizado en máquinas sin GPU ni sistema de ventanas:
+//
+//     cargo run --release --no-default-features --example render_png -- [salida.png] [shader 1-5] [tiempo]
+
+use lab5::framebuffer::Framebuffer;
+use lab5::mesh::ObjMesh;
+use lab5::png;
+use lab5::renderer::{BlendMode, Renderer};
+use lab5::scene::{FrameOptions, RenderObject, SceneCamera, render_frame};
+use lab5::shaders::*;
+use nalgebra_glm::Vec3;
+use std::rc::Rc;
+
+/// Dimensiones de la imagen generada.
+const WIDTH: usize = 800;
+const HEIGHT: usize = 600;
+/// Campo de visión vertical de la cámara, en grados.
+const FOV_Y_DEG: f32 = 60.0;
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let output = args.next().unwrap_or_else(|| "render.png".to_string());
+    let shader_number: usize = args.next().and_then(|a| a.parse().ok()).unwrap_or(1);
+    let time: f32 = args.next().and_then(|a| a.parse().ok()).unwrap_or(1.0);
+
+    // Mismos shaders y numeración (1-5) que las teclas del binario interactivo.
+    let shader: Box<dyn StarShader> = match shader_number {
+        2 => Box::new(PulsarShader::default()),
+        3 => Box::new(PlasmaStarShader::default()),
+        4 => Box::new(SupernovaShader::default()),
+        5 => Box::new(ProminenceSunShader::new(DEFAULT_OCTAVES)),
+        _ => Box::new(ClassicSunShader::default()),
+    };
+    let camera = SceneCamera::orbit(shader.suggested_camera_distance(), FOV_Y_DEG, false);
+
+    let sphere = Rc::new(ObjMesh::create_sphere(1.0, 64, 64));
+    let mut scene = vec![RenderObject::new(
+        Rc::clone(&sphere),
+        shader,
+        Vec3::zeros(),
+        1.5,
+    )];
+    // Como en el binario interactivo, la supernova se acompaña de su onda de choque.
+    if shader_number == 4 {
+        scene.push(
+            RenderObject::new(
+                sphere,
+                Box::new(ShockwaveShader::default()),
+                Vec3::zeros(),
+                1.5,
+            )
+            .with_rotation_speed(0.0)
+            .with_blend_mode(BlendMode::Additive),
+        );
+    }
+
+    let renderer = Renderer::new(WIDTH, HEIGHT);
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    render_frame(
+        &renderer,
+        &mut framebuffer,
+        &scene,
+        &camera,
+        time,
+        &FrameOptions::default(),
+    );
+
+    match std::fs::write(&output, png::encode_framebuffer(&framebuffer)) {
+        Ok(()) => println!("✓ Imagen guardada en {}", output),
+        Err(e) => {
+            eprintln!("Error guardando {}: {}", output, e);
+            std::process::exit(1);
+        }
+    }
+}
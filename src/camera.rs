@@ -0,0 +1,338 @@
+//! Módulo de utilidades de cámara y proyección.
+//
+// Este archivo agrupa la construcción de matrices de proyección usadas por el renderer,
+// de modo que la escena pueda alternar entre vista en perspectiva y ortográfica, y el
+// cálculo inverso de rayos por píxel para efectos que dependen de la dirección de vista,
+// además de los recorridos de cámara por fotogramas clave usados en las demostraciones.
+
+use nalgebra_glm::{Mat4, Vec3, Vec4, look_at, ortho, perspective}; // Tipos y proyecciones.
+
+/// Campo de visión vertical mínimo aceptado por [`make_projection`], en grados.
+pub const MIN_FOV_DEG: f32 = 1.0;
+/// Campo de visión vertical máximo aceptado por [`make_projection`], en grados.
+pub const MAX_FOV_DEG: f32 = 179.0;
+
+/// Ajusta un campo de visión vertical, en grados, al rango [`MIN_FOV_DEG`, `MAX_FOV_DEG`].
+///
+/// Un FOV de 0° (o de 180° o más) produce una proyección degenerada que deja la pantalla
+/// vacía sin ningún error visible.
+///
+/// # Panics
+/// Si `fov_deg` no es finito.
+pub fn clamp_fov_deg(fov_deg: f32) -> f32 {
+    assert!(fov_deg.is_finite(), "FOV must be finite, got {}", fov_deg);
+    fov_deg.clamp(MIN_FOV_DEG, MAX_FOV_DEG)
+}
+
+/// Construye una proyección en perspectiva validando sus parámetros.
+///
+/// Es el punto único de construcción de perspectivas: el campo de visión se ajusta con
+/// [`clamp_fov_deg`] y los planos de recorte se comprueban, de modo que un valor fuera de
+/// rango nunca produce una matriz degenerada o no finita.
+///
+/// # Argumentos
+/// * `aspect` - Relación de aspecto (ancho / alto), positiva.
+/// * `fov_deg` - Campo de visión vertical, en grados.
+/// * `near`, `far` - Planos de recorte cercano y lejano, con `0 < near < far`.
+///
+/// # Panics
+/// Si `aspect` no es positivo y finito, si `fov_deg` no es finito o si los planos de
+/// recorte no cumplen `0 < near < far`.
+pub fn make_projection(aspect: f32, fov_deg: f32, near: f32, far: f32) -> Mat4 {
+    assert!(
+        aspect.is_finite() && aspect > 0.0,
+        "Aspect ratio must be positive, got {}",
+        aspect
+    );
+    assert!(near > 0.0, "Near plane must be positive, got {}", near);
+    assert!(
+        near < far && far.is_finite(),
+        "Near plane ({}) must be closer than far plane ({})",
+        near,
+        far
+    );
+    perspective(aspect, clamp_fov_deg(fov_deg).to_radians(), near, far)
+}
+
+/// Construye una proyección ortográfica con el mismo encuadre que una perspectiva.
+///
+/// El volumen de vista se dimensiona para que, a la distancia `distance` de la cámara,
+/// se vea la misma región que con una perspectiva de campo de visión `fov_y`. Así, al
+/// alternar entre ambas proyecciones el objeto enfocado conserva su tamaño en pantalla.
+///
+/// # Argumentos
+/// * `aspect` - Relación de aspecto (ancho / alto).
+/// * `fov_y` - Campo de visión vertical de referencia, en radianes.
+/// * `distance` - Distancia de la cámara al punto enfocado.
+/// * `near`, `far` - Planos de recorte cercano y lejano.
+///
+/// # Retorna
+/// La matriz de proyección ortográfica.
+pub fn orthographic_framing(aspect: f32, fov_y: f32, distance: f32, near: f32, far: f32) -> Mat4 {
+    let half_height = distance * (fov_y * 0.5).tan();
+    let half_width = half_height * aspect;
+    ortho(
+        -half_width,
+        half_width,
+        -half_height,
+        half_height,
+        near,
+        far,
+    )
+}
+
+/// Calcula la dirección en espacio mundo del rayo de vista que pasa por un píxel.
+///
+/// Desproyecta el centro del píxel en los planos cercano y lejano con la inversa de la
+/// matriz vista-proyección y devuelve la dirección normalizada entre ambos puntos. Funciona
+/// tanto con perspectiva como con proyección ortográfica.
+///
+/// # Argumentos
+/// * `x`, `y` - Coordenadas del píxel (con origen arriba a la izquierda, como el framebuffer).
+/// * `width`, `height` - Dimensiones de la pantalla en píxeles.
+/// * `inverse_view_projection` - Inversa de `projection * view`.
+pub fn screen_ray(x: f32, y: f32, width: f32, height: f32, inverse_view_projection: &Mat4) -> Vec3 {
+    // Inversa de la conversión NDC -> pantalla del rasterizador (Y invertida).
+    let ndc_x = (x + 0.5) / width * 2.0 - 1.0;
+    let ndc_y = 1.0 - (y + 0.5) / height * 2.0;
+
+    let unproject = |z: f32| {
+        let p = inverse_view_projection * Vec4::new(ndc_x, ndc_y, z, 1.0);
+        p.xyz() / p.w
+    };
+
+    (unproject(1.0) - unproject(-1.0)).normalize()
+}
+
+/// Fotograma clave de un recorrido de cámara: posición y punto enfocado en un instante.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraKeyframe {
+    /// Instante del fotograma, en segundos.
+    pub time: f32,
+    /// Posición de la cámara.
+    pub eye: Vec3,
+    /// Punto hacia el que mira la cámara.
+    pub target: Vec3,
+}
+
+/// Recorrido de cámara definido por fotogramas clave, para grabar demostraciones
+/// reproducibles.
+///
+/// Entre fotogramas, la posición y el punto enfocado se interpolan con splines de
+/// Catmull-Rom, que pasan exactamente por cada fotograma y mantienen la velocidad continua.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CameraPath {
+    keyframes: Vec<CameraKeyframe>,
+}
+
+impl CameraPath {
+    /// Crea un recorrido a partir de sus fotogramas, ordenándolos por tiempo.
+    pub fn new(mut keyframes: Vec<CameraKeyframe>) -> Self {
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        CameraPath { keyframes }
+    }
+
+    /// Lee un recorrido en formato de texto: una línea por fotograma con
+    /// `tiempo eye_x eye_y eye_z target_x target_y target_z`. Las líneas vacías y las que
+    /// empiezan con `#` se ignoran.
+    ///
+    /// # Retorna
+    /// `Ok(CameraPath)` si todas las líneas son válidas, o un mensaje de error con el número
+    /// de la primera línea incorrecta.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut keyframes = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let values = line
+                .split_whitespace()
+                .map(|v| v.parse::<f32>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("Camera path line {}: {}", number + 1, e))?;
+            if values.len() != 7 || values.iter().any(|v| !v.is_finite()) {
+                return Err(format!(
+                    "Camera path line {}: expected 7 finite numbers",
+                    number + 1
+                ));
+            }
+            keyframes.push(CameraKeyframe {
+                time: values[0],
+                eye: Vec3::new(values[1], values[2], values[3]),
+                target: Vec3::new(values[4], values[5], values[6]),
+            });
+        }
+        if keyframes.is_empty() {
+            return Err("Camera path has no keyframes".to_string());
+        }
+        Ok(CameraPath::new(keyframes))
+    }
+
+    /// Fotogramas clave, ordenados por tiempo.
+    pub fn keyframes(&self) -> &[CameraKeyframe] {
+        &self.keyframes
+    }
+
+    /// Instante del último fotograma (0 si el recorrido está vacío).
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |k| k.time)
+    }
+
+    /// Posición y punto enfocado de la cámara en el instante `time`.
+    ///
+    /// Antes del primer fotograma y después del último, la cámara se queda en ellos.
+    ///
+    /// # Retorna
+    /// `(eye, target)`, o `None` si el recorrido está vacío.
+    pub fn sample(&self, time: f32) -> Option<(Vec3, Vec3)> {
+        let first = self.keyframes.first()?;
+        let last = self.keyframes[self.keyframes.len() - 1];
+        if time <= first.time {
+            return Some((first.eye, first.target));
+        }
+        if time >= last.time {
+            return Some((last.eye, last.target));
+        }
+
+        // Segmento [k1, k2] que contiene `time`; los extremos se duplican como vecinos.
+        let i = self.keyframes.partition_point(|k| k.time <= time) - 1;
+        let k1 = &self.keyframes[i];
+        let k2 = &self.keyframes[i + 1];
+        let k0 = &self.keyframes[i.saturating_sub(1)];
+        let k3 = &self.keyframes[(i + 2).min(self.keyframes.len() - 1)];
+
+        let span = k2.time - k1.time;
+        let t = if span > 0.0 {
+            (time - k1.time) / span
+        } else {
+            0.0
+        };
+        Some((
+            catmull_rom(&k0.eye, &k1.eye, &k2.eye, &k3.eye, t),
+            catmull_rom(&k0.target, &k1.target, &k2.target, &k3.target, t),
+        ))
+    }
+
+    /// Matriz de vista en el instante `time`, con el eje Y como vertical.
+    pub fn view_matrix(&self, time: f32) -> Option<Mat4> {
+        let (eye, target) = self.sample(time)?;
+        Some(look_at(&eye, &target, &Vec3::new(0.0, 1.0, 0.0)))
+    }
+}
+
+/// Evalúa el segmento de Catmull-Rom uniforme entre `p1` (t = 0) y `p2` (t = 1).
+fn catmull_rom(p0: &Vec3, p1: &Vec3, p2: &Vec3, p3: &Vec3, t: f32) -> Vec3 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (p1 * 2.0
+        + (p2 - p0) * t
+        + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+        + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+        * 0.5
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra_glm::{look_at, perspective};
+
+    /// Inversa de vista-proyección de una cámara en (0, 0, 3) que mira al origen.
+    fn inverse_view_projection(projection: Mat4) -> Mat4 {
+        let view = look_at(&Vec3::new(0.0, 0.0, 3.0), &Vec3::zeros(), &Vec3::y());
+        (projection * view).try_inverse().unwrap()
+    }
+
+    #[test]
+    fn screen_ray_points_through_pixel() {
+        let inverse = inverse_view_projection(perspective(1.0, 1.0, 0.1, 100.0));
+        let center = screen_ray(49.5, 49.5, 100.0, 100.0, &inverse);
+        assert!((center - Vec3::new(0.0, 0.0, -1.0)).magnitude() < 1e-4);
+
+        // La esquina superior izquierda mira hacia -X y +Y.
+        let corner = screen_ray(0.0, 0.0, 100.0, 100.0, &inverse);
+        assert!(corner.x < -0.3 && corner.y > 0.3 && corner.z < 0.0);
+        assert!((corner.magnitude() - 1.0).abs() < 1e-5);
+
+        // En ortográfica todos los rayos son paralelos.
+        let inverse = inverse_view_projection(orthographic_framing(1.0, 1.0, 3.0, 0.1, 100.0));
+        let corner = screen_ray(0.0, 0.0, 100.0, 100.0, &inverse);
+        assert!((corner - Vec3::new(0.0, 0.0, -1.0)).magnitude() < 1e-4);
+    }
+
+    #[test]
+    fn camera_path_passes_through_keyframes_and_holds_ends() {
+        let path =
+            CameraPath::parse("# t eye target\n\n4 0 0 8  1 0 0\n0 0 0 4  0 0 0\n8 4 0 0  0 0 0\n")
+                .unwrap();
+        assert_eq!(path.keyframes()[0].time, 0.0, "se ordena por tiempo");
+        assert_eq!(path.duration(), 8.0);
+
+        for keyframe in path.keyframes() {
+            let (eye, target) = path.sample(keyframe.time).unwrap();
+            assert!((eye - keyframe.eye).magnitude() < 1e-5);
+            assert!((target - keyframe.target).magnitude() < 1e-5);
+        }
+        assert_eq!(
+            path.sample(-1.0),
+            Some((Vec3::new(0.0, 0.0, 4.0), Vec3::zeros()))
+        );
+        assert_eq!(
+            path.sample(99.0),
+            Some((Vec3::new(4.0, 0.0, 0.0), Vec3::zeros()))
+        );
+
+        // Entre fotogramas la curva es continua.
+        let (a, _) = path.sample(3.999).unwrap();
+        let (b, _) = path.sample(4.001).unwrap();
+        assert!((a - b).magnitude() < 0.01);
+    }
+
+    #[test]
+    fn camera_path_parse_reports_the_bad_line() {
+        let error = CameraPath::parse("0 0 0 4 0 0 0\n1 2 3\n").unwrap_err();
+        assert!(error.contains("line 2"), "{error}");
+        assert!(CameraPath::parse("1 2 x 4 5 6 7").is_err());
+        assert!(CameraPath::parse("# vacío\n").is_err());
+
+        let demo = std::fs::read_to_string(format!(
+            "{}/assets/camera_path.txt",
+            env!("CARGO_MANIFEST_DIR")
+        ))
+        .unwrap();
+        assert!(CameraPath::parse(&demo).is_ok());
+    }
+
+    #[test]
+    fn make_projection_clamps_fov_and_matches_perspective_in_range() {
+        assert_eq!(clamp_fov_deg(0.0), MIN_FOV_DEG);
+        assert_eq!(clamp_fov_deg(180.0), MAX_FOV_DEG);
+        assert_eq!(clamp_fov_deg(60.0), 60.0);
+
+        let expected = perspective(1.5, 60f32.to_radians(), 0.1, 100.0);
+        assert_eq!(make_projection(1.5, 60.0, 0.1, 100.0), expected);
+
+        // Fuera de rango el resultado sigue siendo finito: 0° y 180° equivalen a los límites.
+        for (fov, limit) in [
+            (0.0, MIN_FOV_DEG),
+            (-30.0, MIN_FOV_DEG),
+            (180.0, MAX_FOV_DEG),
+        ] {
+            let projection = make_projection(1.5, fov, 0.1, 100.0);
+            assert!(projection.iter().all(|v| v.is_finite()));
+            assert_eq!(projection, make_projection(1.5, limit, 0.1, 100.0));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "closer than far plane")]
+    fn make_projection_rejects_inverted_clip_planes() {
+        make_projection(1.0, 60.0, 10.0, 1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Near plane must be positive")]
+    fn make_projection_rejects_non_positive_near_plane() {
+        make_projection(1.0, 60.0, 0.0, 1.0);
+    }
+}
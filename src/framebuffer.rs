@@ -3,9 +3,20 @@
 // Este archivo define las estructuras y métodos para el manejo de color, almacenamiento de píxeles y profundidad (z-buffer)
 // en imágenes renderizadas, facilitando la integración con librerías gráficas como Raylib.
 
-use nalgebra_glm::Vec3; // Vector 3D para manipulación de colores flotantes.
+use crate::raster::BarycentricSetup; // Coordenadas baricéntricas para rellenar triángulos.
+use crate::texture::{SampleFilter, Texture}; // Imágenes de fondo.
+use nalgebra_glm::{Vec2, Vec3}; // Vectores para posiciones en pantalla y colores flotantes.
+#[cfg(feature = "parallel")]
+use rayon::prelude::*; // Limpieza por filas en paralelo.
+use std::cell::Cell; // Contadores de saturación por hilo.
+use std::ops::Range; // Filas de una franja del framebuffer.
 
-/// Representa un color RGB de 8 bits por canal.
+/// Número mínimo de píxeles para limpiar en paralelo; en framebuffers pequeños repartir las
+/// filas entre hilos cuesta más que escribirlas.
+#[cfg(feature = "parallel")]
+const PARALLEL_CLEAR_THRESHOLD: usize = 1 << 18;
+
+/// Representa un color RGBA de 8 bits por canal.
 #[derive(Debug, Clone, Copy)]
 pub struct Color {
     /// Componente rojo (0-255).
@@ -14,26 +25,157 @@ pub struct Color {
     pub g: u8,
     /// Componente azul (0-255).
     pub b: u8,
+    /// Opacidad (0 transparente, 255 opaco). Se guarda tal cual en el framebuffer para que
+    /// quien consuma los bytes pueda componer la imagen sobre otro contenido.
+    pub a: u8,
 }
 
 impl Color {
-    /// Crea un nuevo color RGB.
+    /// Crea un nuevo color RGB opaco.
     #[inline]
     pub fn new(r: u8, g: u8, b: u8) -> Self {
-        Color { r, g, b }
+        Color { r, g, b, a: 255 }
+    }
+
+    /// Crea un color RGB con opacidad `a`.
+    #[inline]
+    pub fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Color { r, g, b, a }
     }
 
     /// Convierte un vector Vec3 (componentes 0.0-1.0) a un color RGB (0-255).
+    ///
+    /// Si el registro de saturación está activo (ver [`set_clamp_stats_enabled`]), cuenta
+    /// los canales que superan 1.0 y se recortan.
     #[inline]
     pub fn from_vec3(v: Vec3) -> Self {
+        record_clamp(&v);
         Color {
             r: (v.x.clamp(0.0, 1.0) * 255.0) as u8,
             g: (v.y.clamp(0.0, 1.0) * 255.0) as u8,
             b: (v.z.clamp(0.0, 1.0) * 255.0) as u8,
+            a: 255,
+        }
+    }
+
+    /// Convierte el color a un vector Vec3 con componentes en el rango 0.0-1.0.
+    #[inline]
+    pub fn to_vec3(self) -> Vec3 {
+        Vec3::new(self.r as f32, self.g as f32, self.b as f32) / 255.0
+    }
+
+    /// Convierte el color (codificado en sRGB) a valores lineales en [0.0, 1.0].
+    ///
+    /// Usa la función de transferencia sRGB por tramos, no una potencia 2.2 aproximada.
+    #[inline]
+    pub fn to_linear(self) -> Vec3 {
+        self.to_vec3().map(srgb_to_linear)
+    }
+
+    /// Codifica un color lineal (componentes 0.0-1.0) en sRGB de 8 bits, redondeando.
+    #[inline]
+    pub fn from_linear(v: Vec3) -> Self {
+        let encoded = v.map(|c| linear_to_srgb(c.clamp(0.0, 1.0)) * 255.0);
+        Color {
+            r: encoded.x.round() as u8,
+            g: encoded.y.round() as u8,
+            b: encoded.z.round() as u8,
+            a: 255,
         }
     }
 }
 
+/// Decodifica un canal sRGB en [0.0, 1.0] a intensidad lineal.
+#[inline]
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Codifica un canal lineal en [0.0, 1.0] con la curva sRGB.
+#[inline]
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Recuento de conversiones con canales saturados (por encima de 1.0).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClampStats {
+    /// Número de colores convertidos con [`Color::from_vec3`].
+    pub samples: u64,
+    /// Número de conversiones que recortaron cada canal (R, G, B).
+    pub clamped: [u64; 3],
+}
+
+impl ClampStats {
+    /// Porcentaje de conversiones que saturaron cada canal, en [0.0, 100.0].
+    pub fn percentages(&self) -> [f32; 3] {
+        if self.samples == 0 {
+            return [0.0; 3];
+        }
+        self.clamped
+            .map(|count| count as f32 * 100.0 / self.samples as f32)
+    }
+}
+
+thread_local! {
+    /// Estadísticas de saturación del hilo actual; `None` si el registro está desactivado.
+    static CLAMP_STATS: Cell<Option<ClampStats>> = const { Cell::new(None) };
+}
+
+/// Activa o desactiva el registro de saturación en el hilo actual, reiniciando el recuento.
+///
+/// Pensado como modo de depuración al ajustar shaders: indica qué fracción de los
+/// fragmentos se sale del rango y, por tanto, dónde hace falta un mapeo de tonos.
+pub fn set_clamp_stats_enabled(enabled: bool) {
+    CLAMP_STATS.with(|stats| stats.set(enabled.then(ClampStats::default)));
+}
+
+/// Devuelve el recuento acumulado del hilo actual y lo reinicia (p. ej. una vez por cuadro).
+///
+/// Devuelve `None` si el registro está desactivado.
+pub fn take_clamp_stats() -> Option<ClampStats> {
+    CLAMP_STATS.with(|stats| {
+        let current = stats.get();
+        if current.is_some() {
+            stats.set(Some(ClampStats::default()));
+        }
+        current
+    })
+}
+
+/// Indica si el registro de saturación está activo en el hilo actual.
+///
+/// El renderizador lo consulta para no repartir fragmentos entre hilos mientras se cuenta,
+/// ya que cada hilo lleva su propio recuento.
+#[cfg(feature = "parallel")]
+pub(crate) fn clamp_stats_enabled() -> bool {
+    CLAMP_STATS.with(|stats| stats.get().is_some())
+}
+
+/// Suma una conversión al recuento de saturación, si el registro está activo.
+#[inline]
+fn record_clamp(v: &Vec3) {
+    CLAMP_STATS.with(|stats| {
+        if let Some(mut current) = stats.get() {
+            current.samples += 1;
+            for (count, channel) in current.clamped.iter_mut().zip([v.x, v.y, v.z]) {
+                if channel > 1.0 {
+                    *count += 1;
+                }
+            }
+            stats.set(Some(current));
+        }
+    });
+}
+
 /// Framebuffer que almacena los datos de color y profundidad de la imagen renderizada.
 pub struct Framebuffer {
     /// Ancho del framebuffer en píxeles.
@@ -44,6 +186,20 @@ pub struct Framebuffer {
     pub buffer: Vec<u8>,
     /// Búfer de profundidad (z-buffer) para pruebas de visibilidad.
     pub zbuffer: Vec<f32>,
+    /// Rectángulo de recorte `(x, y, ancho, alto)`; si existe, solo se escribe dentro de él.
+    pub scissor: Option<(usize, usize, usize, usize)>,
+    /// Búfer de geometría opcional, usado por efectos en espacio de pantalla como SSAO.
+    pub gbuffer: Option<GBuffer>,
+}
+
+/// Búfer de geometría (G-buffer): posición y normal en espacio mundo de cada píxel.
+///
+/// Solo es válido en los píxeles cuya profundidad no es infinita.
+pub struct GBuffer {
+    /// Posición en espacio mundo de la superficie visible en cada píxel.
+    pub positions: Vec<Vec3>,
+    /// Normal en espacio mundo de la superficie visible en cada píxel.
+    pub normals: Vec<Vec3>,
 }
 
 impl Framebuffer {
@@ -54,27 +210,173 @@ impl Framebuffer {
             height,
             buffer: vec![0; width * height * 4], // Inicializa el color a negro.
             zbuffer: vec![f32::INFINITY; width * height], // Inicializa la profundidad a infinito.
+            scissor: None,
+            gbuffer: None,
         }
     }
 
     /// Limpia el framebuffer, estableciendo todos los píxeles a un color y reseteando el z-buffer.
+    ///
+    /// Con la feature `parallel`, los framebuffers grandes (como los de supersampling) se
+    /// limpian con [`Framebuffer::clear_parallel`]; el resultado es idéntico.
     #[inline]
     pub fn clear(&mut self, color: Color) {
-        for i in 0..self.width * self.height {
-            let idx = i * 4;
-            self.buffer[idx] = color.r;
-            self.buffer[idx + 1] = color.g;
-            self.buffer[idx + 2] = color.b;
-            self.buffer[idx + 3] = 255; // Canal alfa opaco.
+        #[cfg(feature = "parallel")]
+        if self.width * self.height >= PARALLEL_CLEAR_THRESHOLD {
+            self.clear_parallel(color);
+            return;
+        }
+
+        let rgba = [color.r, color.g, color.b, color.a];
+        for pixel in self.buffer.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&rgba);
         }
         self.zbuffer.fill(f32::INFINITY); // Resetea la profundidad.
     }
 
-    /// Establece el color de un píxel (x, y) si pasa la prueba de profundidad.
+    /// Igual que [`Framebuffer::clear`], repartiendo las filas entre los hilos de rayon.
+    #[cfg(feature = "parallel")]
+    pub fn clear_parallel(&mut self, color: Color) {
+        if self.width == 0 {
+            return;
+        }
+        let rgba = [color.r, color.g, color.b, color.a];
+        self.buffer.par_chunks_mut(self.width * 4).for_each(|row| {
+            for pixel in row.chunks_exact_mut(4) {
+                pixel.copy_from_slice(&rgba);
+            }
+        });
+        self.zbuffer
+            .par_chunks_mut(self.width)
+            .for_each(|row| row.fill(f32::INFINITY));
+    }
+
+    /// Limpia el framebuffer con un degradado vertical de `top` (primera fila) a `bottom`
+    /// (última fila) y resetea la profundidad, igual que [`Framebuffer::clear`].
+    ///
+    /// El color se interpola por fila directamente sobre los valores almacenados, así que
+    /// la fila central es el promedio de ambos extremos.
+    pub fn clear_gradient(&mut self, top: Color, bottom: Color) {
+        if self.width == 0 {
+            return;
+        }
+        let rows = (self.height.max(2) - 1) as f32;
+        let lerp = |a: u8, b: u8, t: f32| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+
+        for (y, row) in self.buffer.chunks_exact_mut(self.width * 4).enumerate() {
+            let t = y as f32 / rows;
+            let color = [
+                lerp(top.r, bottom.r, t),
+                lerp(top.g, bottom.g, t),
+                lerp(top.b, bottom.b, t),
+                lerp(top.a, bottom.a, t),
+            ];
+            for pixel in row.chunks_exact_mut(4) {
+                pixel.copy_from_slice(&color);
+            }
+        }
+        self.zbuffer.fill(f32::INFINITY); // Resetea la profundidad.
+    }
+
+    /// Limpia el framebuffer copiando una imagen de fondo y resetea la profundidad, igual
+    /// que [`Framebuffer::clear`].
+    ///
+    /// La imagen se escala conservando su proporción hasta cubrir todo el framebuffer y se
+    /// centra, recortando lo que sobresale en un eje (ajuste tipo *cover*). Se muestrea su
+    /// nivel base con `filter`; en los bordes el filtrado bilineal no mezcla con el lado
+    /// opuesto de la imagen. El alfa queda opaco.
+    pub fn clear_image(&mut self, image: &Texture, filter: SampleFilter) {
+        if self.width == 0 {
+            return;
+        }
+        let (image_w, image_h) = (image.width() as f32, image.height() as f32);
+        let scale = (self.width as f32 / image_w).max(self.height as f32 / image_h);
+        // Desplazamiento (en píxeles) de la esquina de la imagen escalada respecto a la
+        // pantalla: negativo en el eje que se recorta.
+        let offset_x = (self.width as f32 - image_w * scale) * 0.5;
+        let offset_y = (self.height as f32 - image_h * scale) * 0.5;
+        let (margin_u, margin_v) = (0.5 / image_w, 0.5 / image_h);
+
+        for (y, row) in self.buffer.chunks_exact_mut(self.width * 4).enumerate() {
+            let v =
+                ((y as f32 + 0.5 - offset_y) / (image_h * scale)).clamp(margin_v, 1.0 - margin_v);
+            for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+                let u = ((x as f32 + 0.5 - offset_x) / (image_w * scale))
+                    .clamp(margin_u, 1.0 - margin_u);
+                // Redondea (en vez de truncar) para que una copia 1:1 conserve los bytes.
+                let color = image
+                    .sample(&Vec2::new(u, v), 0, filter)
+                    .map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+                pixel.copy_from_slice(&[color.x, color.y, color.z, 255]);
+            }
+        }
+        self.zbuffer.fill(f32::INFINITY); // Resetea la profundidad.
+    }
+
+    /// Limpia solo la región del rectángulo de recorte (o todo, si no hay recorte).
+    pub fn clear_scissor(&mut self, color: Color) {
+        let Some((x0, y0, x1, y1)) = self.scissor_bounds() else {
+            self.clear(color);
+            return;
+        };
+
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let index = y * self.width + x;
+                let idx = index * 4;
+                self.buffer[idx] = color.r;
+                self.buffer[idx + 1] = color.g;
+                self.buffer[idx + 2] = color.b;
+                self.buffer[idx + 3] = color.a;
+                self.zbuffer[index] = f32::INFINITY;
+            }
+        }
+    }
+
+    /// Límites `(x0, y0, x1, y1)` (exclusivos) del recorte, ajustados al tamaño del búfer.
     #[inline]
-    pub fn set_pixel(&mut self, x: usize, y: usize, color: Color, depth: f32) {
+    fn scissor_bounds(&self) -> Option<(usize, usize, usize, usize)> {
+        self.scissor.map(|(x, y, w, h)| {
+            (
+                x.min(self.width),
+                y.min(self.height),
+                x.saturating_add(w).min(self.width),
+                y.saturating_add(h).min(self.height),
+            )
+        })
+    }
+
+    /// Indica si el píxel (x, y) está dentro del búfer y del rectángulo de recorte.
+    #[inline]
+    pub(crate) fn writable(&self, x: usize, y: usize) -> bool {
         if x >= self.width || y >= self.height {
-            return; // Ignora coordenadas fuera de rango.
+            return false;
+        }
+        match self.scissor_bounds() {
+            Some((x0, y0, x1, y1)) => x >= x0 && x < x1 && y >= y0 && y < y1,
+            None => true,
+        }
+    }
+
+    /// Activa el G-buffer, de modo que el rasterizador guarde posición y normal por píxel.
+    pub fn enable_gbuffer(&mut self) {
+        let size = self.width * self.height;
+        self.gbuffer = Some(GBuffer {
+            positions: vec![Vec3::zeros(); size],
+            normals: vec![Vec3::zeros(); size],
+        });
+    }
+
+    /// Establece el color de un píxel (x, y) si pasa la prueba de profundidad.
+    ///
+    /// Los píxeles fuera del rectángulo de recorte no se modifican.
+    ///
+    /// # Retorna
+    /// `true` si el píxel se escribió.
+    #[inline]
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Color, depth: f32) -> bool {
+        if !self.writable(x, y) {
+            return false; // Ignora coordenadas fuera de rango o del recorte.
         }
 
         let index = y * self.width + x;
@@ -82,11 +384,188 @@ impl Framebuffer {
         // Solo dibuja si el nuevo píxel está más cerca que el anterior.
         if depth < self.zbuffer[index] {
             self.zbuffer[index] = depth;
-            let idx = index * 4;
-            self.buffer[idx] = color.r;
-            self.buffer[idx + 1] = color.g;
-            self.buffer[idx + 2] = color.b;
-            self.buffer[idx + 3] = 255;
+            store_pixel(&mut self.buffer[index * 4..index * 4 + 4], color);
+            return true;
+        }
+        false
+    }
+
+    /// Indica si un fragmento a profundidad `depth` pasaría la prueba de profundidad en
+    /// (x, y), sin escribir nada.
+    ///
+    /// Permite descartar un fragmento antes de ejecutar su shader.
+    #[inline]
+    pub fn depth_test(&self, x: usize, y: usize, depth: f32) -> bool {
+        self.writable(x, y) && depth < self.zbuffer[y * self.width + x]
+    }
+
+    /// Igual que [`Framebuffer::set_pixel`], pero además guarda la posición y la normal
+    /// del fragmento en el G-buffer si está activo.
+    #[inline]
+    pub fn set_fragment(
+        &mut self,
+        x: usize,
+        y: usize,
+        color: Color,
+        depth: f32,
+        world_pos: Vec3,
+        normal: Vec3,
+    ) {
+        if !self.set_pixel(x, y, color, depth) {
+            return;
+        }
+        if let Some(gbuffer) = &mut self.gbuffer {
+            let index = y * self.width + x;
+            gbuffer.positions[index] = world_pos;
+            gbuffer.normals[index] = normal;
+        }
+    }
+
+    /// Mezcla un color sobre un píxel de fondo según su cobertura, sin escribir profundidad.
+    ///
+    /// Solo actúa si ninguna geometría ocupa aún el píxel (profundidad infinita), de modo
+    /// que una superficie dibujada después lo sobrescribe normalmente.
+    #[inline]
+    pub fn blend_background(&mut self, x: usize, y: usize, color: Color, coverage: f32) {
+        if !self.writable(x, y) {
+            return;
+        }
+
+        let index = y * self.width + x;
+        if self.zbuffer[index] != f32::INFINITY {
+            return;
+        }
+        // La opacidad se mezcla hacia la del color, para que el borde sobre un fondo
+        // transparente quede parcialmente opaco.
+        let alpha = coverage.clamp(0.0, 1.0);
+        mix_pixel(
+            &mut self.buffer[index * 4..index * 4 + 4],
+            color,
+            alpha,
+            color.a as f32,
+        );
+    }
+
+    /// Suma `color`, ponderado por su opacidad, al píxel (x, y) si pasa la prueba de
+    /// profundidad, sin escribirla (mezcla aditiva).
+    ///
+    /// La suma se hace en espacio lineal y satura en blanco. La opacidad del píxel crece
+    /// como en el operador *over*, para que la capa se conserve sobre un fondo transparente.
+    #[inline]
+    pub fn blend_additive(&mut self, x: usize, y: usize, color: Color, depth: f32) {
+        if self.depth_test(x, y, depth) {
+            let idx = (y * self.width + x) * 4;
+            add_pixel(&mut self.buffer[idx..idx + 4], color);
+        }
+    }
+
+    /// Compone un color translúcido encima del píxel (x, y) con el operador *over*, si pasa
+    /// la prueba de profundidad, sin escribirla.
+    ///
+    /// Es la mezcla sin ordenar de [`BlendMode::Alpha`](crate::renderer::BlendMode::Alpha):
+    /// solo es correcta si los fragmentos llegan de atrás hacia adelante. Para capas que se
+    /// solapan conviene [`peel::render_peeled`](crate::peel::render_peeled).
+    pub fn blend_over(&mut self, x: usize, y: usize, color: Color, depth: f32) {
+        if self.depth_test(x, y, depth) {
+            let idx = (y * self.width + x) * 4;
+            mix_pixel(
+                &mut self.buffer[idx..idx + 4],
+                color,
+                color.a as f32 / 255.0,
+                255.0,
+            );
+        }
+    }
+
+    /// Rellena un triángulo 2D con un color sólido, sin prueba ni escritura de profundidad.
+    ///
+    /// Pensado para superponer elementos de interfaz (leyendas, indicadores de ejes) sin
+    /// pasar por el pipeline 3D. Un píxel se cubre si su centro cae dentro del triángulo,
+    /// y se respeta el rectángulo de recorte.
+    ///
+    /// # Argumentos
+    /// * `a`, `b`, `c` - Vértices en coordenadas de pantalla (píxeles).
+    /// * `color` - Color de relleno.
+    pub fn fill_triangle(&mut self, a: Vec2, b: Vec2, c: Vec2, color: Color) {
+        // Triángulos degenerados (área nula) o framebuffer vacío: nada que dibujar.
+        if (b - a).perp(&(c - a)).abs() < 1e-8 || self.width == 0 || self.height == 0 {
+            return;
+        }
+
+        // Caja delimitadora ajustada a la pantalla.
+        let min_x = a.x.min(b.x).min(c.x).floor().max(0.0) as usize;
+        let min_y = a.y.min(b.y).min(c.y).floor().max(0.0) as usize;
+        let max_x = (a.x.max(b.x).max(c.x).ceil().max(0.0) as usize).min(self.width - 1);
+        let max_y = (a.y.max(b.y).max(c.y).ceil().max(0.0) as usize).min(self.height - 1);
+
+        let setup = BarycentricSetup::new(&a, &b, &c);
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let p = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let (w0, w1, w2) = setup.weights(&p);
+
+                if w0 >= 0.0 && w1 >= 0.0 && w2 >= 0.0 && self.writable(x, y) {
+                    let idx = (y * self.width + x) * 4;
+                    store_pixel(&mut self.buffer[idx..idx + 4], color);
+                }
+            }
+        }
+    }
+
+    /// Divide el framebuffer en franjas de `rows` filas consecutivas (la última puede ser
+    /// más baja), cada una con acceso exclusivo a su color, profundidad y G-buffer.
+    ///
+    /// Las franjas no se solapan, así que pueden escribirse desde hilos distintos; todas
+    /// respetan el rectángulo de recorte. Un `rows` nulo se trata como 1.
+    pub fn bands_mut(&mut self, rows: usize) -> Vec<FramebufferBand<'_>> {
+        let rows = rows.max(1);
+        let (width, height) = (self.width, self.height);
+        let scissor = self.scissor_bounds();
+        let pixels = rows * width;
+        let (mut positions, mut normals) = match &mut self.gbuffer {
+            Some(gbuffer) => (
+                Some(gbuffer.positions.chunks_mut(pixels.max(1))),
+                Some(gbuffer.normals.chunks_mut(pixels.max(1))),
+            ),
+            None => (None, None),
+        };
+
+        self.buffer
+            .chunks_mut((pixels * 4).max(1))
+            .zip(self.zbuffer.chunks_mut(pixels.max(1)))
+            .enumerate()
+            .map(|(band, (buffer, zbuffer))| {
+                let start = band * rows;
+                let gbuffer = match (&mut positions, &mut normals) {
+                    (Some(positions), Some(normals)) => positions.next().zip(normals.next()),
+                    _ => None,
+                };
+                FramebufferBand {
+                    width,
+                    height,
+                    rows: start..(start + rows).min(height),
+                    scissor,
+                    buffer,
+                    zbuffer,
+                    gbuffer,
+                }
+            })
+            .collect()
+    }
+
+    /// Invierte verticalmente la imagen, intercambiando el orden de las filas en el sitio.
+    ///
+    /// Útil al exportar a formatos que esperan las filas de abajo hacia arriba (como BMP).
+    /// El z-buffer se invierte también para que siga alineado con el color.
+    pub fn flip_vertical(&mut self) {
+        let row_bytes = self.width * 4;
+        for y in 0..self.height / 2 {
+            let mirror = self.height - 1 - y;
+            let (top, bottom) = self.buffer.split_at_mut(mirror * row_bytes);
+            top[y * row_bytes..(y + 1) * row_bytes].swap_with_slice(&mut bottom[..row_bytes]);
+
+            let (top, bottom) = self.zbuffer.split_at_mut(mirror * self.width);
+            top[y * self.width..(y + 1) * self.width].swap_with_slice(&mut bottom[..self.width]);
         }
     }
 
@@ -94,4 +573,828 @@ impl Framebuffer {
     pub fn as_bytes(&self) -> &[u8] {
         &self.buffer
     }
+
+    /// Devuelve una copia del color en RGB compacto (`width * height * 3` bytes), sin el
+    /// canal alfa, para consumidores que no aceptan RGBA (p. ej. PPM).
+    ///
+    /// A diferencia de [`Framebuffer::as_bytes`], reserva un búfer nuevo en cada llamada.
+    pub fn as_rgb_bytes(&self) -> Vec<u8> {
+        self.buffer
+            .chunks_exact(4)
+            .flat_map(|pixel| [pixel[0], pixel[1], pixel[2]])
+            .collect()
+    }
+
+    /// Devuelve el z-buffer de solo lectura: una profundidad por píxel, ordenadas por filas.
+    ///
+    /// Los píxeles sin geometría guardan `f32::INFINITY`.
+    pub fn depth_slice(&self) -> &[f32] {
+        &self.zbuffer
+    }
+
+    /// Histograma de las profundidades escritas, en `bins` intervalos iguales de [-1, 1]
+    /// (Z en NDC).
+    ///
+    /// Ignora los píxeles sin geometría (profundidad no finita); las que quedan fuera del
+    /// rango, por ejemplo por `depth_bias`, se cuentan en el intervalo del extremo. Con
+    /// proyección en perspectiva casi todo se agrupa cerca de 1.0, lo que delata la pérdida
+    /// de precisión del z-buffer. Devuelve un vector vacío si `bins` es 0.
+    pub fn depth_histogram(&self, bins: usize) -> Vec<usize> {
+        let mut counts = vec![0; bins];
+        if bins == 0 {
+            return counts;
+        }
+        for depth in self.zbuffer.iter().filter(|depth| depth.is_finite()) {
+            let bin = ((depth + 1.0) * 0.5 * bins as f32).floor();
+            counts[(bin.max(0.0) as usize).min(bins - 1)] += 1;
+        }
+        counts
+    }
+}
+
+/// Escribe un color en un píxel RGBA de 4 bytes.
+#[inline]
+fn store_pixel(pixel: &mut [u8], color: Color) {
+    pixel[0] = color.r;
+    pixel[1] = color.g;
+    pixel[2] = color.b;
+    pixel[3] = color.a;
+}
+
+/// Mezcla un píxel RGBA hacia `color` en espacio lineal (para que los bordes no se
+/// oscurezcan), con peso `alpha`; la opacidad se mezcla hacia `target_alpha` (0-255).
+#[inline]
+fn mix_pixel(pixel: &mut [u8], color: Color, alpha: f32, target_alpha: f32) {
+    let current = Color::new(pixel[0], pixel[1], pixel[2]).to_linear();
+    let blended = Color::from_linear(current + (color.to_linear() - current) * alpha);
+    let current_alpha = pixel[3] as f32;
+    store_pixel(
+        pixel,
+        Color {
+            a: (current_alpha + (target_alpha - current_alpha) * alpha).round() as u8,
+            ..blended
+        },
+    );
+}
+
+/// Suma `color`, ponderado por su opacidad, a un píxel RGBA en espacio lineal. La
+/// opacidad del píxel crece como en el operador *over*.
+#[inline]
+fn add_pixel(pixel: &mut [u8], color: Color) {
+    let alpha = color.a as f32 / 255.0;
+    let current = Color::new(pixel[0], pixel[1], pixel[2]).to_linear();
+    let blended = Color::from_linear(current + color.to_linear() * alpha);
+    let current_alpha = pixel[3] as f32;
+    store_pixel(
+        pixel,
+        Color {
+            a: (current_alpha + (255.0 - current_alpha) * alpha).round() as u8,
+            ..blended
+        },
+    );
+}
+
+/// Destino genérico del renderizado: color con prueba de profundidad.
+///
+/// Permite que el `Renderer` dibuje en cualquier búfer (el framebuffer principal, un mapa
+/// de sombras, un búfer HDR...) sin estar atado a `Framebuffer`. Las dimensiones deben
+/// coincidir con las del `Renderer` que lo usa.
+pub trait RenderTarget {
+    /// Ancho del destino en píxeles.
+    fn width(&self) -> usize;
+
+    /// Alto del destino en píxeles.
+    fn height(&self) -> usize;
+
+    /// Escribe un píxel si pasa la prueba de profundidad; devuelve `true` si se escribió.
+    fn set_pixel(&mut self, x: usize, y: usize, color: Color, depth: f32) -> bool;
+
+    /// Prueba de profundidad anticipada: `false` si el fragmento seguro que se descartaría.
+    ///
+    /// El rasterizador la consulta antes de ejecutar el shader. Por defecto acepta todo, lo
+    /// que solo desactiva la optimización.
+    fn depth_test(&self, _x: usize, _y: usize, _depth: f32) -> bool {
+        true
+    }
+
+    /// Escribe un fragmento con su geometría. Por defecto descarta la posición y la normal.
+    fn set_fragment(
+        &mut self,
+        x: usize,
+        y: usize,
+        color: Color,
+        depth: f32,
+        _world_pos: Vec3,
+        _normal: Vec3,
+    ) {
+        self.set_pixel(x, y, color, depth);
+    }
+
+    /// Mezcla un color parcial sobre el fondo (bordes suavizados). Por defecto no hace nada.
+    fn blend_background(&mut self, _x: usize, _y: usize, _color: Color, _coverage: f32) {}
+
+    /// Suma un color translúcido si pasa la prueba de profundidad, sin escribirla
+    /// ([`BlendMode::Additive`](crate::renderer::BlendMode::Additive)). Por defecto no hace
+    /// nada, de modo que las capas translúcidas no afectan a los destinos de solo
+    /// profundidad.
+    fn blend_additive(&mut self, _x: usize, _y: usize, _color: Color, _depth: f32) {}
+
+    /// Compone un color translúcido con el operador *over*
+    /// ([`BlendMode::Alpha`](crate::renderer::BlendMode::Alpha)). Por defecto no hace nada,
+    /// igual que [`RenderTarget::blend_additive`].
+    fn blend_over(&mut self, _x: usize, _y: usize, _color: Color, _depth: f32) {}
+
+    /// Filas que el destino puede escribir; el rasterizador no recorre las demás.
+    fn rows(&self) -> Range<usize> {
+        0..self.height()
+    }
+
+    /// Divide el destino en franjas de filas independientes para rasterizarlas en
+    /// paralelo. Por defecto no se divide (lista vacía) y se dibuja en un solo hilo.
+    fn bands_mut(&mut self, _rows: usize) -> Vec<FramebufferBand<'_>> {
+        Vec::new()
+    }
+}
+
+impl RenderTarget for Framebuffer {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn set_pixel(&mut self, x: usize, y: usize, color: Color, depth: f32) -> bool {
+        Framebuffer::set_pixel(self, x, y, color, depth)
+    }
+
+    fn depth_test(&self, x: usize, y: usize, depth: f32) -> bool {
+        Framebuffer::depth_test(self, x, y, depth)
+    }
+
+    fn set_fragment(
+        &mut self,
+        x: usize,
+        y: usize,
+        color: Color,
+        depth: f32,
+        world_pos: Vec3,
+        normal: Vec3,
+    ) {
+        Framebuffer::set_fragment(self, x, y, color, depth, world_pos, normal);
+    }
+
+    fn blend_background(&mut self, x: usize, y: usize, color: Color, coverage: f32) {
+        Framebuffer::blend_background(self, x, y, color, coverage);
+    }
+
+    fn blend_additive(&mut self, x: usize, y: usize, color: Color, depth: f32) {
+        Framebuffer::blend_additive(self, x, y, color, depth);
+    }
+
+    fn blend_over(&mut self, x: usize, y: usize, color: Color, depth: f32) {
+        Framebuffer::blend_over(self, x, y, color, depth);
+    }
+
+    fn bands_mut(&mut self, rows: usize) -> Vec<FramebufferBand<'_>> {
+        Framebuffer::bands_mut(self, rows)
+    }
+}
+
+/// Franja de filas consecutivas de un [`Framebuffer`], obtenida con
+/// [`Framebuffer::bands_mut`].
+///
+/// Se direcciona con las coordenadas del framebuffer completo y descarta lo que cae fuera
+/// de sus filas, de modo que el renderizador la usa como un destino más. Las escrituras
+/// dan exactamente el mismo resultado que en el framebuffer.
+pub struct FramebufferBand<'a> {
+    /// Ancho del framebuffer completo en píxeles.
+    width: usize,
+    /// Alto del framebuffer completo en píxeles.
+    height: usize,
+    /// Filas del framebuffer cubiertas por la franja.
+    rows: Range<usize>,
+    /// Límites `(x0, y0, x1, y1)` del recorte del framebuffer, si lo hay.
+    scissor: Option<(usize, usize, usize, usize)>,
+    /// Color RGBA de las filas de la franja.
+    buffer: &'a mut [u8],
+    /// Profundidad de las filas de la franja.
+    zbuffer: &'a mut [f32],
+    /// Posiciones y normales del G-buffer de las filas de la franja, si está activo.
+    gbuffer: Option<(&'a mut [Vec3], &'a mut [Vec3])>,
+}
+
+impl FramebufferBand<'_> {
+    /// Índice local del píxel (x, y), o `None` si cae fuera de la franja o del recorte.
+    #[inline]
+    fn index(&self, x: usize, y: usize) -> Option<usize> {
+        let inside = match self.scissor {
+            Some((x0, y0, x1, y1)) => x >= x0 && x < x1 && y >= y0 && y < y1,
+            None => true,
+        };
+        if !inside || x >= self.width || !self.rows.contains(&y) {
+            return None;
+        }
+        Some((y - self.rows.start) * self.width + x)
+    }
+}
+
+impl RenderTarget for FramebufferBand<'_> {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn set_pixel(&mut self, x: usize, y: usize, color: Color, depth: f32) -> bool {
+        let Some(index) = self.index(x, y) else {
+            return false;
+        };
+        if depth < self.zbuffer[index] {
+            self.zbuffer[index] = depth;
+            store_pixel(&mut self.buffer[index * 4..index * 4 + 4], color);
+            return true;
+        }
+        false
+    }
+
+    fn depth_test(&self, x: usize, y: usize, depth: f32) -> bool {
+        self.index(x, y)
+            .is_some_and(|index| depth < self.zbuffer[index])
+    }
+
+    fn set_fragment(
+        &mut self,
+        x: usize,
+        y: usize,
+        color: Color,
+        depth: f32,
+        world_pos: Vec3,
+        normal: Vec3,
+    ) {
+        if !self.set_pixel(x, y, color, depth) {
+            return;
+        }
+        if let Some((positions, normals)) = &mut self.gbuffer {
+            let index = (y - self.rows.start) * self.width + x;
+            positions[index] = world_pos;
+            normals[index] = normal;
+        }
+    }
+
+    fn blend_background(&mut self, x: usize, y: usize, color: Color, coverage: f32) {
+        let Some(index) = self.index(x, y) else {
+            return;
+        };
+        if self.zbuffer[index] == f32::INFINITY {
+            let alpha = coverage.clamp(0.0, 1.0);
+            mix_pixel(
+                &mut self.buffer[index * 4..index * 4 + 4],
+                color,
+                alpha,
+                color.a as f32,
+            );
+        }
+    }
+
+    fn blend_additive(&mut self, x: usize, y: usize, color: Color, depth: f32) {
+        if RenderTarget::depth_test(self, x, y, depth) {
+            let idx = ((y - self.rows.start) * self.width + x) * 4;
+            add_pixel(&mut self.buffer[idx..idx + 4], color);
+        }
+    }
+
+    fn blend_over(&mut self, x: usize, y: usize, color: Color, depth: f32) {
+        if RenderTarget::depth_test(self, x, y, depth) {
+            let idx = ((y - self.rows.start) * self.width + x) * 4;
+            mix_pixel(
+                &mut self.buffer[idx..idx + 4],
+                color,
+                color.a as f32 / 255.0,
+                255.0,
+            );
+        }
+    }
+
+    fn rows(&self) -> Range<usize> {
+        self.rows.clone()
+    }
+}
+
+/// Framebuffer de alta precisión: guarda el color en `f32` durante el cuadro.
+///
+/// Expone la misma interfaz básica que [`Framebuffer`], pero solo cuantiza a 8 bits al
+/// llamar a [`HdrFramebuffer::resolve`]. Así las mezclas y el post-procesado no acumulan
+/// error de redondeo, y los degradados suaves no producen banding.
+pub struct HdrFramebuffer {
+    /// Ancho del framebuffer en píxeles.
+    pub width: usize,
+    /// Alto del framebuffer en píxeles.
+    pub height: usize,
+    /// Color de cada píxel en punto flotante (puede superar 1.0 antes de resolver).
+    pub buffer: Vec<Vec3>,
+    /// Búfer de profundidad (z-buffer) para pruebas de visibilidad.
+    pub zbuffer: Vec<f32>,
+}
+
+impl HdrFramebuffer {
+    /// Crea un framebuffer de alta precisión en negro con las dimensiones especificadas.
+    pub fn new(width: usize, height: usize) -> Self {
+        HdrFramebuffer {
+            width,
+            height,
+            buffer: vec![Vec3::zeros(); width * height],
+            zbuffer: vec![f32::INFINITY; width * height],
+        }
+    }
+
+    /// Limpia el framebuffer a un color y resetea el z-buffer.
+    pub fn clear(&mut self, color: Vec3) {
+        self.buffer.fill(color);
+        self.zbuffer.fill(f32::INFINITY);
+    }
+
+    /// Establece el color de un píxel (x, y) si pasa la prueba de profundidad.
+    ///
+    /// # Retorna
+    /// `true` si el píxel se escribió.
+    #[inline]
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Vec3, depth: f32) -> bool {
+        if x >= self.width || y >= self.height {
+            return false; // Ignora coordenadas fuera de rango.
+        }
+
+        let index = y * self.width + x;
+        if depth < self.zbuffer[index] {
+            self.zbuffer[index] = depth;
+            self.buffer[index] = color;
+            return true;
+        }
+        false
+    }
+
+    /// Suma un color al píxel (x, y) sin prueba de profundidad (mezcla aditiva).
+    #[inline]
+    pub fn add_pixel(&mut self, x: usize, y: usize, color: Vec3) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        self.buffer[y * self.width + x] += color;
+    }
+
+    /// Cuantiza el color a RGBA de 8 bits, en el mismo formato que [`Framebuffer::as_bytes`].
+    pub fn resolve(&self) -> Vec<u8> {
+        self.buffer
+            .iter()
+            .flat_map(|&color| {
+                let color = Color::from_vec3(color);
+                [color.r, color.g, color.b, 255]
+            })
+            .collect()
+    }
+}
+
+impl RenderTarget for HdrFramebuffer {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn set_pixel(&mut self, x: usize, y: usize, color: Color, depth: f32) -> bool {
+        HdrFramebuffer::set_pixel(self, x, y, color.to_vec3(), depth)
+    }
+
+    fn depth_test(&self, x: usize, y: usize, depth: f32) -> bool {
+        x < self.width && y < self.height && depth < self.zbuffer[y * self.width + x]
+    }
+
+    fn blend_additive(&mut self, x: usize, y: usize, color: Color, depth: f32) {
+        if RenderTarget::depth_test(self, x, y, depth) {
+            self.add_pixel(x, y, color.to_vec3() * (color.a as f32 / 255.0));
+        }
+    }
+
+    fn blend_over(&mut self, x: usize, y: usize, color: Color, depth: f32) {
+        if RenderTarget::depth_test(self, x, y, depth) {
+            let index = y * self.width + x;
+            let alpha = color.a as f32 / 255.0;
+            self.buffer[index] = self.buffer[index].lerp(&color.to_vec3(), alpha);
+        }
+    }
+}
+
+/// Búfer de acumulación en punto flotante para pasadas emisivas (brillos).
+///
+/// A diferencia de [`Framebuffer`], solo suma: no hay prueba de profundidad ni saturación,
+/// de modo que los brillos superpuestos se acumulan antes de un único mapeo de tonos y
+/// cuantizado al resolver.
+pub struct AccumBuffer {
+    /// Ancho del búfer en píxeles.
+    pub width: usize,
+    /// Alto del búfer en píxeles.
+    pub height: usize,
+    /// Color acumulado (HDR) de cada píxel.
+    pub buffer: Vec<Vec3>,
+}
+
+impl AccumBuffer {
+    /// Crea un búfer de acumulación en negro con las dimensiones especificadas.
+    pub fn new(width: usize, height: usize) -> Self {
+        AccumBuffer {
+            width,
+            height,
+            buffer: vec![Vec3::zeros(); width * height],
+        }
+    }
+
+    /// Reinicia la acumulación a negro.
+    pub fn clear(&mut self) {
+        self.buffer.fill(Vec3::zeros());
+    }
+
+    /// Suma un color al píxel (x, y).
+    #[inline]
+    pub fn add(&mut self, x: usize, y: usize, color: Vec3) {
+        if x >= self.width || y >= self.height {
+            return; // Ignora coordenadas fuera de rango.
+        }
+        self.buffer[y * self.width + x] += color;
+    }
+
+    /// Aplica el mapeo de tonos a lo acumulado y lo suma sobre el contenido del framebuffer.
+    ///
+    /// Los píxeles sin acumulación se dejan intactos; el resto se compone de forma aditiva
+    /// (en espacio lineal) sobre la imagen ya renderizada, saturando en 1.0.
+    ///
+    /// # Argumentos
+    /// * `fb` - Framebuffer destino, de las mismas dimensiones.
+    /// * `tonemap` - Función que lleva el color HDR acumulado al rango [0.0, 1.0].
+    pub fn resolve_into(&self, fb: &mut Framebuffer, tonemap: fn(Vec3) -> Vec3) {
+        let width = self.width.min(fb.width);
+        let height = self.height.min(fb.height);
+
+        for y in 0..height {
+            for x in 0..width {
+                let accumulated = self.buffer[y * self.width + x];
+                if accumulated == Vec3::zeros() {
+                    continue;
+                }
+
+                let idx = (y * fb.width + x) * 4;
+                // La luz se suma en espacio lineal y se vuelve a codificar en sRGB.
+                let current = Color::new(fb.buffer[idx], fb.buffer[idx + 1], fb.buffer[idx + 2]);
+                let color = Color::from_linear(current.to_linear() + tonemap(accumulated));
+                fb.buffer[idx] = color.r;
+                fb.buffer[idx + 1] = color.g;
+                fb.buffer[idx + 2] = color.b;
+            }
+        }
+    }
+}
+
+/// Mapeo de tonos de Reinhard (`c / (1 + c)`), útil como argumento de
+/// [`AccumBuffer::resolve_into`].
+pub fn reinhard(color: Vec3) -> Vec3 {
+    color.component_div(&(color.add_scalar(1.0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::texture::{SampleFilter, Texture};
+
+    /// Canales RGB del píxel (x, y).
+    fn rgb(fb: &Framebuffer, x: usize, y: usize) -> [u8; 3] {
+        let idx = (y * fb.width + x) * 4;
+        [fb.buffer[idx], fb.buffer[idx + 1], fb.buffer[idx + 2]]
+    }
+
+    #[test]
+    fn accum_buffer_sums_glows_before_tonemapping() {
+        let mut accum = AccumBuffer::new(4, 4);
+        accum.add(1, 1, Vec3::new(1.0, 0.0, 0.0));
+        accum.add(1, 1, Vec3::new(1.0, 0.0, 0.0));
+        accum.add(9, 9, Vec3::new(1.0, 1.0, 1.0)); // Fuera de rango: se ignora.
+        assert_eq!(accum.buffer[4 + 1], Vec3::new(2.0, 0.0, 0.0));
+
+        let mut fb = Framebuffer::new(4, 4);
+        fb.clear(Color::new(0, 0, 40));
+        accum.resolve_into(&mut fb, reinhard);
+        // Reinhard de 2.0 es 2/3 lineal, sumado sobre el fondo y codificado en sRGB.
+        assert_eq!(rgb(&fb, 1, 1), [213, 0, 40]);
+        assert_eq!(rgb(&fb, 0, 0), [0, 0, 40], "sin acumulación queda intacto");
+    }
+
+    #[test]
+    fn flip_vertical_swaps_rows_and_depth() {
+        let mut fb = Framebuffer::new(2, 3);
+        fb.clear(Color::new(0, 0, 0));
+        fb.set_pixel(0, 0, Color::new(255, 0, 0), 0.1);
+        fb.set_pixel(1, 1, Color::new(0, 255, 0), 0.2);
+
+        fb.flip_vertical();
+        assert_eq!(rgb(&fb, 0, 2), [255, 0, 0]);
+        assert_eq!(fb.zbuffer[2 * 2], 0.1);
+        assert_eq!(rgb(&fb, 0, 0), [0, 0, 0]);
+        assert_eq!(rgb(&fb, 1, 1), [0, 255, 0], "la fila central no se mueve");
+    }
+
+    #[test]
+    fn scissor_limits_writes_and_clears() {
+        let mut fb = Framebuffer::new(4, 4);
+        fb.clear(Color::new(0, 0, 0));
+        fb.scissor = Some((1, 1, 2, 2));
+
+        fb.set_pixel(0, 0, Color::new(255, 0, 0), 0.5);
+        fb.set_pixel(2, 2, Color::new(255, 0, 0), 0.5);
+        assert_eq!(rgb(&fb, 0, 0), [0, 0, 0]);
+        assert_eq!(fb.zbuffer[0], f32::INFINITY);
+        assert_eq!(rgb(&fb, 2, 2), [255, 0, 0]);
+
+        fb.clear_scissor(Color::new(0, 0, 255));
+        assert_eq!(rgb(&fb, 2, 2), [0, 0, 255]);
+        assert_eq!(rgb(&fb, 3, 3), [0, 0, 0], "fuera del recorte no se limpia");
+    }
+
+    #[test]
+    fn fill_triangle_covers_pixel_centers_without_depth() {
+        let mut fb = Framebuffer::new(8, 8);
+        fb.clear(Color::new(0, 0, 0));
+        // Mitad inferior izquierda del búfer.
+        fb.fill_triangle(
+            Vec2::new(0.0, 0.0),
+            Vec2::new(0.0, 8.0),
+            Vec2::new(8.0, 8.0),
+            Color::new(255, 255, 0),
+        );
+
+        assert_eq!(rgb(&fb, 1, 6), [255, 255, 0]);
+        assert_eq!(rgb(&fb, 6, 1), [0, 0, 0]);
+        assert!(fb.zbuffer.iter().all(|&z| z == f32::INFINITY));
+    }
+
+    #[test]
+    fn clamp_stats_count_saturated_channels_per_frame() {
+        assert_eq!(take_clamp_stats(), None, "desactivado por defecto");
+
+        set_clamp_stats_enabled(true);
+        Color::from_vec3(Vec3::new(1.5, 0.5, 0.0));
+        Color::from_vec3(Vec3::new(2.0, 1.0, 3.0));
+        Color::from_vec3(Vec3::new(0.2, 0.2, 0.2));
+        Color::from_vec3(Vec3::new(0.2, 0.2, 0.2));
+
+        let stats = take_clamp_stats().unwrap();
+        assert_eq!(stats.samples, 4);
+        assert_eq!(stats.clamped, [2, 0, 1]);
+        assert_eq!(stats.percentages(), [50.0, 0.0, 25.0]);
+
+        // Tomar el recuento lo reinicia para el siguiente cuadro.
+        assert_eq!(take_clamp_stats(), Some(ClampStats::default()));
+        set_clamp_stats_enabled(false);
+        Color::from_vec3(Vec3::new(5.0, 5.0, 5.0));
+        assert_eq!(take_clamp_stats(), None);
+    }
+
+    #[test]
+    fn hdr_framebuffer_keeps_precision_until_resolve() {
+        let mut hdr = HdrFramebuffer::new(2, 1);
+        hdr.clear(Vec3::zeros());
+
+        // Dos aportes menores que un escalón de 8 bits suman uno completo al resolver.
+        let tiny = Vec3::repeat(0.6 / 255.0);
+        hdr.add_pixel(0, 0, tiny);
+        hdr.add_pixel(0, 0, tiny);
+        assert_eq!(Color::from_vec3(tiny).r, 0);
+
+        assert!(hdr.set_pixel(1, 0, Vec3::new(3.0, 0.5, 0.0), 0.5));
+        assert!(
+            !hdr.set_pixel(1, 0, Vec3::zeros(), 0.9),
+            "más lejos: se descarta"
+        );
+        assert_eq!(hdr.buffer[1].x, 3.0, "sin saturar antes de resolver");
+
+        assert_eq!(hdr.resolve(), [1, 1, 1, 255, 255, 127, 0, 255]);
+    }
+
+    #[test]
+    fn srgb_round_trips_and_blends_in_linear_space() {
+        for value in 0..=255u8 {
+            let color = Color::new(value, value, value);
+            assert_eq!(Color::from_linear(color.to_linear()).r, value);
+        }
+        assert!((srgb_to_linear(0.5) - 0.214).abs() < 1e-3);
+
+        // La mitad de cobertura entre negro y blanco es la mitad de la luz, no del código.
+        let mut fb = Framebuffer::new(1, 1);
+        fb.clear(Color::new(0, 0, 0));
+        fb.zbuffer[0] = f32::INFINITY;
+        fb.blend_background(0, 0, Color::new(255, 255, 255), 0.5);
+        assert_eq!(rgb(&fb, 0, 0), [188, 188, 188]);
+    }
+
+    #[test]
+    fn clear_gradient_interpolates_rows() {
+        let mut fb = Framebuffer::new(2, 5);
+        fb.set_pixel(0, 0, Color::new(9, 9, 9), 0.1);
+        fb.clear_gradient(Color::new(0, 0, 0), Color::new(200, 100, 40));
+
+        assert_eq!(rgb(&fb, 1, 0), [0, 0, 0]);
+        assert_eq!(
+            rgb(&fb, 0, 2),
+            [100, 50, 20],
+            "la fila central es el promedio"
+        );
+        assert_eq!(rgb(&fb, 1, 4), [200, 100, 40]);
+        assert!(fb.zbuffer.iter().all(|&z| z == f32::INFINITY));
+    }
+
+    #[test]
+    fn rgb_bytes_drop_the_alpha_channel() {
+        let mut fb = Framebuffer::new(2, 1);
+        fb.clear(Color::new(1, 2, 3));
+        fb.set_pixel(1, 0, Color::new(4, 5, 6), 0.5);
+        assert_eq!(fb.as_rgb_bytes(), [1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn alpha_is_stored_and_blended() {
+        let mut fb = Framebuffer::new(2, 1);
+        fb.clear(Color::rgba(0, 0, 0, 0));
+        assert_eq!(fb.buffer[3], 0, "fondo transparente");
+
+        fb.set_pixel(0, 0, Color::rgba(10, 20, 30, 128), 0.5);
+        assert_eq!(fb.buffer[..4], [10, 20, 30, 128]);
+
+        fb.blend_background(1, 0, Color::new(255, 255, 255), 0.5);
+        assert_eq!(fb.buffer[7], 128, "el borde queda parcialmente opaco");
+        assert_eq!(Color::new(1, 2, 3).a, 255);
+    }
+
+    #[test]
+    fn additive_blend_sums_in_front_without_writing_depth() {
+        let mut fb = Framebuffer::new(2, 1);
+        fb.clear(Color::new(0, 0, 0));
+        fb.set_pixel(0, 0, Color::new(255, 0, 0), 0.5);
+
+        fb.blend_additive(0, 0, Color::rgba(0, 0, 255, 255), 0.2);
+        assert_eq!(rgb(&fb, 0, 0), [255, 0, 255]);
+        assert_eq!(fb.zbuffer[0], 0.5, "la capa aditiva no ocupa el z-buffer");
+
+        // Por detrás de la superficie no aporta nada.
+        fb.blend_additive(0, 0, Color::rgba(0, 255, 0, 255), 0.9);
+        assert_eq!(rgb(&fb, 0, 0), [255, 0, 255]);
+
+        // La opacidad pondera la suma, en espacio lineal.
+        fb.blend_additive(1, 0, Color::rgba(0, 0, 255, 128), 0.5);
+        fb.blend_additive(1, 0, Color::rgba(0, 0, 255, 128), 0.5);
+        assert_eq!(rgb(&fb, 1, 0), [0, 0, 255]);
+        fb.clear(Color::new(0, 0, 0));
+        fb.blend_additive(1, 0, Color::rgba(0, 0, 255, 128), 0.5);
+        assert_eq!(rgb(&fb, 1, 0), [0, 0, 188]);
+    }
+
+    #[test]
+    fn depth_histogram_bins_written_depths_and_clamps_extremes() {
+        let mut fb = Framebuffer::new(3, 2);
+        fb.clear(Color::new(0, 0, 0));
+        fb.set_pixel(0, 0, Color::new(1, 1, 1), -0.9);
+        fb.set_pixel(1, 0, Color::new(1, 1, 1), 0.1);
+        fb.set_pixel(2, 0, Color::new(1, 1, 1), 0.99);
+        fb.set_pixel(0, 1, Color::new(1, 1, 1), -3.0);
+
+        assert_eq!(fb.depth_slice().len(), 6);
+        assert_eq!(fb.depth_slice()[1], 0.1);
+        assert_eq!(fb.depth_slice()[5], f32::INFINITY);
+
+        // Intervalos de 0.5: [-1, -0.5), [-0.5, 0), [0, 0.5), [0.5, 1].
+        assert_eq!(fb.depth_histogram(4), [2, 0, 1, 1]);
+        assert!(fb.depth_histogram(0).is_empty());
+    }
+
+    #[test]
+    fn clear_image_copies_one_to_one_and_crops_to_cover() {
+        // Copia 1:1: los bytes se conservan con ambos filtros y el alfa queda opaco.
+        let rgba = [10, 20, 30, 0, 200, 100, 50, 0, 0, 255, 0, 0, 7, 8, 9, 0];
+        let image = Texture::from_rgba8(2, 2, &rgba);
+        let mut fb = Framebuffer::new(2, 2);
+        for filter in [SampleFilter::Nearest, SampleFilter::Bilinear] {
+            fb.set_pixel(0, 0, Color::new(1, 1, 1), 0.5);
+            fb.clear_image(&image, filter);
+            assert_eq!(
+                fb.buffer,
+                [
+                    10, 20, 30, 255, 200, 100, 50, 255, 0, 255, 0, 255, 7, 8, 9, 255
+                ]
+            );
+            assert!(fb.zbuffer.iter().all(|d| d.is_infinite()));
+        }
+
+        // Una imagen de 4x2 sobre un cuadro de 2x2 se recorta por los lados: quedan sus dos
+        // columnas centrales.
+        let columns = [[255, 0, 0], [0, 255, 0], [0, 0, 255], [255, 255, 255]];
+        let rgba: Vec<u8> = (0..8)
+            .flat_map(|i| {
+                let [r, g, b] = columns[i % 4];
+                [r, g, b, 255]
+            })
+            .collect();
+        fb.clear_image(&Texture::from_rgba8(4, 2, &rgba), SampleFilter::Nearest);
+        assert_eq!(rgb(&fb, 0, 1), [0, 255, 0]);
+        assert_eq!(rgb(&fb, 1, 0), [0, 0, 255]);
+    }
+
+    #[test]
+    fn clear_fills_every_pixel_and_resets_depth() {
+        // 600x500 supera el umbral de la limpieza en paralelo; 7x3 no.
+        for (width, height) in [(7, 3), (600, 500)] {
+            let mut fb = Framebuffer::new(width, height);
+            fb.set_pixel(width - 1, height - 1, Color::new(9, 9, 9), 0.25);
+            fb.clear(Color::rgba(1, 2, 3, 4));
+            assert!(fb.buffer.chunks_exact(4).all(|p| p == [1, 2, 3, 4]));
+            assert_eq!(fb.buffer.len(), width * height * 4);
+            assert!(fb.zbuffer.iter().all(|d| d.is_infinite()));
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_clear_matches_serial_clear() {
+        let mut serial = Framebuffer::new(33, 17);
+        let mut parallel = Framebuffer::new(33, 17);
+        for fb in [&mut serial, &mut parallel] {
+            fb.set_pixel(5, 5, Color::new(9, 9, 9), 0.25);
+        }
+        serial.clear(Color::rgba(200, 100, 50, 25));
+        parallel.clear_parallel(Color::rgba(200, 100, 50, 25));
+        assert_eq!(serial.buffer, parallel.buffer);
+        assert_eq!(serial.zbuffer, parallel.zbuffer);
+    }
+
+    #[test]
+    fn blend_over_mixes_in_linear_space_without_writing_depth() {
+        let mut fb = Framebuffer::new(2, 1);
+        fb.clear(Color::new(0, 0, 0));
+        fb.set_pixel(0, 0, Color::new(0, 0, 255), 0.5);
+
+        // Opaco por delante: reemplaza el color pero no ocupa el z-buffer.
+        fb.blend_over(0, 0, Color::rgba(255, 0, 0, 255), 0.2);
+        assert_eq!(rgb(&fb, 0, 0), [255, 0, 0]);
+        assert_eq!(fb.zbuffer[0], 0.5);
+        // Por detrás de la superficie no aporta nada.
+        fb.blend_over(0, 0, Color::rgba(0, 255, 0, 255), 0.9);
+        assert_eq!(rgb(&fb, 0, 0), [255, 0, 0]);
+
+        // Con media opacidad sobre negro queda la mitad de la luz, no la mitad del byte.
+        fb.blend_over(1, 0, Color::rgba(255, 0, 0, 128), 0.5);
+        assert_eq!(rgb(&fb, 1, 0), [188, 0, 0]);
+        assert!(fb.zbuffer[1].is_infinite());
+    }
+
+    #[test]
+    fn bands_split_rows_and_write_like_the_full_framebuffer() {
+        let mut full = Framebuffer::new(5, 7);
+        let mut banded = Framebuffer::new(5, 7);
+        for fb in [&mut full, &mut banded] {
+            fb.clear(Color::new(0, 0, 0));
+            fb.scissor = Some((1, 1, 3, 5));
+        }
+
+        let mut bands = banded.bands_mut(2);
+        let rows: Vec<_> = bands.iter().map(|band| band.rows()).collect();
+        assert_eq!(
+            rows,
+            [0..2, 2..4, 4..6, 6..7],
+            "la última franja es más baja"
+        );
+
+        // Cada franja acepta solo sus filas, con el recorte y la profundidad del original.
+        let writes = [
+            (2, 1, 0.5),
+            (2, 1, 0.7),
+            (3, 3, 0.2),
+            (0, 3, 0.2),
+            (2, 6, 0.1),
+        ];
+        for band in &mut bands {
+            for &(x, y, depth) in &writes {
+                let inside = band.rows().contains(&y);
+                let written = band.set_pixel(x, y, Color::new(255, 0, 0), depth);
+                assert!(!written || inside);
+                band.blend_additive(x, y, Color::rgba(0, 0, 255, 128), depth - 0.1);
+            }
+        }
+        for &(x, y, depth) in &writes {
+            full.set_pixel(x, y, Color::new(255, 0, 0), depth);
+            full.blend_additive(x, y, Color::rgba(0, 0, 255, 128), depth - 0.1);
+        }
+        assert_eq!(banded.buffer, full.buffer);
+        assert_eq!(banded.zbuffer, full.zbuffer);
+        assert_eq!(rgb(&banded, 2, 1), [255, 0, 188]);
+    }
 }
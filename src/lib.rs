@@ -0,0 +1,22 @@
+//! Star Shader Renderer - Biblioteca del pipeline de renderizado por software.
+//
+// Este archivo expone los módulos que no dependen de raylib (mallas, renderer, framebuffer,
+// shaders y post-procesado), de modo que el pipeline pueda usarse sin ventana: el binario
+// interactivo y los ejemplos sin interfaz comparten exactamente el mismo código.
+
+pub mod camera;
+pub mod clock;
+pub mod framebuffer;
+pub mod mesh;
+pub mod peel;
+pub mod png;
+pub mod postprocess;
+pub mod raster;
+pub mod renderer;
+pub mod scene;
+pub mod shaders;
+pub mod shadow;
+pub mod skybox;
+#[cfg(test)]
+mod testutil;
+pub mod texture;
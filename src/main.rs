@@ -3,52 +3,119 @@
 // Este archivo inicializa la ventana, recursos y ciclo principal de renderizado para mostrar
 // una esfera 3D con diferentes shaders de estrellas, permitiendo interacción en tiempo real.
 
-mod framebuffer;
-mod mesh;
-mod renderer;
-mod shaders;
-
-use framebuffer::{Color, Framebuffer};
-use mesh::ObjMesh;
-use nalgebra_glm::{Mat4, Vec3, look_at, perspective, rotate};
+use lab5::camera::CameraPath;
+use lab5::clock::Clock;
+use lab5::framebuffer::{Framebuffer, set_clamp_stats_enabled, take_clamp_stats};
+use lab5::mesh::{MeshError, ObjMesh};
+use lab5::postprocess;
+use lab5::renderer::{BlendMode, Renderer, ShadingMode};
+use lab5::scene::{FrameOptions, RenderObject, SceneCamera, render_frame};
+use lab5::shaders::*;
+use lab5::skybox::Skybox;
+use nalgebra_glm::Vec3;
 use raylib::prelude::*;
-use renderer::Renderer;
-use shaders::*;
+use std::rc::Rc;
 
 /// Dimensiones de la ventana de renderizado.
 const WIDTH: usize = 800;
 const HEIGHT: usize = 600;
+/// Campo de visión vertical de la cámara, en grados.
+const FOV_Y_DEG: f32 = 60.0;
+/// Límites y paso del multiplicador de exposición ajustable con `-` y `=`.
+const MIN_EXPOSURE: f32 = 0.1;
+const MAX_EXPOSURE: f32 = 4.0;
+const EXPOSURE_STEP: f32 = 0.1;
+/// Índice de la supernova en `create_shader`, que se acompaña de su onda de choque.
+const SUPERNOVA_SHADER: usize = 3;
+/// Nombre del objeto de la onda de choque, usado para quitarlo al cambiar de shader.
+const SHOCKWAVE_NAME: &str = "Onda de choque";
+/// Avance por segundo de la transición hacia la supernova mientras se mantiene `J` o `K`.
+const MORPH_SPEED: f32 = 0.5;
+/// Índice del siguiente objeto seleccionado al recorrer una escena de `count` objetos.
+///
+/// El recorrido pasa por cada objeto en orden y, tras el último, vuelve a "ninguno"
+/// antes de empezar de nuevo por el primero.
+fn next_selection(current: Option<usize>, count: usize) -> Option<usize> {
+    match current {
+        None if count > 0 => Some(0),
+        Some(index) if index + 1 < count => Some(index + 1),
+        _ => None,
+    }
+}
 
-/// Estructura que representa un objeto renderizable con malla, shader y transformaciones.
-struct RenderObject {
-    mesh: ObjMesh,
-    shader: Box<dyn StarShader>,
-    position: Vec3,
-    scale: f32,
+/// Líneas de la interfaz con los parámetros de cámara y la rotación de la estrella, para
+/// poder anotar y reproducir un encuadre (el tiempo de simulación ya aparece en otra línea).
+///
+/// La rotación acumulada es `sim_time * rotation_speed`, mostrada en grados dentro de [0, 360).
+fn camera_readout(
+    camera_distance: f32,
+    fov_deg: f32,
+    orthographic: bool,
     rotation_speed: f32,
-    rotation_axis: Vec3,
+    sim_time: f32,
+) -> [String; 2] {
+    let projection = if orthographic { "orto" } else { "persp" };
+    let angle = (sim_time * rotation_speed).to_degrees().rem_euclid(360.0);
+    [
+        format!(
+            "Cámara: {:.2} u | FOV {:.0}° ({})",
+            camera_distance, fov_deg, projection
+        ),
+        format!("Giro: {:.2} rad/s | Ángulo: {:.1}°", rotation_speed, angle),
+    ]
+}
+
+/// Crea el shader de estrella correspondiente al índice seleccionado.
+///
+/// `octaves` solo afecta a los shaders basados en turbulencia (sol clásico, supernova y
+/// sol con protuberancias); se ajusta al rango [`MIN_OCTAVES`, `MAX_OCTAVES`].
+fn create_shader(shader_type: usize, octaves: i32) -> Box<dyn StarShader> {
+    match shader_type {
+        0 => Box::new(ClassicSunShader::new(octaves)),
+        1 => Box::new(PulsarShader::default()),
+        2 => Box::new(PlasmaStarShader::default()),
+        SUPERNOVA_SHADER => Box::new(SupernovaShader::new(octaves)),
+        4 => Box::new(ProminenceSunShader::new(octaves)),
+        _ => Box::new(ClassicSunShader::new(octaves)),
+    }
 }
 
-impl RenderObject {
-    /// Crea un nuevo objeto renderizable.
-    fn new(mesh: ObjMesh, shader: Box<dyn StarShader>, position: Vec3, scale: f32) -> Self {
-        RenderObject {
-            mesh,
+/// Shader de la estrella principal: el seleccionado, fundido con la supernova según `morph`
+/// y con la semilla de ruido `seed`.
+///
+/// Con `morph` igual a 0.0 (o si la supernova ya es la seleccionada) devuelve el shader tal
+/// cual, sin el coste de evaluar dos shaders por fragmento. Todas las reconstrucciones del
+/// shader pasan por aquí, así que la semilla elegida con E se conserva.
+fn create_star_shader(
+    shader_type: usize,
+    octaves: i32,
+    morph: f32,
+    seed: u32,
+) -> Box<dyn StarShader> {
+    let mut shader = create_shader(shader_type, octaves);
+    if morph > 0.0 && shader_type != SUPERNOVA_SHADER {
+        shader = Box::new(BlendShader::new(
             shader,
-            position,
-            scale,
-            rotation_speed: 0.3,
-            rotation_axis: Vec3::new(0.0, 1.0, 0.0),
-        }
+            create_shader(SUPERNOVA_SHADER, octaves),
+            morph,
+        ));
     }
+    shader.set_seed(seed);
+    shader
+}
 
-    /// Calcula la matriz de modelo (transformación) animada por tiempo.
-    fn get_model_matrix(&self, time: f32) -> Mat4 {
-        let mut transform = Mat4::identity();
-        transform = nalgebra_glm::translate(&transform, &self.position);
-        transform = rotate(&transform, time * self.rotation_speed, &self.rotation_axis);
-        transform = nalgebra_glm::scale(&transform, &Vec3::new(self.scale, self.scale, self.scale));
-        transform
+/// Malla de la estrella principal: la cargada de `sphere.obj` si `use_obj` y existe, o la
+/// esfera procedural en otro caso.
+///
+/// Solo incrementa el contador de referencias; los vértices no se copian al alternar con M.
+fn select_sphere(
+    procedural: &Rc<ObjMesh>,
+    obj: Option<&Rc<ObjMesh>>,
+    use_obj: bool,
+) -> Rc<ObjMesh> {
+    match obj {
+        Some(mesh) if use_obj => Rc::clone(mesh),
+        _ => Rc::clone(procedural),
     }
 }
 
@@ -66,13 +133,21 @@ fn main() {
 
     // Genera una esfera procedural por defecto.
     println!("Generando esfera procedural...");
-    let sphere_mesh = ObjMesh::create_sphere(1.0, 64, 64);
+    let sphere_mesh = Rc::new(ObjMesh::create_sphere(1.0, 64, 64));
 
     // Intenta cargar una esfera desde archivo OBJ.
     let obj_sphere = match ObjMesh::load_from_obj("assets/sphere.obj") {
-        Ok(mesh) => {
+        Ok(mut mesh) => {
+            // El OBJ gira en sentido antihorario; se invierte para compartir el sentido de
+            // las caras frontales (`renderer.front_face`) con la esfera procedural.
+            mesh.flip_winding(false);
             println!("✓ sphere.obj cargado exitosamente");
-            Some(mesh)
+            Some(Rc::new(mesh))
+        }
+        // Que falte el archivo es normal (es opcional); cualquier otro error indica un OBJ dañado.
+        Err(MeshError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+            println!("  sphere.obj no encontrado, usando esfera procedural");
+            None
         }
         Err(e) => {
             println!("⚠ No se pudo cargar sphere.obj: {}", e);
@@ -83,43 +158,124 @@ fn main() {
 
     let mut use_obj_model = obj_sphere.is_some();
 
-    // Función para obtener la malla de esfera actual (procedural u OBJ).
-    let get_sphere = |use_obj: bool| -> ObjMesh {
-        if use_obj && obj_sphere.is_some() {
-            obj_sphere.as_ref().unwrap().clone()
-        } else {
-            sphere_mesh.clone()
+    // Intenta cargar el recorrido de cámara para las demostraciones.
+    let camera_path = match std::fs::read_to_string("assets/camera_path.txt")
+        .map_err(|e| e.to_string())
+        .and_then(|text| CameraPath::parse(&text))
+    {
+        Ok(path) => {
+            println!(
+                "✓ camera_path.txt cargado ({} fotogramas)",
+                path.keyframes().len()
+            );
+            Some(path)
+        }
+        Err(e) => {
+            println!("⚠ No se pudo cargar camera_path.txt: {}", e);
+            None
         }
     };
 
-    // Crea un objeto estrella con el shader seleccionado.
-    let create_star = |use_obj: bool, shader_type: usize| -> RenderObject {
-        let current_sphere = get_sphere(use_obj);
-
-        let shader: Box<dyn StarShader> = match shader_type {
-            0 => Box::new(ClassicSunShader),
-            1 => Box::new(PulsarShader),
-            2 => Box::new(PlasmaStarShader),
-            3 => Box::new(SupernovaShader),
-            _ => Box::new(ClassicSunShader),
-        };
-
-        RenderObject::new(current_sphere, shader, Vec3::new(0.0, 0.0, 0.0), 1.5)
+    // Intenta cargar una imagen de fondo (foto, nebulosa...) para usarla en lugar del degradado.
+    let backplate = match Image::load_image("assets/background.png") {
+        Ok(image) => {
+            let rgba: Vec<u8> = image
+                .get_image_data()
+                .iter()
+                .flat_map(|c| [c.r, c.g, c.b, c.a])
+                .collect();
+            let (width, height) = (image.width() as usize, image.height() as usize);
+            println!("✓ background.png cargado ({}x{})", width, height);
+            Some(lab5::texture::Texture::from_rgba8(width, height, &rgba))
+        }
+        Err(e) => {
+            println!("⚠ No se pudo cargar background.png: {}", e);
+            None
+        }
     };
+    let mut show_backplate = backplate.is_some();
+
+    // Función para obtener la malla de esfera actual (procedural u OBJ).
+    let get_sphere = |use_obj: bool| select_sphere(&sphere_mesh, obj_sphere.as_ref(), use_obj);
 
     // Nombres de los shaders disponibles.
-    let shader_names = vec![
+    let shader_names = [
         "1: Sol Clásico (Perlin + Turbulence)",
         "2: Pulsar (Simplex + Pulsación)",
         "3: Estrella de Plasma (Cellular + Vortex)",
         "4: Supernova (Multi-layer + Flare)",
+        "5: Sol con Protuberancias (Vertex + Perlin)",
     ];
 
+    // Teclas que seleccionan cada shader, en el mismo orden que `shader_names`.
+    let shader_keys = [
+        KeyboardKey::KEY_ONE,
+        KeyboardKey::KEY_TWO,
+        KeyboardKey::KEY_THREE,
+        KeyboardKey::KEY_FOUR,
+        KeyboardKey::KEY_FIVE,
+    ];
+
+    // Crea un objeto estrella con el shader seleccionado.
+    let create_star = |use_obj: bool,
+                       shader_type: usize,
+                       rotation_speed: f32,
+                       octaves: i32,
+                       seed: u32,
+                       morph: f32| {
+        let current_sphere = get_sphere(use_obj);
+        let shader = create_star_shader(shader_type, octaves, morph, seed);
+
+        RenderObject::new(current_sphere, shader, Vec3::new(0.0, 0.0, 0.0), 1.5)
+            .with_rotation_speed(rotation_speed)
+            .with_name(shader_names[shader_type])
+    };
+
+    // Cáscara translúcida que se expande alrededor de la supernova, sumada sobre el núcleo.
+    let create_shockwave = || {
+        RenderObject::new(
+            Rc::clone(&sphere_mesh),
+            Box::new(ShockwaveShader::default()),
+            Vec3::new(0.0, 0.0, 0.0),
+            1.5,
+        )
+        .with_rotation_speed(0.0)
+        .with_blend_mode(BlendMode::Additive)
+        .with_name(SHOCKWAVE_NAME)
+    };
+
     let mut current_shader = 0;
-    let mut star = create_star(use_obj_model, current_shader);
+    let mut rotation_speed = 0.3f32;
+    let mut octaves = DEFAULT_OCTAVES;
+    let mut seed = 0u32;
+    // Avance de la transición del shader seleccionado hacia la supernova, en [0.0, 1.0].
+    let mut morph = 0.0f32;
+
+    // Escena: la estrella principal (índice 0, controlada con el teclado) y una compañera
+    // más pequeña con otra semilla de ruido.
+    let mut scene = vec![
+        create_star(
+            use_obj_model,
+            current_shader,
+            rotation_speed,
+            octaves,
+            seed,
+            morph,
+        ),
+        RenderObject::new(
+            Rc::clone(&sphere_mesh),
+            Box::new(ClassicSunShader::default()),
+            Vec3::new(2.4, 0.9, -1.5),
+            0.35,
+        )
+        .with_rotation_speed(0.8)
+        .with_seed(7)
+        .with_name(shader_names[0]),
+    ];
+    let mut selection: Option<usize> = None;
 
     let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
-    let renderer = Renderer::new(WIDTH, HEIGHT);
+    let mut renderer = Renderer::new(WIDTH, HEIGHT);
 
     // Crea la textura inicial para mostrar el framebuffer en pantalla.
     println!("Creando textura...");
@@ -131,52 +287,100 @@ fn main() {
         .expect("No se pudo crear textura");
 
     // Variables de control de animación y cámara.
-    let mut paused = false;
-    let mut paused_time = 0.0f32;
-    let mut last_active_time = 0.0f32;
-    let mut camera_distance = 3.5f32;
+    let mut clock = Clock::default();
+    let mut camera_distance = scene[0].shader.suggested_camera_distance();
+    let mut orthographic = false;
+    let mut play_camera_path = false;
+    let mut wireframe = false;
+    let mut show_normals = false;
+
+    // Filtros de post-procesado aplicados al framebuffer.
+    let mut grayscale = false;
+    let mut invert = false;
+    let mut dither = false;
+    let mut fxaa = false;
+    let mut ambient_occlusion = false;
+
+    // Fondo de entorno opcional detrás de la estrella.
+    let skybox = Skybox::default();
+    let mut show_skybox = false;
+
+    // Registro de canales saturados (depuración de shaders).
+    let mut clamp_stats_enabled = false;
 
     println!("=== Entrando al loop principal ===\n");
     println!("Controles:");
-    println!("  1-4: Cambiar shader");
+    println!("  1-5: Cambiar shader");
     println!("  M: Toggle modelo .obj / procedural");
+    println!("  F: Toggle sombreado plano / suave");
+    println!("  T: Toggle sombreado a dos caras (sin descarte de caras traseras)");
+    println!("  O: Toggle proyección ortográfica / perspectiva");
+    println!("  P: Toggle recorrido de cámara (assets/camera_path.txt)");
+    println!("  W: Toggle aristas (wireframe) sobre la superficie");
+    println!("  V: Toggle normales de los vértices");
+    println!("  TAB: Seleccionar el siguiente objeto (resalta sus aristas)");
+    println!("  U: Ocultar / mostrar el objeto seleccionado");
+    println!("  A: Toggle suavizado de bordes (silueta)");
+    println!("  G: Toggle filtro de escala de grises");
+    println!("  I: Toggle filtro de inversión de color");
+    println!("  D: Toggle dithering ordenado (anti-banding)");
+    println!("  X: Toggle suavizado FXAA de pantalla completa");
+    println!("  S: Toggle oclusión ambiental (SSAO)");
+    println!("  C: Toggle reporte de canales saturados");
+    println!("  B: Toggle fondo de entorno (skybox)");
+    println!("  H: Toggle imagen de fondo (assets/background.png)");
+    println!("  E: Cambiar semilla del ruido");
+    println!("  - / =: Reducir / aumentar la exposición");
     println!("  SPACE: Pausar");
+    println!("  N: Avanzar un cuadro (en pausa)");
+    println!("  , / .: Ralentizar / acelerar el tiempo");
     println!("  UP/DOWN: Zoom cámara");
+    println!("  LEFT/RIGHT: Velocidad de rotación");
+    println!("  [ / ]: Octavas de turbulencia");
+    println!("  J / K: Revertir / avanzar la transición hacia la supernova (mantener)");
     println!("  ESC: Salir\n");
 
     // Ciclo principal de la aplicación.
     while !rl.window_should_close() {
-        let current_real_time = rl.get_time() as f32;
-
-        // Calcula el tiempo de animación considerando pausa.
-        let time = if paused {
-            paused_time
-        } else {
-            last_active_time + (current_real_time - last_active_time)
-        };
-
-        // Cambia el shader activo según la tecla presionada.
-        if rl.is_key_pressed(KeyboardKey::KEY_ONE) {
-            current_shader = 0;
-            star = create_star(use_obj_model, current_shader);
-        }
-        if rl.is_key_pressed(KeyboardKey::KEY_TWO) {
-            current_shader = 1;
-            star = create_star(use_obj_model, current_shader);
-        }
-        if rl.is_key_pressed(KeyboardKey::KEY_THREE) {
-            current_shader = 2;
-            star = create_star(use_obj_model, current_shader);
-        }
-        if rl.is_key_pressed(KeyboardKey::KEY_FOUR) {
-            current_shader = 3;
-            star = create_star(use_obj_model, current_shader);
+        let dt = rl.get_frame_time();
+
+        // Cambia el shader activo según la tecla presionada y ajusta el zoom al encuadre
+        // sugerido por el nuevo shader.
+        for (index, key) in shader_keys.iter().enumerate() {
+            if rl.is_key_pressed(*key) {
+                current_shader = index;
+                scene[0] = create_star(
+                    use_obj_model,
+                    current_shader,
+                    rotation_speed,
+                    octaves,
+                    seed,
+                    morph,
+                );
+                camera_distance = scene[0].shader.suggested_camera_distance();
+
+                scene.retain(|object| object.name != SHOCKWAVE_NAME);
+                if current_shader == SUPERNOVA_SHADER {
+                    scene.push(create_shockwave());
+                }
+                selection = selection.filter(|&index| index < scene.len());
+                for (index, object) in scene.iter_mut().enumerate() {
+                    object.selected = selection == Some(index);
+                }
+            }
         }
 
         // Alterna entre modelo procedural y OBJ si está disponible.
         if rl.is_key_pressed(KeyboardKey::KEY_M) && obj_sphere.is_some() {
             use_obj_model = !use_obj_model;
-            star = create_star(use_obj_model, current_shader);
+            scene[0] = create_star(
+                use_obj_model,
+                current_shader,
+                rotation_speed,
+                octaves,
+                seed,
+                morph,
+            );
             println!(
                 "Cambiando a: {}",
                 if use_obj_model {
@@ -187,18 +391,165 @@ fn main() {
             );
         }
 
-        // Control de pausa de animación.
-        if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
-            if paused {
-                let pause_duration = current_real_time - paused_time;
-                last_active_time = current_real_time - pause_duration;
-                paused = false;
+        // Alterna entre sombreado suave y plano (normales por cara).
+        if rl.is_key_pressed(KeyboardKey::KEY_F) {
+            renderer.shading_mode = match renderer.shading_mode {
+                ShadingMode::Smooth => ShadingMode::Flat,
+                ShadingMode::Flat => ShadingMode::Smooth,
+            };
+        }
+
+        // Activa o desactiva el sombreado a dos caras; las caras traseras solo se ven si no
+        // se descartan.
+        if rl.is_key_pressed(KeyboardKey::KEY_T) {
+            renderer.two_sided = !renderer.two_sided;
+            renderer.cull_backfaces = !renderer.two_sided;
+        }
+
+        // Alterna entre proyección en perspectiva y ortográfica.
+        if rl.is_key_pressed(KeyboardKey::KEY_O) {
+            orthographic = !orthographic;
+        }
+
+        // Reproduce el recorrido de cámara, si se cargó.
+        if rl.is_key_pressed(KeyboardKey::KEY_P) && camera_path.is_some() {
+            play_camera_path = !play_camera_path;
+        }
+
+        // Dibuja las normales de los vértices como segmentos (depuración de modelos).
+        if rl.is_key_pressed(KeyboardKey::KEY_V) {
+            show_normals = !show_normals;
+        }
+
+        // Recorre la selección de objetos; el seleccionado se resalta con sus aristas.
+        if rl.is_key_pressed(KeyboardKey::KEY_TAB) {
+            selection = next_selection(selection, scene.len());
+            for (index, object) in scene.iter_mut().enumerate() {
+                object.selected = selection == Some(index);
+            }
+        }
+
+        // Oculta o muestra el objeto seleccionado sin quitarlo de la escena, para aislar
+        // el shader de otro.
+        if rl.is_key_pressed(KeyboardKey::KEY_U)
+            && let Some(object) = selection.and_then(|index| scene.get_mut(index))
+        {
+            object.visible = !object.visible;
+        }
+
+        // Dibuja las aristas de la malla sobre la superficie sombreada.
+        if rl.is_key_pressed(KeyboardKey::KEY_W) {
+            wireframe = !wireframe;
+        }
+
+        // Activa o desactiva el suavizado de la silueta.
+        if rl.is_key_pressed(KeyboardKey::KEY_A) {
+            renderer.edge_aa = !renderer.edge_aa;
+        }
+
+        // Activa o desactiva los filtros de post-procesado.
+        if rl.is_key_pressed(KeyboardKey::KEY_G) {
+            grayscale = !grayscale;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_I) {
+            invert = !invert;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_D) {
+            dither = !dither;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_X) {
+            fxaa = !fxaa;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_S) {
+            // El SSAO necesita el G-buffer, que solo se mantiene mientras está activo.
+            ambient_occlusion = !ambient_occlusion;
+            if ambient_occlusion {
+                framebuffer.enable_gbuffer();
             } else {
-                paused_time = time;
-                paused = true;
+                framebuffer.gbuffer = None;
             }
         }
 
+        if rl.is_key_pressed(KeyboardKey::KEY_B) {
+            show_skybox = !show_skybox;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_H) && backplate.is_some() {
+            show_backplate = !show_backplate;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_C) {
+            clamp_stats_enabled = !clamp_stats_enabled;
+            set_clamp_stats_enabled(clamp_stats_enabled);
+        }
+
+        // Escala de tiempo: permite estudiar ciclos lentos o acelerar la animación.
+        if rl.is_key_pressed(KeyboardKey::KEY_PERIOD) {
+            clock.adjust_scale(0.25);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_COMMA) {
+            clock.adjust_scale(-0.25);
+        }
+
+        // Exposición global: atenúa o realza la salida de todos los shaders.
+        if rl.is_key_pressed(KeyboardKey::KEY_MINUS) {
+            renderer.exposure = (renderer.exposure - EXPOSURE_STEP).max(MIN_EXPOSURE);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_EQUAL) {
+            renderer.exposure = (renderer.exposure + EXPOSURE_STEP).min(MAX_EXPOSURE);
+        }
+
+        // Cambia la semilla del ruido para ver otra variante del mismo shader.
+        if rl.is_key_pressed(KeyboardKey::KEY_E) {
+            seed = seed.wrapping_add(1);
+            scene[0].shader.set_seed(seed);
+        }
+
+        // Control de pausa de animación.
+        if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
+            clock.toggle_pause();
+        }
+        // Con la animación en pausa, avanza un único cuadro.
+        if clock.paused && rl.is_key_pressed(KeyboardKey::KEY_N) {
+            clock.step();
+        }
+
+        // Control de la velocidad de rotación de la estrella.
+        if rl.is_key_down(KeyboardKey::KEY_RIGHT) {
+            rotation_speed = (rotation_speed + 0.01).min(3.0);
+            scene[0].rotation_speed = rotation_speed;
+        }
+        if rl.is_key_down(KeyboardKey::KEY_LEFT) {
+            rotation_speed = (rotation_speed - 0.01).max(-3.0);
+            scene[0].rotation_speed = rotation_speed;
+        }
+
+        // Ajusta las octavas de turbulencia (detalle frente a rendimiento).
+        let octave_step = if rl.is_key_pressed(KeyboardKey::KEY_RIGHT_BRACKET) {
+            1
+        } else if rl.is_key_pressed(KeyboardKey::KEY_LEFT_BRACKET) {
+            -1
+        } else {
+            0
+        };
+        if octave_step != 0 {
+            octaves = (octaves + octave_step).clamp(MIN_OCTAVES, MAX_OCTAVES);
+            scene[0].shader = create_star_shader(current_shader, octaves, morph, seed);
+        }
+
+        // Transforma gradualmente la estrella en una supernova (K) o la devuelve a su
+        // shader original (J).
+        let morph_step = if rl.is_key_down(KeyboardKey::KEY_K) {
+            MORPH_SPEED * dt
+        } else if rl.is_key_down(KeyboardKey::KEY_J) {
+            -MORPH_SPEED * dt
+        } else {
+            0.0
+        };
+        let next_morph = (morph + morph_step).clamp(0.0, 1.0);
+        if next_morph != morph {
+            morph = next_morph;
+            scene[0].shader = create_star_shader(current_shader, octaves, morph, seed);
+        }
+
         // Control de zoom de cámara.
         if rl.is_key_down(KeyboardKey::KEY_UP) {
             camera_distance -= 0.02;
@@ -209,42 +560,60 @@ fn main() {
             camera_distance = camera_distance.min(10.0);
         }
 
-        if !paused {
-            last_active_time = time;
+        // Calcula el tiempo de animación considerando pausa.
+        let time = clock.tick(dt);
+
+        // Dibuja la escena en el framebuffer.
+        let mut scene_camera = SceneCamera::orbit(camera_distance, FOV_Y_DEG, orthographic);
+        // Durante la reproducción, el recorrido (en bucle) sustituye a la cámara orbital.
+        let path_pose = camera_path
+            .as_ref()
+            .filter(|_| play_camera_path)
+            .and_then(|path| path.sample(time.rem_euclid(path.duration().max(f32::EPSILON))));
+        if let Some((eye, target)) = path_pose {
+            scene_camera.eye = eye;
+            scene_camera.target = target;
         }
-
-        // Matriz de vista de la cámara (orbita alrededor del origen).
-        let view_matrix = look_at(
-            &Vec3::new(0.0, 0.0, camera_distance),
-            &Vec3::new(0.0, 0.0, 0.0),
-            &Vec3::new(0.0, 1.0, 0.0),
-        );
-
-        // Matriz de proyección perspectiva.
-        let projection_matrix = perspective(
-            WIDTH as f32 / HEIGHT as f32,
-            60.0_f32.to_radians(),
-            0.1,
-            100.0,
-        );
-
-        // Limpia el framebuffer con un color de fondo.
-        framebuffer.clear(Color::new(5, 5, 15));
-
-        // Calcula la transformación del modelo animada.
-        let model_matrix = star.get_model_matrix(time);
-
-        // Renderiza la malla de la estrella con el shader seleccionado.
-        renderer.render_mesh(
+        let frame_options = FrameOptions {
+            wireframe,
+            show_normals,
+            skybox: show_skybox.then_some(&skybox),
+            backplate: backplate.as_ref().filter(|_| show_backplate),
+        };
+        let frame_stats = render_frame(
+            &renderer,
             &mut framebuffer,
-            &star.mesh,
-            star.shader.as_ref(),
-            &model_matrix,
-            &view_matrix,
-            &projection_matrix,
+            &scene,
+            &scene_camera,
             time,
+            &frame_options,
         );
 
+        // Recoge la saturación de este cuadro antes de que el post-procesado convierta más colores.
+        let clamp_report = take_clamp_stats();
+
+        // Aplica los filtros de post-procesado activos. La supernova separa los canales
+        // de color hacia los bordes para reforzar la sensación de energía extrema.
+        if ambient_occlusion {
+            postprocess::ssao(&mut framebuffer, 6, 0.8);
+        }
+        // El FXAA va antes de los filtros de color, que alterarían la luminancia de los bordes.
+        if fxaa {
+            postprocess::fxaa(&mut framebuffer);
+        }
+        if current_shader == SUPERNOVA_SHADER {
+            postprocess::chromatic_aberration(&mut framebuffer, 4.0);
+        }
+        if grayscale {
+            postprocess::grayscale(&mut framebuffer);
+        }
+        if invert {
+            postprocess::invert(&mut framebuffer);
+        }
+        if dither {
+            postprocess::dither(&mut framebuffer);
+        }
+
         // Actualiza la textura de Raylib con el framebuffer generado.
         if let Err(e) = texture.update_texture(framebuffer.as_bytes()) {
             eprintln!("Error actualizando textura: {:?}", e);
@@ -258,9 +627,18 @@ fn main() {
 
         d.draw_fps(10, 10);
 
-        let status = if paused { " [PAUSADO]" } else { "" };
+        let status = if clock.paused { " [PAUSADO]" } else { "" };
+        let playback = if play_camera_path { " [RECORRIDO]" } else { "" };
+        let morph_label = if morph > 0.0 && current_shader != SUPERNOVA_SHADER {
+            format!(" → Supernova {:.0}%", morph * 100.0)
+        } else {
+            String::new()
+        };
         d.draw_text(
-            &format!("{}{}", shader_names[current_shader], status),
+            &format!(
+                "{}{}{}{}",
+                shader_names[current_shader], morph_label, status, playback
+            ),
             10,
             35,
             20,
@@ -273,13 +651,107 @@ fn main() {
             "Modelo: Procedural"
         };
         d.draw_text(mesh_type, 10, 60, 16, raylib::color::Color::YELLOW);
+        d.draw_text(
+            &format!(
+                "Octavas: {} | Tiempo: x{:.2} ({:.3} s) | Semilla: {} | Exposición: x{:.1}",
+                octaves, clock.time_scale, clock.sim_time, seed, renderer.exposure
+            ),
+            10,
+            85,
+            16,
+            raylib::color::Color::YELLOW,
+        );
+
+        // Parámetros de cámara y rotación, alineados a la derecha de la ventana.
+        let readout = camera_readout(
+            camera_distance,
+            FOV_Y_DEG,
+            orthographic,
+            scene[0].rotation_speed,
+            clock.sim_time,
+        );
+        for (line, text) in readout.iter().enumerate() {
+            let width = d.measure_text(text, 16);
+            d.draw_text(
+                text,
+                WIDTH as i32 - width - 10,
+                10 + 25 * line as i32,
+                16,
+                raylib::color::Color::SKYBLUE,
+            );
+        }
+
+        // Objetos dibujados y ocultos del último cuadro, bajo los datos de cámara.
+        let scene_text = format!(
+            "Objetos: {} ({} ocultos) | Triángulos: {}",
+            frame_stats.drawn_objects, frame_stats.skipped_objects, frame_stats.triangles
+        );
+        let scene_text_width = d.measure_text(&scene_text, 16);
+        d.draw_text(
+            &scene_text,
+            WIDTH as i32 - scene_text_width - 10,
+            10 + 25 * readout.len() as i32,
+            16,
+            raylib::color::Color::SKYBLUE,
+        );
+
+        if let Some(object) = selection.and_then(|index| scene.get(index)) {
+            d.draw_text(
+                &format!(
+                    "Seleccionado: {}/{} - {}{}",
+                    selection.unwrap_or(0) + 1,
+                    scene.len(),
+                    object.name,
+                    if object.visible { "" } else { " (oculto)" }
+                ),
+                10,
+                135,
+                16,
+                raylib::color::Color::GOLD,
+            );
+        }
+
+        if let Some(stats) = clamp_report {
+            let [r, g, b] = stats.percentages();
+            d.draw_text(
+                &format!("Saturación R/G/B: {:.1}% / {:.1}% / {:.1}%", r, g, b),
+                10,
+                110,
+                16,
+                raylib::color::Color::ORANGE,
+            );
+        }
 
         let controls = if obj_sphere.is_some() {
-            "1-4: Shaders | M: Modelo | SPACE: Pausa | up/down: Zoom | ESC: Salir"
+            "1-5: Shaders | M: Modelo | SPACE: Pausa | N: Paso | up/down: Zoom | left/right: Giro | [ ]: Octavas | , .: Tiempo | ESC: Salir"
+        } else {
+            "1-5: Shaders | SPACE: Pausa | N: Paso | up/down: Zoom | left/right: Giro | [ ]: Octavas | , .: Tiempo | ESC: Salir"
+        };
+        let render_controls = if camera_path.is_some() {
+            "F: Plano | T: 2 caras | O: Orto | P: Recorrido | W: Aristas | V: Normales | TAB: Selección | U: Ocultar | A: AA bordes | J K: Supernova"
         } else {
-            "1-4: Shaders | SPACE: Pausa | up/down: Zoom | ESC: Salir"
+            "F: Plano | T: 2 caras | O: Orto | W: Aristas | V: Normales | TAB: Selección | U: Ocultar | A: AA bordes | J K: Supernova"
+        };
+        let filter_controls = if backplate.is_some() {
+            "G: Grises | I: Invertir | D: Dither | X: FXAA | S: SSAO | C: Saturación | B: Fondo | H: Imagen | E: Semilla | - =: Exposición"
+        } else {
+            "G: Grises | I: Invertir | D: Dither | X: FXAA | S: SSAO | C: Saturación | B: Fondo | E: Semilla | - =: Exposición"
         };
 
+        d.draw_text(
+            filter_controls,
+            10,
+            HEIGHT as i32 - 65,
+            14,
+            raylib::color::Color::LIGHTGRAY,
+        );
+        d.draw_text(
+            render_controls,
+            10,
+            HEIGHT as i32 - 45,
+            14,
+            raylib::color::Color::LIGHTGRAY,
+        );
         d.draw_text(
             controls,
             10,
@@ -291,3 +763,116 @@ fn main() {
 
     println!("=== Cerrando aplicación ===");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::{FRAC_PI_2, TAU};
+
+    #[test]
+    fn selection_cycles_through_objects_then_none() {
+        let mut selection = None;
+        let mut visited = Vec::new();
+        for _ in 0..4 {
+            selection = next_selection(selection, 3);
+            visited.push(selection);
+        }
+        assert_eq!(visited, [Some(0), Some(1), Some(2), None]);
+        assert_eq!(next_selection(None, 0), None, "escena vacía");
+    }
+
+    #[test]
+    fn camera_readout_formats_projection_and_wrapped_angle() {
+        let [camera, spin] = camera_readout(3.5, 60.0, false, 0.5, (TAU + FRAC_PI_2) / 0.5);
+        assert_eq!(camera, "Cámara: 3.50 u | FOV 60° (persp)");
+        assert_eq!(spin, "Giro: 0.50 rad/s | Ángulo: 90.0°");
+
+        let [camera, _] = camera_readout(10.0, 45.0, true, 0.0, 0.0);
+        assert!(camera.ends_with("(orto)"), "{camera}");
+    }
+
+    #[test]
+    fn toggling_the_model_shares_the_loaded_meshes() {
+        let procedural = Rc::new(ObjMesh::create_sphere(1.0, 8, 8));
+        let obj = Rc::new(ObjMesh::create_sphere(1.0, 4, 4));
+        let vertices = (procedural.vertices.as_ptr(), obj.vertices.as_ptr());
+
+        // Ida y vuelta con M: cada objeto recibe la misma malla, no una copia.
+        for use_obj in [true, false, true, false] {
+            let object = RenderObject::new(
+                select_sphere(&procedural, Some(&obj), use_obj),
+                Box::new(ClassicSunShader::default()),
+                Vec3::zeros(),
+                1.5,
+            );
+            let expected = if use_obj { &obj } else { &procedural };
+            assert!(Rc::ptr_eq(&object.mesh, expected), "use_obj = {use_obj}");
+            assert_eq!(Rc::strong_count(expected), 2);
+        }
+        assert_eq!(
+            Rc::strong_count(&procedural),
+            1,
+            "el objeto suelta su referencia"
+        );
+        assert_eq!(
+            (procedural.vertices.as_ptr(), obj.vertices.as_ptr()),
+            vertices
+        );
+
+        // Sin `sphere.obj` siempre se usa la procedural.
+        assert!(Rc::ptr_eq(
+            &select_sphere(&procedural, None, true),
+            &procedural
+        ));
+    }
+
+    /// Colores del shader en varias direcciones de la esfera unitaria.
+    fn samples(shader: &dyn StarShader) -> Vec<[u8; 3]> {
+        (0..64)
+            .map(|i| {
+                let (theta, phi) = (i as f32 * 0.7, i as f32 * 0.31);
+                let dir = Vec3::new(theta.cos() * phi.sin(), phi.cos(), theta.sin() * phi.sin());
+                let color = shader.fragment(&FragmentInput {
+                    world_pos: dir,
+                    object_pos: dir,
+                    normal: dir,
+                    view_dir: Vec3::z(),
+                    tangent: nalgebra_glm::Vec4::zeros(),
+                    time: 1.0,
+                    view_depth: 3.0,
+                    dpdx: Vec3::zeros(),
+                    dpdy: Vec3::zeros(),
+                    object_dpdx: Vec3::zeros(),
+                    object_dpdy: Vec3::zeros(),
+                    uv: nalgebra_glm::Vec2::zeros(),
+                    duvdx: nalgebra_glm::Vec2::zeros(),
+                    duvdy: nalgebra_glm::Vec2::zeros(),
+                    barycentric: Vec3::new(1.0, 0.0, 0.0),
+                    triangle_id: 0,
+                });
+                [color.r, color.g, color.b]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rebuilt_star_shader_keeps_the_seed() {
+        // Cambiar las octavas o el avance del morph reconstruye el shader con la semilla.
+        for (octaves, morph) in [
+            (DEFAULT_OCTAVES, 0.0),
+            (MAX_OCTAVES, 0.0),
+            (DEFAULT_OCTAVES, 0.5),
+        ] {
+            let rebuilt = create_star_shader(0, octaves, morph, 5);
+            let mut expected = create_star_shader(0, octaves, morph, 0);
+            let unseeded = samples(expected.as_ref());
+            expected.set_seed(5);
+            assert_eq!(samples(rebuilt.as_ref()), samples(expected.as_ref()));
+            assert_ne!(
+                samples(rebuilt.as_ref()),
+                unseeded,
+                "{octaves} octavas, morph {morph}"
+            );
+        }
+    }
+}
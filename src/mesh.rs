@@ -2,16 +2,78 @@
 //
 // Este archivo define las estructuras y funciones necesarias para la generación procedural de esferas y la carga de modelos desde archivos OBJ.
 
-use nalgebra_glm::Vec3; // Vector 3D de la biblioteca nalgebra_glm.
+use nalgebra_glm::{Vec2, Vec3, Vec4}; // Vectores de la biblioteca nalgebra_glm.
+use std::collections::{HashMap, HashSet}; // Agrupación de vértices que comparten posición.
 use std::f32::consts::PI; // Constante PI para cálculos trigonométricos.
+use std::fmt; // Mensajes de error legibles.
+use std::fs::File; // Apertura de archivos OBJ.
+use std::io::{self, BufRead, BufReader}; // Lectura de OBJ desde archivos o memoria.
+use std::path::Path; // Rutas relativas de las bibliotecas de materiales.
 
-/// Representa un vértice en el espacio 3D, incluyendo su posición y normal.
-#[derive(Debug, Clone)]
+/// Tolerancia para considerar que dos vértices ocupan la misma posición.
+const WELD_EPSILON: f32 = 1e-5;
+
+/// Error al cargar o validar una malla.
+#[derive(Debug)]
+pub enum MeshError {
+    /// No se pudo abrir o leer el archivo (no existe, sin permisos...).
+    Io(io::Error),
+    /// El contenido no es un OBJ válido; incluye la descripción del analizador.
+    Parse(String),
+    /// El archivo no contiene ningún modelo con caras.
+    Empty,
+    /// Los índices no describen triángulos completos sobre vértices existentes.
+    InvalidIndex(String),
+    /// La escala pedida al cargar no es positiva y finita.
+    InvalidScale(f32),
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeshError::Io(e) => write!(f, "Could not read OBJ file: {}", e),
+            MeshError::Parse(message) => write!(f, "Error loading OBJ: {}", message),
+            MeshError::Empty => write!(f, "No models found in OBJ file"),
+            MeshError::InvalidIndex(message) => write!(f, "Invalid mesh indices: {}", message),
+            MeshError::InvalidScale(scale) => write!(f, "Invalid OBJ scale: {}", scale),
+        }
+    }
+}
+
+impl std::error::Error for MeshError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MeshError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<tobj::LoadError> for MeshError {
+    /// Las referencias de caras fuera de rango son errores de índices; el resto, de formato.
+    fn from(error: tobj::LoadError) -> Self {
+        match error {
+            tobj::LoadError::FaceVertexOutOfBounds
+            | tobj::LoadError::FaceTexCoordOutOfBounds
+            | tobj::LoadError::FaceNormalOutOfBounds
+            | tobj::LoadError::FaceColorOutOfBounds => MeshError::InvalidIndex(error.to_string()),
+            _ => MeshError::Parse(error.to_string()),
+        }
+    }
+}
+
+/// Representa un vértice en el espacio 3D, incluyendo su posición, normal y coordenadas de textura.
+#[derive(Debug, Clone, Copy)]
 pub struct Vertex {
     /// Posición del vértice en coordenadas 3D.
     pub position: Vec3,
     /// Vector normal del vértice, utilizado para iluminación y sombreado.
     pub normal: Vec3,
+    /// Coordenadas de textura (u, v) en el rango [0, 1].
+    pub uv: Vec2,
+    /// Tangente en la dirección de +u (`xyz`) y orientación de la bitangente (`w`, ±1):
+    /// la bitangente es `cross(normal, tangent.xyz) * w`. Ver [`ObjMesh::compute_tangents`].
+    pub tangent: Vec4,
 }
 
 /// Estructura que representa una malla 3D compuesta por vértices e índices de triángulos.
@@ -21,6 +83,19 @@ pub struct ObjMesh {
     pub vertices: Vec<Vertex>,
     /// Lista de índices que definen los triángulos de la malla.
     pub indices: Vec<u32>,
+    /// Rangos de índices que usan cada material. Vacío si toda la malla usa el material 0.
+    pub submeshes: Vec<Submesh>,
+}
+
+/// Rango contiguo de triángulos de una malla que comparte un mismo material.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Submesh {
+    /// Posición del primer índice del rango dentro de `ObjMesh::indices`.
+    pub start: usize,
+    /// Número de índices del rango (múltiplo de 3).
+    pub count: usize,
+    /// Identificador del material, usado para elegir el shader al renderizar.
+    pub material_id: usize,
 }
 
 impl ObjMesh {
@@ -31,23 +106,40 @@ impl ObjMesh {
     /// * `rings` - Número de divisiones horizontales (latitud).
     /// * `sectors` - Número de divisiones verticales (longitud).
     ///
+    /// Valores degenerados se ajustan a los mínimos válidos (`rings >= 2`, `sectors >= 3`),
+    /// evitando el desbordamiento en la aritmética de índices.
+    ///
     /// # Retorna
     /// Una instancia de `ObjMesh` representando la esfera generada.
     pub fn create_sphere(radius: f32, rings: u32, sectors: u32) -> Self {
+        // Con menos de 2 anillos no hay anillo intermedio, y con menos de 3 sectores
+        // no se forma un volumen cerrado.
+        let rings = rings.max(2);
+        let sectors = sectors.max(3);
+
         let mut vertices = Vec::new();
         let mut indices = Vec::new();
 
-        // Polo norte: único vértice superior.
-        vertices.push(Vertex {
-            position: Vec3::new(0.0, radius, 0.0),
-            normal: Vec3::new(0.0, 1.0, 0.0),
-        });
+        // Polo norte: un vértice por sector. Todos comparten posición, pero cada uno lleva
+        // la `u` del centro de su sector, ya que el polo no tiene una `u` única.
+        for s in 0..sectors {
+            vertices.push(Vertex {
+                position: Vec3::new(0.0, radius, 0.0),
+                normal: Vec3::new(0.0, 1.0, 0.0),
+                uv: Vec2::new((s as f32 + 0.5) / sectors as f32, 0.0),
+                tangent: Vec4::zeros(),
+            });
+        }
 
         // Vértices intermedios (excluyendo polos), generados por anillos y sectores.
+        // La columna `s == sectors` duplica la costura en phi = 0/2π con u = 1, para que
+        // la textura no se estire al volver de u = 1 a u = 0. Su posición se calcula con
+        // phi = 0 (y no 2π, cuyo seno en f32 no es exactamente 0) para que coincida bit a
+        // bit con la columna `s == 0` y la costura no deje una rendija entre triángulos.
         for r in 1..rings {
             for s in 0..=sectors {
                 let theta = PI * r as f32 / rings as f32; // Ángulo de latitud.
-                let phi = 2.0 * PI * s as f32 / sectors as f32; // Ángulo de longitud.
+                let phi = 2.0 * PI * (s % sectors) as f32 / sectors as f32; // Ángulo de longitud.
 
                 let x = theta.sin() * phi.cos();
                 let y = theta.cos();
@@ -55,28 +147,39 @@ impl ObjMesh {
 
                 let position = Vec3::new(x * radius, y * radius, z * radius);
                 let normal = Vec3::new(x, y, z);
+                let uv = Vec2::new(s as f32 / sectors as f32, r as f32 / rings as f32);
 
-                vertices.push(Vertex { position, normal });
+                vertices.push(Vertex {
+                    position,
+                    normal,
+                    uv,
+                    tangent: Vec4::zeros(),
+                });
             }
         }
 
-        // Polo sur: único vértice inferior.
-        vertices.push(Vertex {
-            position: Vec3::new(0.0, -radius, 0.0),
-            normal: Vec3::new(0.0, -1.0, 0.0),
-        });
+        // Polo sur: igual que el norte, un vértice por sector.
+        for s in 0..sectors {
+            vertices.push(Vertex {
+                position: Vec3::new(0.0, -radius, 0.0),
+                normal: Vec3::new(0.0, -1.0, 0.0),
+                uv: Vec2::new((s as f32 + 0.5) / sectors as f32, 1.0),
+                tangent: Vec4::zeros(),
+            });
+        }
 
         // Triángulos que conectan el polo norte con el primer anillo.
+        let first_ring_start = sectors;
         for s in 0..sectors {
-            indices.push(0); // Índice del polo norte.
-            indices.push(1 + s);
-            indices.push(1 + s + 1);
+            indices.push(s); // Vértice del polo norte de este sector.
+            indices.push(first_ring_start + s);
+            indices.push(first_ring_start + s + 1);
         }
 
         // Triángulos de los anillos intermedios (dos triángulos por quad).
         for r in 0..(rings - 2) {
             for s in 0..sectors {
-                let current = 1 + r * (sectors + 1) + s;
+                let current = first_ring_start + r * (sectors + 1) + s;
                 let next = current + sectors + 1;
 
                 // Primer triángulo del quad.
@@ -92,16 +195,22 @@ impl ObjMesh {
         }
 
         // Triángulos que conectan el último anillo con el polo sur.
-        let south_pole_index = vertices.len() as u32 - 1;
-        let last_ring_start = south_pole_index - (sectors + 1);
+        let south_pole_start = vertices.len() as u32 - sectors;
+        let last_ring_start = south_pole_start - (sectors + 1);
 
         for s in 0..sectors {
             indices.push(last_ring_start + s);
-            indices.push(south_pole_index);
+            indices.push(south_pole_start + s);
             indices.push(last_ring_start + s + 1);
         }
 
-        ObjMesh { vertices, indices }
+        let mut sphere = ObjMesh {
+            vertices,
+            indices,
+            submeshes: Vec::new(),
+        };
+        sphere.compute_tangents();
+        sphere
     }
 
     /// Carga una malla desde un archivo en formato OBJ.
@@ -110,45 +219,1306 @@ impl ObjMesh {
     /// * `path` - Ruta al archivo .obj a cargar.
     ///
     /// # Retorna
-    /// `Ok(ObjMesh)` si la carga fue exitosa, o un mensaje de error en caso contrario.
-    pub fn load_from_obj(path: &str) -> Result<Self, String> {
-        // Carga el archivo OBJ usando la biblioteca tobj.
-        let (models, _) = tobj::load_obj(path, &tobj::GPU_LOAD_OPTIONS)
-            .map_err(|e| format!("Error loading OBJ: {}", e))?;
+    /// `Ok(ObjMesh)` si la carga fue exitosa; [`MeshError::Io`] si el archivo no se pudo
+    /// abrir y [`MeshError::Empty`] si no contiene modelos.
+    pub fn load_from_obj(path: &str) -> Result<Self, MeshError> {
+        // Abre el archivo aquí (y no dentro de tobj) para conservar el error de E/S.
+        let mut reader = BufReader::new(File::open(path).map_err(MeshError::Io)?);
+        let directory = Path::new(path).parent().unwrap_or(Path::new(""));
+        let (models, _) = tobj::load_obj_buf(&mut reader, &tobj::GPU_LOAD_OPTIONS, |mtl| {
+            tobj::load_mtl(directory.join(mtl))
+        })?;
 
-        if models.is_empty() {
-            return Err("No models found in OBJ file".to_string());
+        Self::from_models(&models)
+    }
+
+    /// Carga una malla OBJ desde cualquier lector en memoria, sin necesidad de un archivo.
+    ///
+    /// Útil para mallas incrustadas con `include_bytes!` o leídas desde un archivo
+    /// comprimido. Las bibliotecas de materiales (`mtllib`) no se cargan, así que todas las
+    /// caras usan el material 0.
+    ///
+    /// # Argumentos
+    /// * `reader` - Lector con el contenido del OBJ, p. ej. un `&[u8]`.
+    ///
+    /// # Retorna
+    /// `Ok(ObjMesh)` si la carga fue exitosa, o el [`MeshError`] correspondiente.
+    pub fn load_from_reader<R: BufRead>(mut reader: R) -> Result<Self, MeshError> {
+        let (models, _) = tobj::load_obj_buf(&mut reader, &tobj::GPU_LOAD_OPTIONS, |_| {
+            Ok((Vec::new(), Default::default()))
+        })?;
+
+        Self::from_models(&models)
+    }
+
+    /// Construye la malla a partir de los modelos devueltos por tobj.
+    fn from_models(models: &[tobj::Model]) -> Result<Self, MeshError> {
+        // tobj devuelve un modelo vacío para un archivo sin caras; también cuenta como vacío.
+        if models.iter().all(|model| model.mesh.indices.is_empty()) {
+            return Err(MeshError::Empty);
         }
 
-        let mesh = &models[0].mesh;
         let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        let mut submeshes = Vec::new();
 
-        // Recorre los vértices del archivo y los convierte a la estructura interna.
-        for i in 0..mesh.positions.len() / 3 {
-            let position = Vec3::new(
-                mesh.positions[i * 3],
-                mesh.positions[i * 3 + 1],
-                mesh.positions[i * 3 + 2],
-            );
+        // tobj separa el archivo en un modelo por grupo de material; se concatenan todos
+        // en una sola malla, registrando el rango de índices de cada uno.
+        for model in models {
+            let mesh = &model.mesh;
+            let base = vertices.len() as u32;
+
+            // Recorre los vértices del archivo y los convierte a la estructura interna.
+            for i in 0..mesh.positions.len() / 3 {
+                let position = Vec3::new(
+                    mesh.positions[i * 3],
+                    mesh.positions[i * 3 + 1],
+                    mesh.positions[i * 3 + 2],
+                );
+
+                // Si el archivo contiene normales, las usa (las nulas se corrigen después con
+                // las caras adyacentes); si no, normaliza la posición.
+                let normal = if !mesh.normals.is_empty() {
+                    Vec3::new(
+                        mesh.normals[i * 3],
+                        mesh.normals[i * 3 + 1],
+                        mesh.normals[i * 3 + 2],
+                    )
+                } else {
+                    sanitize_normal(position, &position)
+                };
 
-            // Si el archivo contiene normales, las usa; si no, normaliza la posición.
-            let normal = if !mesh.normals.is_empty() {
-                Vec3::new(
-                    mesh.normals[i * 3],
-                    mesh.normals[i * 3 + 1],
-                    mesh.normals[i * 3 + 2],
-                )
-                .normalize()
+                // Coordenadas de textura si el archivo las incluye.
+                let uv = if mesh.texcoords.len() >= (i + 1) * 2 {
+                    Vec2::new(mesh.texcoords[i * 2], mesh.texcoords[i * 2 + 1])
+                } else {
+                    Vec2::zeros()
+                };
+
+                vertices.push(Vertex {
+                    position,
+                    normal,
+                    uv,
+                    tangent: Vec4::zeros(),
+                });
+            }
+
+            submeshes.push(Submesh {
+                start: indices.len(),
+                count: mesh.indices.len(),
+                material_id: mesh.material_id.unwrap_or(0),
+            });
+            indices.extend(mesh.indices.iter().map(|&index| base + index));
+        }
+
+        let mut obj_mesh = ObjMesh {
+            vertices,
+            indices,
+            submeshes,
+        };
+        obj_mesh.validate()?;
+        obj_mesh.repair_normals();
+        obj_mesh.compute_tangents();
+
+        Ok(obj_mesh)
+    }
+
+    /// Normaliza las normales de los vértices, reemplazando las nulas o no finitas.
+    ///
+    /// Una normal (0, 0, 0) produce NaN al normalizarse, que se propagan a los productos
+    /// punto de los shaders y dejan píxeles negros. Las normales inválidas se sustituyen por
+    /// la suma de las normales de las caras adyacentes (según el orden antihorario de OBJ) o,
+    /// si no hay caras útiles, por la dirección de la posición del vértice.
+    fn repair_normals(&mut self) {
+        let is_valid = |n: &Vec3| n.iter().all(|c| c.is_finite()) && n.magnitude_squared() > 1e-12;
+        if self.vertices.iter().all(|v| is_valid(&v.normal)) {
+            for vertex in &mut self.vertices {
+                vertex.normal = vertex.normal.normalize();
+            }
+            return;
+        }
+
+        let mut face_sums = vec![Vec3::zeros(); self.vertices.len()];
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|k| self.vertices[triangle[k] as usize].position);
+            let face = (b - a).cross(&(c - a));
+            for &index in triangle {
+                face_sums[index as usize] += face;
+            }
+        }
+
+        for (vertex, face_sum) in self.vertices.iter_mut().zip(face_sums) {
+            vertex.normal = if is_valid(&vertex.normal) {
+                vertex.normal.normalize()
             } else {
-                position.normalize()
+                sanitize_normal(face_sum, &vertex.position)
             };
+        }
+    }
+
+    /// Carga una malla OBJ aplicando una escala uniforme y una traslación a sus vértices.
+    ///
+    /// La transformación se aplica una sola vez al cargar, de modo que la malla queda lista
+    /// para usarse sin ajustar su matriz de modelo en cada cuadro. Las normales no cambian
+    /// con una traslación ni con una escala uniforme positiva.
+    ///
+    /// # Argumentos
+    /// * `path` - Ruta al archivo .obj a cargar.
+    /// * `scale` - Factor de escala uniforme (debe ser positivo y finito).
+    /// * `offset` - Desplazamiento aplicado después de escalar.
+    ///
+    /// # Retorna
+    /// `Ok(ObjMesh)` con las posiciones transformadas, [`MeshError::InvalidScale`] si la
+    /// escala no es válida, o el error de [`ObjMesh::load_from_obj`].
+    pub fn load_from_obj_transformed(
+        path: &str,
+        scale: f32,
+        offset: Vec3,
+    ) -> Result<Self, MeshError> {
+        if !(scale.is_finite() && scale > 0.0) {
+            return Err(MeshError::InvalidScale(scale));
+        }
 
-            vertices.push(Vertex { position, normal });
+        let mut mesh = Self::load_from_obj(path)?;
+        for vertex in &mut mesh.vertices {
+            vertex.position = vertex.position * scale + offset;
         }
 
-        Ok(ObjMesh {
-            vertices,
-            indices: mesh.indices.clone(),
+        Ok(mesh)
+    }
+
+    /// Invierte el orden de recorrido (winding) de todos los triángulos de la malla.
+    ///
+    /// Intercambia el segundo y tercer índice de cada triángulo, lo que corrige mallas
+    /// exportadas con las caras invertidas sin tener que volver a exportarlas.
+    ///
+    /// # Argumentos
+    /// * `flip_normals` - Si es `true`, también niega las normales de los vértices.
+    pub fn flip_winding(&mut self, flip_normals: bool) {
+        for triangle in self.indices.chunks_exact_mut(3) {
+            triangle.swap(1, 2);
+        }
+
+        if flip_normals {
+            for vertex in &mut self.vertices {
+                vertex.normal = -vertex.normal;
+            }
+        }
+    }
+
+    /// Recorre los triángulos de la malla devolviendo sus tres vértices.
+    ///
+    /// Los triángulos con algún índice fuera de rango se omiten, y los índices sobrantes
+    /// al final (si `indices.len()` no es múltiplo de 3) se ignoran.
+    pub fn triangles(&self) -> impl Iterator<Item = [&Vertex; 3]> + '_ {
+        self.indices.chunks_exact(3).filter_map(|triangle| {
+            Some([
+                self.vertices.get(triangle[0] as usize)?,
+                self.vertices.get(triangle[1] as usize)?,
+                self.vertices.get(triangle[2] as usize)?,
+            ])
         })
     }
+
+    /// Calcula el área total de la superficie sumando el área de cada triángulo.
+    ///
+    /// Los triángulos degenerados aportan un área nula.
+    pub fn surface_area(&self) -> f32 {
+        self.triangle_areas().map(|(area, _)| area).sum()
+    }
+
+    /// Calcula el centroide de la superficie, ponderando el centro de cada triángulo por su área.
+    ///
+    /// Si la malla no tiene área (vacía o solo con triángulos degenerados), devuelve el
+    /// promedio de las posiciones de sus vértices, o el origen si no hay vértices.
+    pub fn centroid(&self) -> Vec3 {
+        let (weighted, total) = self
+            .triangle_areas()
+            .fold((Vec3::zeros(), 0.0), |(sum, total), (area, center)| {
+                (sum + center * area, total + area)
+            });
+
+        if total > f32::EPSILON {
+            return weighted / total;
+        }
+
+        if self.vertices.is_empty() {
+            Vec3::zeros()
+        } else {
+            let sum = self
+                .vertices
+                .iter()
+                .fold(Vec3::zeros(), |acc, v| acc + v.position);
+            sum / self.vertices.len() as f32
+        }
+    }
+
+    /// Recorre los triángulos devolviendo `(área, centro)` de cada uno.
+    fn triangle_areas(&self) -> impl Iterator<Item = (f32, Vec3)> + '_ {
+        self.triangles().map(|[a, b, c]| {
+            let (a, b, c) = (a.position, b.position, c.position);
+
+            let area = (b - a).cross(&(c - a)).magnitude() * 0.5;
+            (area, (a + b + c) / 3.0)
+        })
+    }
+
+    /// Recalcula las normales de los vértices a partir de las caras de la malla.
+    ///
+    /// Cada esquina de un triángulo promedia (ponderando por área) las normales de las caras
+    /// que comparten su posición, pero solo de aquellas cuyo ángulo con la cara propia sea
+    /// menor que `smoothing_angle_deg`. Así las aristas vivas se mantienen facetadas y las
+    /// regiones suaves se difuminan. Los vértices que terminan con normales distintas se
+    /// duplican.
+    ///
+    /// La orientación de cada cara se ajusta para coincidir con las normales previas de sus
+    /// vértices (igual que en el rasterizador), de modo que funciona con ambos órdenes de
+    /// recorrido.
+    ///
+    /// # Argumentos
+    /// * `smoothing_angle_deg` - Ángulo máximo en grados entre caras que se suavizan juntas.
+    pub fn recompute_normals(&mut self, smoothing_angle_deg: f32) {
+        // Normal de cada cara, sin normalizar: su magnitud es el doble del área.
+        let face_normals: Vec<Vec3> = self
+            .indices
+            .chunks_exact(3)
+            .map(|triangle| {
+                let [a, b, c] = [0, 1, 2].map(|k| &self.vertices[triangle[k] as usize]);
+                let normal = (b.position - a.position).cross(&(c.position - a.position));
+                if normal.dot(&(a.normal + b.normal + c.normal)) < 0.0 {
+                    -normal
+                } else {
+                    normal
+                }
+            })
+            .collect();
+
+        // Caras incidentes a cada posición (los vértices duplicados en costuras se agrupan).
+        let mut faces_at: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+        for (face, triangle) in self.indices.chunks_exact(3).enumerate() {
+            for &index in triangle {
+                let key = weld_key(&self.vertices[index as usize].position);
+                faces_at.entry(key).or_default().push(face);
+            }
+        }
+
+        let cos_threshold = smoothing_angle_deg.to_radians().cos();
+        let mut vertices: Vec<Vertex> = Vec::with_capacity(self.vertices.len());
+        // Para cada vértice original, las versiones ya emitidas: (normal, índice nuevo).
+        let mut emitted: Vec<Vec<(Vec3, u32)>> = vec![Vec::new(); self.vertices.len()];
+        let mut indices = Vec::with_capacity(self.indices.len());
+
+        for (face, &index) in self.indices.iter().enumerate().map(|(i, idx)| (i / 3, idx)) {
+            let original = self.vertices[index as usize];
+            let own = face_normals[face];
+
+            let normal = if own.magnitude_squared() > 0.0 {
+                let own_dir = own.normalize();
+                let sum = faces_at[&weld_key(&original.position)]
+                    .iter()
+                    .filter(|&&other| other != face)
+                    .map(|&other| face_normals[other])
+                    .filter(|other| {
+                        other.magnitude_squared() > 0.0
+                            && own_dir.dot(&other.normalize()) > cos_threshold + 1e-4
+                    })
+                    .fold(own, |acc, other| acc + other);
+                sum.normalize()
+            } else {
+                // Cara degenerada: conserva la normal que ya tenía el vértice.
+                original.normal
+            };
+
+            let versions = &mut emitted[index as usize];
+            let new_index = match versions.iter().find(|(n, _)| n.dot(&normal) > 1.0 - 1e-5) {
+                Some(&(_, existing)) => existing,
+                None => {
+                    let new_index = vertices.len() as u32;
+                    vertices.push(Vertex { normal, ..original });
+                    versions.push((normal, new_index));
+                    new_index
+                }
+            };
+            indices.push(new_index);
+        }
+
+        self.vertices = vertices;
+        self.indices = indices;
+        self.compute_tangents();
+    }
+
+    /// Calcula la tangente de cada vértice a partir de las coordenadas de textura.
+    ///
+    /// Cada triángulo aporta las direcciones en espacio del objeto en las que crecen `u`
+    /// (tangente) y `v` (bitangente), ponderadas por su área. En cada vértice la tangente
+    /// acumulada se ortogonaliza respecto a la normal (Gram-Schmidt) y `w` guarda si la
+    /// bitangente coincide con `cross(normal, tangente)` (+1) o con su opuesta (-1), lo
+    /// que ocurre en las UV reflejadas.
+    ///
+    /// Los vértices sin UV útiles (triángulos degenerados en UV, o UV nulas) reciben una
+    /// tangente arbitraria perpendicular a su normal, para que la base TBN siga siendo
+    /// válida.
+    pub fn compute_tangents(&mut self) {
+        let mut tangents = vec![Vec3::zeros(); self.vertices.len()];
+        let mut bitangents = vec![Vec3::zeros(); self.vertices.len()];
+
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|k| &self.vertices[triangle[k] as usize]);
+            let (e1, e2) = (b.position - a.position, c.position - a.position);
+            let (d1, d2) = (b.uv - a.uv, c.uv - a.uv);
+
+            // Determinante del sistema UV; con el producto cruzado de las aristas el peso
+            // resulta proporcional al área del triángulo.
+            let det = d1.x * d2.y - d2.x * d1.y;
+            if det.abs() < 1e-12 {
+                continue;
+            }
+            let sign = det.signum();
+            let tangent = (e1 * d2.y - e2 * d1.y) * sign;
+            let bitangent = (e2 * d1.x - e1 * d2.x) * sign;
+
+            for &index in triangle {
+                tangents[index as usize] += tangent;
+                bitangents[index as usize] += bitangent;
+            }
+        }
+
+        for ((vertex, tangent), bitangent) in self.vertices.iter_mut().zip(tangents).zip(bitangents)
+        {
+            let normal = vertex.normal;
+            let projected = tangent - normal * normal.dot(&tangent);
+            let tangent = if projected.iter().all(|c| c.is_finite())
+                && projected.magnitude_squared() > 1e-12
+            {
+                projected.normalize()
+            } else {
+                any_perpendicular(&normal)
+            };
+            let handedness = if normal.cross(&tangent).dot(&bitangent) < 0.0 {
+                -1.0
+            } else {
+                1.0
+            };
+            vertex.tangent = Vec4::new(tangent.x, tangent.y, tangent.z, handedness);
+        }
+    }
+
+    /// Suaviza la malla aplicando `iterations` pasos de subdivisión de Loop.
+    ///
+    /// Cada paso divide cada triángulo en cuatro, inserta un vértice nuevo por arista y
+    /// reubica los vértices existentes con los pesos de Loop. La topología se calcula sobre
+    /// las posiciones soldadas, de modo que las costuras de UV no se abren; las coordenadas
+    /// de textura de los vértices nuevos se interpolan linealmente.
+    ///
+    /// Las aristas con una sola cara (o más de dos) se tratan como bordes y usan las
+    /// reglas de borde, que solo consideran vecinos a lo largo del borde.
+    pub fn subdivide_loop(&mut self, iterations: u32) {
+        for _ in 0..iterations {
+            self.subdivide_loop_once();
+        }
+        self.compute_tangents();
+    }
+
+    /// Un paso de subdivisión de Loop. Ver [`ObjMesh::subdivide_loop`].
+    fn subdivide_loop_once(&mut self) {
+        // Identificador soldado de cada vértice: los que comparten posición comparten id.
+        let mut weld_ids: HashMap<[i64; 3], usize> = HashMap::new();
+        let welded: Vec<usize> = self
+            .vertices
+            .iter()
+            .map(|v| {
+                let next_id = weld_ids.len();
+                *weld_ids.entry(weld_key(&v.position)).or_insert(next_id)
+            })
+            .collect();
+
+        let count = weld_ids.len();
+        let mut positions = vec![Vec3::zeros(); count];
+        let mut normals = vec![Vec3::zeros(); count];
+        for (vertex, &id) in self.vertices.iter().zip(&welded) {
+            positions[id] = vertex.position;
+            normals[id] += vertex.normal;
+        }
+
+        // Adyacencia por aristas soldadas: vértices opuestos de cada cara incidente.
+        let edge_key = |a: usize, b: usize| (a.min(b), a.max(b));
+        let mut opposite: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|k| welded[triangle[k] as usize]);
+            for (u, v, w) in [(a, b, c), (b, c, a), (c, a, b)] {
+                opposite.entry(edge_key(u, v)).or_default().push(w);
+            }
+        }
+
+        // Vecinos de cada vértice, separando los que están a lo largo de un borde.
+        let mut neighbors: Vec<Vec<usize>> = vec![Vec::new(); count];
+        let mut boundary_neighbors: Vec<Vec<usize>> = vec![Vec::new(); count];
+        for (&(a, b), faces) in &opposite {
+            neighbors[a].push(b);
+            neighbors[b].push(a);
+            if faces.len() != 2 {
+                boundary_neighbors[a].push(b);
+                boundary_neighbors[b].push(a);
+            }
+        }
+
+        // Nueva posición (y normal) de los vértices existentes según los pesos de Loop.
+        let reposition = |values: &[Vec3], id: usize| -> Vec3 {
+            let border = &boundary_neighbors[id];
+            if border.len() >= 2 {
+                values[id] * 0.75 + (values[border[0]] + values[border[1]]) * 0.125
+            } else if border.is_empty() && !neighbors[id].is_empty() {
+                let n = neighbors[id].len();
+                let beta = if n == 3 {
+                    3.0 / 16.0
+                } else {
+                    3.0 / (8.0 * n as f32)
+                };
+                let sum = neighbors[id]
+                    .iter()
+                    .fold(Vec3::zeros(), |acc, &j| acc + values[j]);
+                values[id] * (1.0 - n as f32 * beta) + sum * beta
+            } else {
+                // Vértice aislado o esquina de un borde no manifold: se mantiene fijo.
+                values[id]
+            }
+        };
+
+        // Posición (y normal) del vértice nuevo insertado en la arista `a`-`b`.
+        let edge_point = |values: &[Vec3], a: usize, b: usize| -> Vec3 {
+            match opposite[&edge_key(a, b)].as_slice() {
+                &[c, d] => (values[a] + values[b]) * 0.375 + (values[c] + values[d]) * 0.125,
+                _ => (values[a] + values[b]) * 0.5,
+            }
+        };
+
+        let safe_normalize = |n: Vec3| {
+            if n.magnitude_squared() > 0.0 {
+                n.normalize()
+            } else {
+                n
+            }
+        };
+
+        let mut vertices: Vec<Vertex> = self
+            .vertices
+            .iter()
+            .zip(&welded)
+            .map(|(vertex, &id)| Vertex {
+                position: reposition(&positions, id),
+                normal: safe_normalize(reposition(&normals, id)),
+                uv: vertex.uv,
+                tangent: Vec4::zeros(),
+            })
+            .collect();
+
+        // Los vértices de arista se indexan por los índices originales, para que una arista
+        // sobre una costura de UV genere un vértice distinto a cada lado.
+        let mut edge_vertices: HashMap<(u32, u32), u32> = HashMap::new();
+        let mut midpoint = |a: u32, b: u32| -> u32 {
+            *edge_vertices
+                .entry((a.min(b), a.max(b)))
+                .or_insert_with(|| {
+                    let (wa, wb) = (welded[a as usize], welded[b as usize]);
+                    let uv = (self.vertices[a as usize].uv + self.vertices[b as usize].uv) * 0.5;
+                    vertices.push(Vertex {
+                        position: edge_point(&positions, wa, wb),
+                        normal: safe_normalize(edge_point(&normals, wa, wb)),
+                        uv,
+                        tangent: Vec4::zeros(),
+                    });
+                    vertices.len() as u32 - 1
+                })
+        };
+
+        let mut indices = Vec::with_capacity(self.indices.len() * 4);
+        for triangle in self.indices.chunks_exact(3) {
+            let (a, b, c) = (triangle[0], triangle[1], triangle[2]);
+            let ab = midpoint(a, b);
+            let bc = midpoint(b, c);
+            let ca = midpoint(c, a);
+
+            // Conserva el orden de recorrido del triángulo original.
+            indices.extend_from_slice(&[a, ab, ca, ab, b, bc, ca, bc, c, ab, bc, ca]);
+        }
+
+        self.vertices = vertices;
+        self.indices = indices;
+
+        // Cada triángulo se reemplaza por cuatro consecutivos, así que los rangos escalan.
+        for submesh in &mut self.submeshes {
+            submesh.start *= 4;
+            submesh.count *= 4;
+        }
+    }
+
+    /// Relaja la malla con `iterations` pasos de suavizado laplaciano y recalcula las
+    /// normales.
+    ///
+    /// En cada paso, cada vértice se desplaza hacia el promedio de sus vecinos una fracción
+    /// `factor` del camino (0.0 no lo mueve, 1.0 lo lleva al promedio). A diferencia de
+    /// [`ObjMesh::subdivide_loop`] no añade triángulos: solo redistribuye las posiciones,
+    /// lo que limpia el ruido de modelos importados de pocos polígonos (a costa de encoger
+    /// un poco las zonas convexas).
+    ///
+    /// La adyacencia se deduce de los índices sobre las posiciones soldadas, así que las
+    /// costuras de UV no se abren. Igual que en la subdivisión, los vértices de borde solo
+    /// promedian a sus vecinos a lo largo del borde, y las esquinas de bordes no manifold se
+    /// mantienen fijas. Las normales se recalculan suaves en toda la malla (ver
+    /// [`ObjMesh::recompute_normals`]).
+    pub fn smooth_laplacian(&mut self, iterations: u32, factor: f32) {
+        if iterations == 0 {
+            return;
+        }
+
+        // Identificador soldado de cada vértice: los que comparten posición comparten id.
+        let mut weld_ids: HashMap<[i64; 3], usize> = HashMap::new();
+        let welded: Vec<usize> = self
+            .vertices
+            .iter()
+            .map(|v| {
+                let next_id = weld_ids.len();
+                *weld_ids.entry(weld_key(&v.position)).or_insert(next_id)
+            })
+            .collect();
+
+        let count = weld_ids.len();
+        let mut positions = vec![Vec3::zeros(); count];
+        for (vertex, &id) in self.vertices.iter().zip(&welded) {
+            positions[id] = vertex.position;
+        }
+
+        // Número de caras de cada arista soldada, para distinguir los bordes.
+        let edge_key = |a: usize, b: usize| (a.min(b), a.max(b));
+        let mut edge_faces: HashMap<(usize, usize), usize> = HashMap::new();
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|k| welded[triangle[k] as usize]);
+            for (u, v) in [(a, b), (b, c), (c, a)] {
+                if u != v {
+                    *edge_faces.entry(edge_key(u, v)).or_default() += 1;
+                }
+            }
+        }
+
+        // Vecinos que promedia cada vértice: todos en el interior, solo los del borde en él.
+        let mut neighbors: Vec<Vec<usize>> = vec![Vec::new(); count];
+        let mut boundary_neighbors: Vec<Vec<usize>> = vec![Vec::new(); count];
+        for (&(a, b), &faces) in &edge_faces {
+            neighbors[a].push(b);
+            neighbors[b].push(a);
+            if faces != 2 {
+                boundary_neighbors[a].push(b);
+                boundary_neighbors[b].push(a);
+            }
+        }
+        let smoothing_set: Vec<&[usize]> = (0..count)
+            .map(|id| match boundary_neighbors[id].len() {
+                0 => neighbors[id].as_slice(),
+                2 => boundary_neighbors[id].as_slice(),
+                _ => &[],
+            })
+            .collect();
+
+        for _ in 0..iterations {
+            positions = smoothing_set
+                .iter()
+                .enumerate()
+                .map(|(id, ring)| {
+                    if ring.is_empty() {
+                        return positions[id];
+                    }
+                    let average = ring
+                        .iter()
+                        .fold(Vec3::zeros(), |acc, &j| acc + positions[j])
+                        / ring.len() as f32;
+                    positions[id] + (average - positions[id]) * factor
+                })
+                .collect();
+        }
+
+        for (vertex, &id) in self.vertices.iter_mut().zip(&welded) {
+            vertex.position = positions[id];
+        }
+        self.recompute_normals(180.0);
+    }
+
+    /// Genera una versión simplificada de la malla con como mucho `target_triangles`
+    /// triángulos, pensada como nivel de detalle (LOD) para instancias lejanas.
+    ///
+    /// Usa agrupamiento de vértices: divide la caja envolvente en una rejilla uniforme y
+    /// fusiona los vértices de cada celda en su promedio (posición, normal y UV). Los
+    /// triángulos que quedan degenerados o repetidos se descartan. La resolución de la
+    /// rejilla se busca para quedar lo más cerca posible del objetivo sin superarlo.
+    ///
+    /// Como cada vértice nuevo es un promedio de vértices originales, el resultado queda
+    /// dentro de la caja envolvente original. Las submallas se conservan.
+    pub fn decimate(&self, target_triangles: usize) -> ObjMesh {
+        if self.indices.len() / 3 <= target_triangles {
+            return self.clone();
+        }
+
+        // Busca la rejilla más fina que cumple el objetivo. El número de triángulos crece
+        // casi siempre con la resolución, así que basta una búsqueda binaria.
+        let (mut low, mut high) = (1u32, 256u32);
+        let mut best = ObjMesh {
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            submeshes: Vec::new(),
+        };
+        while low <= high {
+            let resolution = low + (high - low) / 2;
+            let candidate = self.cluster_vertices(resolution);
+            if candidate.indices.len() / 3 <= target_triangles {
+                best = candidate;
+                low = resolution + 1;
+            } else {
+                high = resolution - 1;
+            }
+        }
+        best
+    }
+
+    /// Fusiona los vértices en una rejilla de `resolution` celdas por el eje más largo de la
+    /// caja envolvente. Ver [`ObjMesh::decimate`].
+    fn cluster_vertices(&self, resolution: u32) -> ObjMesh {
+        let (min, max) = self.vertices.iter().fold(
+            (Vec3::repeat(f32::INFINITY), Vec3::repeat(f32::NEG_INFINITY)),
+            |(min, max), v| (min.inf(&v.position), max.sup(&v.position)),
+        );
+        let cell_size = (max - min).max() / resolution as f32;
+        let cell_of = |p: &Vec3| -> [i64; 3] {
+            if cell_size <= 0.0 {
+                return [0; 3];
+            }
+            let c = (p - min) / cell_size;
+            [c.x.floor() as i64, c.y.floor() as i64, c.z.floor() as i64]
+        };
+
+        // Acumula los atributos de cada celda y asigna a cada vértice su grupo.
+        let mut cells: HashMap<[i64; 3], u32> = HashMap::new();
+        let mut sums: Vec<(Vertex, f32)> = Vec::new();
+        let cluster: Vec<u32> = self
+            .vertices
+            .iter()
+            .map(|v| {
+                let id = *cells.entry(cell_of(&v.position)).or_insert_with(|| {
+                    sums.push((
+                        Vertex {
+                            position: Vec3::zeros(),
+                            normal: Vec3::zeros(),
+                            uv: Vec2::zeros(),
+                            tangent: Vec4::zeros(),
+                        },
+                        0.0,
+                    ));
+                    sums.len() as u32 - 1
+                });
+                let (sum, count) = &mut sums[id as usize];
+                sum.position += v.position;
+                sum.normal += v.normal;
+                sum.uv += v.uv;
+                *count += 1.0;
+                id
+            })
+            .collect();
+
+        let vertices: Vec<Vertex> = sums
+            .into_iter()
+            .map(|(sum, count)| Vertex {
+                position: sum.position / count,
+                normal: sanitize_normal(sum.normal, &sum.position),
+                uv: sum.uv / count,
+                tangent: Vec4::zeros(),
+            })
+            .collect();
+
+        let whole_mesh = [Submesh {
+            start: 0,
+            count: self.indices.len(),
+            material_id: 0,
+        }];
+        let groups = if self.submeshes.is_empty() {
+            &whole_mesh[..]
+        } else {
+            &self.submeshes[..]
+        };
+
+        let mut indices = Vec::new();
+        let mut submeshes = Vec::with_capacity(groups.len());
+        for group in groups {
+            let start = indices.len();
+            // Un triángulo repetido se reconoce por su rotación canónica (menor índice
+            // primero), que conserva el sentido de recorrido.
+            let mut seen = HashSet::new();
+            let end = (group.start + group.count).min(self.indices.len());
+            for triangle in self.indices[group.start..end].chunks_exact(3) {
+                let [a, b, c] = [0, 1, 2].map(|k| cluster[triangle[k] as usize]);
+                if a == b || b == c || c == a {
+                    continue;
+                }
+                let key = if a < b && a < c {
+                    [a, b, c]
+                } else if b < c {
+                    [b, c, a]
+                } else {
+                    [c, a, b]
+                };
+                if seen.insert(key) {
+                    indices.extend_from_slice(&[a, b, c]);
+                }
+            }
+            submeshes.push(Submesh {
+                start,
+                count: indices.len() - start,
+                material_id: group.material_id,
+            });
+        }
+        if self.submeshes.is_empty() {
+            submeshes.clear();
+        }
+
+        let mut mesh = ObjMesh {
+            vertices,
+            indices,
+            submeshes,
+        };
+        mesh.compute_tangents();
+        mesh
+    }
+
+    /// Verifica que el búfer de índices sea consistente con los vértices de la malla.
+    ///
+    /// Comprueba que el número de índices sea múltiplo de 3 (solo triángulos), que
+    /// cada índice apunte a un vértice existente y que cada submalla abarque triángulos
+    /// completos dentro del búfer.
+    ///
+    /// # Retorna
+    /// `Ok(())` si la malla es válida, o [`MeshError::InvalidIndex`] describiendo el primer
+    /// problema encontrado.
+    pub fn validate(&self) -> Result<(), MeshError> {
+        if !self.indices.len().is_multiple_of(3) {
+            return Err(MeshError::InvalidIndex(format!(
+                "Index count {} is not a multiple of 3",
+                self.indices.len()
+            )));
+        }
+
+        let vertex_count = self.vertices.len();
+        if let Some((position, &index)) = self
+            .indices
+            .iter()
+            .enumerate()
+            .find(|&(_, &index)| index as usize >= vertex_count)
+        {
+            return Err(MeshError::InvalidIndex(format!(
+                "Index {} at position {} is out of range ({} vertices)",
+                index, position, vertex_count
+            )));
+        }
+
+        if let Some(submesh) = self.submeshes.iter().find(|submesh| {
+            !submesh.start.is_multiple_of(3)
+                || !submesh.count.is_multiple_of(3)
+                || submesh.start + submesh.count > self.indices.len()
+        }) {
+            return Err(MeshError::InvalidIndex(format!(
+                "Submesh {:?} does not cover whole triangles within {} indices",
+                submesh,
+                self.indices.len()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Normaliza `normal`; si es nula o no finita, usa la dirección de `position` desde el
+/// origen y, si ésta también es degenerada, el eje +Y.
+///
+/// Garantiza una normal finita y de longitud unitaria.
+pub fn sanitize_normal(normal: Vec3, position: &Vec3) -> Vec3 {
+    [normal, *position]
+        .into_iter()
+        .find(|v| v.iter().all(|c| c.is_finite()) && v.magnitude_squared() > 1e-12)
+        .map_or_else(|| Vec3::new(0.0, 1.0, 0.0), |v| v.normalize())
+}
+
+/// Devuelve un vector unitario cualquiera perpendicular a `normal` (que se supone unitaria).
+fn any_perpendicular(normal: &Vec3) -> Vec3 {
+    let helper = if normal.y.abs() < 0.9 {
+        Vec3::new(0.0, 1.0, 0.0)
+    } else {
+        Vec3::new(1.0, 0.0, 0.0)
+    };
+    helper.cross(normal).normalize()
+}
+
+/// Clave de soldadura de una posición: la cuantiza con `WELD_EPSILON` para agrupar los
+/// vértices duplicados en costuras, cuyo cálculo trigonométrico difiere en el último bit.
+fn weld_key(position: &Vec3) -> [i64; 3] {
+    let q = position / WELD_EPSILON;
+    [q.x.round() as i64, q.y.round() as i64, q.z.round() as i64]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_sphere_clamps_degenerate_counts() {
+        for (rings, sectors) in [(1, 2), (0, 0), (1, 3), (2, 2)] {
+            let sphere = ObjMesh::create_sphere(1.0, rings, sectors);
+            assert!(!sphere.indices.is_empty(), "{rings}x{sectors}");
+            assert_eq!(sphere.indices.len() % 3, 0);
+            assert!(
+                sphere
+                    .indices
+                    .iter()
+                    .all(|&i| (i as usize) < sphere.vertices.len()),
+                "índice fuera de rango con {rings}x{sectors}"
+            );
+        }
+    }
+
+    #[test]
+    fn validate_rejects_out_of_range_index() {
+        let vertex = Vertex {
+            position: Vec3::zeros(),
+            normal: Vec3::new(0.0, 1.0, 0.0),
+            uv: Vec2::zeros(),
+            tangent: Vec4::zeros(),
+        };
+        let mut mesh = ObjMesh {
+            vertices: vec![vertex; 3],
+            indices: vec![0, 1, 2],
+            submeshes: Vec::new(),
+        };
+        assert!(mesh.validate().is_ok());
+
+        mesh.indices[2] = 3;
+        assert!(mesh.validate().is_err());
+        mesh.indices = vec![0, 1];
+        assert!(mesh.validate().is_err());
+    }
+
+    #[test]
+    fn sphere_equator_spans_full_u_range() {
+        let sphere = ObjMesh::create_sphere(1.0, 8, 12);
+        let equator: Vec<f32> = sphere
+            .vertices
+            .iter()
+            .filter(|v| v.position.y.abs() < 1e-5)
+            .map(|v| v.uv.x)
+            .collect();
+        assert_eq!(equator.len(), 13, "12 sectores más la costura duplicada");
+        assert_eq!(equator.iter().cloned().fold(f32::INFINITY, f32::min), 0.0);
+        assert_eq!(
+            equator.iter().cloned().fold(f32::NEG_INFINITY, f32::max),
+            1.0
+        );
+    }
+
+    /// Ruta de un modelo incluido en `assets/`.
+    fn asset(name: &str) -> String {
+        format!("{}/assets/{name}", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    #[test]
+    fn transformed_load_scales_then_offsets_positions() {
+        let path = asset("sphere.obj");
+        let original = ObjMesh::load_from_obj(&path).unwrap();
+        let offset = Vec3::new(1.0, -2.0, 0.5);
+        let moved = ObjMesh::load_from_obj_transformed(&path, 2.5, offset).unwrap();
+
+        assert_eq!(moved.vertices.len(), original.vertices.len());
+        for (a, b) in original.vertices.iter().zip(&moved.vertices) {
+            assert!((a.position * 2.5 + offset - b.position).magnitude() < 1e-5);
+            assert_eq!(a.normal, b.normal);
+        }
+        assert!(ObjMesh::load_from_obj_transformed(&path, 0.0, offset).is_err());
+    }
+
+    #[test]
+    fn flip_winding_swaps_triangle_order_and_optionally_normals() {
+        let mut mesh = ObjMesh::create_sphere(1.0, 4, 6);
+        let indices = mesh.indices.clone();
+        let normals: Vec<Vec3> = mesh.vertices.iter().map(|v| v.normal).collect();
+
+        mesh.flip_winding(false);
+        for (old, new) in indices.chunks_exact(3).zip(mesh.indices.chunks_exact(3)) {
+            assert_eq!([old[0], old[2], old[1]], [new[0], new[1], new[2]]);
+        }
+        assert!(
+            mesh.vertices
+                .iter()
+                .zip(&normals)
+                .all(|(v, n)| v.normal == *n)
+        );
+
+        mesh.flip_winding(true);
+        assert_eq!(mesh.indices, indices);
+        assert!(
+            mesh.vertices
+                .iter()
+                .zip(&normals)
+                .all(|(v, n)| v.normal == -n)
+        );
+    }
+
+    /// Construye una malla con normales `+Z` a partir de posiciones e índices.
+    fn mesh_from(positions: &[[f32; 3]], indices: &[u32]) -> ObjMesh {
+        ObjMesh {
+            vertices: positions
+                .iter()
+                .map(|&[x, y, z]| Vertex {
+                    position: Vec3::new(x, y, z),
+                    normal: Vec3::new(0.0, 0.0, 1.0),
+                    uv: Vec2::zeros(),
+                    tangent: Vec4::zeros(),
+                })
+                .collect(),
+            indices: indices.to_vec(),
+            submeshes: Vec::new(),
+        }
+    }
+
+    /// Cuadrado unitario en el plano `z = 0`, formado por dos triángulos.
+    fn unit_square() -> ObjMesh {
+        mesh_from(
+            &[
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [0.0, 1.0, 0.0],
+            ],
+            &[0, 1, 2, 0, 2, 3],
+        )
+    }
+
+    #[test]
+    fn unit_square_area_and_centroid() {
+        let square = unit_square();
+        assert!((square.surface_area() - 1.0).abs() < 1e-6);
+        assert!((square.centroid() - Vec3::new(0.5, 0.5, 0.0)).magnitude() < 1e-6);
+
+        // Sin área, el centroide cae al promedio de los vértices.
+        let degenerate = mesh_from(
+            &[[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [4.0, 0.0, 0.0]],
+            &[0, 1, 2],
+        );
+        assert_eq!(degenerate.surface_area(), 0.0);
+        assert!((degenerate.centroid() - Vec3::new(2.0, 0.0, 0.0)).magnitude() < 1e-6);
+    }
+
+    #[test]
+    fn recompute_normals_respects_smoothing_angle() {
+        // Dos caras a 90° que comparten la arista (0,0,0)-(0,1,0): normales +Z y +X.
+        let fold = || {
+            let mut mesh = mesh_from(
+                &[
+                    [0.0, 0.0, 0.0],
+                    [1.0, 0.0, 0.0],
+                    [0.0, 1.0, 0.0],
+                    [0.0, 0.0, 1.0],
+                ],
+                &[0, 1, 2, 0, 2, 3],
+            );
+            for vertex in &mut mesh.vertices {
+                vertex.normal = Vec3::new(1.0, 0.0, 1.0).normalize();
+            }
+            mesh
+        };
+
+        let mut sharp = fold();
+        sharp.recompute_normals(45.0);
+        assert_eq!(
+            sharp.vertices.len(),
+            6,
+            "la arista viva duplica sus vértices"
+        );
+        for (face, expected) in [Vec3::new(0.0, 0.0, 1.0), Vec3::new(1.0, 0.0, 0.0)]
+            .iter()
+            .enumerate()
+        {
+            for &index in &sharp.indices[face * 3..face * 3 + 3] {
+                let normal = sharp.vertices[index as usize].normal;
+                assert!((normal - expected).magnitude() < 1e-5, "{normal:?}");
+            }
+        }
+
+        let mut smooth = fold();
+        smooth.recompute_normals(100.0);
+        assert_eq!(smooth.vertices.len(), 4);
+        let shared = Vec3::new(1.0, 0.0, 1.0).normalize();
+        for index in [0, 2] {
+            let normal = smooth.vertices[smooth.indices[index] as usize].normal;
+            assert!((normal - shared).magnitude() < 1e-5, "{normal:?}");
+        }
+    }
+
+    #[test]
+    fn loop_subdivision_quadruples_triangles_and_keeps_planes_flat() {
+        let mut square = unit_square();
+        square.subdivide_loop(2);
+        assert_eq!(square.indices.len(), 6 * 16);
+        assert!(square.validate().is_ok());
+        assert!(square.vertices.iter().all(|v| v.position.z == 0.0));
+        assert!((square.surface_area() - 1.0).abs() < 0.5);
+
+        // En una malla cerrada los vértices se acercan al centro sin salirse de la esfera.
+        let mut sphere = ObjMesh::create_sphere(1.0, 6, 8);
+        let triangles = sphere.indices.len() / 3;
+        sphere.subdivide_loop(1);
+        assert_eq!(sphere.indices.len() / 3, triangles * 4);
+        assert!(
+            sphere
+                .vertices
+                .iter()
+                .all(|v| v.position.magnitude() <= 1.0 + 1e-5 && v.position.magnitude() > 0.7)
+        );
+    }
+
+    #[test]
+    fn zero_normals_are_repaired_from_faces() {
+        // Triángulo en el plano XY cuyas normales del archivo son nulas.
+        let path = std::env::temp_dir().join(format!("normales_nulas_{}.obj", std::process::id()));
+        std::fs::write(
+            &path,
+            "v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 0\nf 1//1 2//1 3//1\n",
+        )
+        .unwrap();
+        let mesh = ObjMesh::load_from_obj(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        for vertex in &mesh.vertices {
+            assert!(
+                (vertex.normal - Vec3::z()).magnitude() < 1e-6,
+                "{:?}",
+                vertex.normal
+            );
+        }
+
+        assert_eq!(
+            sanitize_normal(Vec3::zeros(), &Vec3::new(2.0, 0.0, 0.0)),
+            Vec3::x()
+        );
+        assert_eq!(
+            sanitize_normal(Vec3::repeat(f32::NAN), &Vec3::zeros()),
+            Vec3::y()
+        );
+    }
+
+    #[test]
+    fn sphere_seam_columns_are_bit_identical() {
+        let (rings, sectors) = (7, 11);
+        let sphere = ObjMesh::create_sphere(1.3, rings, sectors);
+        // Tras los vértices del polo norte, cada anillo intermedio tiene `sectors + 1`.
+        let (start, row) = (sectors as usize, sectors as usize + 1);
+        for r in 0..rings as usize - 1 {
+            let first = sphere.vertices[start + r * row].position;
+            let last = sphere.vertices[start + r * row + sectors as usize].position;
+            assert_eq!(first, last, "anillo {r}");
+        }
+    }
+
+    #[test]
+    fn submeshes_are_validated_and_follow_subdivision() {
+        let mut square = unit_square();
+        square.submeshes = vec![
+            Submesh {
+                start: 0,
+                count: 3,
+                material_id: 0,
+            },
+            Submesh {
+                start: 3,
+                count: 3,
+                material_id: 1,
+            },
+        ];
+        assert!(square.validate().is_ok());
+
+        square.subdivide_loop(1);
+        assert_eq!(square.submeshes[1].start, 12);
+        assert_eq!(square.submeshes[1].count, 12);
+        assert!(square.validate().is_ok());
+
+        square.submeshes[1].count = 13;
+        assert!(square.validate().is_err());
+    }
+
+    #[test]
+    fn decimate_respects_target_and_bounds() {
+        let sphere = ObjMesh::create_sphere(1.0, 24, 32);
+        let original = sphere.indices.len() / 3;
+        assert_eq!(sphere.decimate(original).indices, sphere.indices);
+
+        let lod = sphere.decimate(200);
+        let triangles = lod.indices.len() / 3;
+        assert!(triangles <= 200 && triangles > 50, "{triangles} triángulos");
+        assert!(lod.validate().is_ok());
+        assert!(lod.vertices.len() < sphere.vertices.len());
+        assert!(
+            lod.vertices
+                .iter()
+                .all(|v| v.position.iter().all(|c| c.abs() <= 1.0 + 1e-5))
+        );
+    }
+
+    #[test]
+    fn triangles_skip_invalid_indices_and_leftovers() {
+        let mut square = unit_square();
+        let corners: Vec<[Vec3; 3]> = square
+            .triangles()
+            .map(|[a, b, c]| [a.position, b.position, c.position])
+            .collect();
+        assert_eq!(corners.len(), 2);
+        assert_eq!(corners[1][2], Vec3::new(0.0, 1.0, 0.0));
+
+        // Un índice fuera de rango descarta su triángulo y los índices sueltos se ignoran.
+        square.indices[4] = 9;
+        square.indices.push(0);
+        assert_eq!(square.triangles().count(), 1);
+    }
+
+    #[test]
+    fn reader_load_matches_file_load() {
+        let path = asset("sphere.obj");
+        let from_file = ObjMesh::load_from_obj(&path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        let from_memory = ObjMesh::load_from_reader(bytes.as_slice()).unwrap();
+
+        assert_eq!(from_memory.indices, from_file.indices);
+        assert_eq!(from_memory.vertices.len(), from_file.vertices.len());
+        for (a, b) in from_memory.vertices.iter().zip(&from_file.vertices) {
+            assert_eq!((a.position, a.normal, a.uv), (b.position, b.normal, b.uv));
+        }
+
+        let broken = b"v 0 0 0\nf 1 2 7\n";
+        assert!(ObjMesh::load_from_reader(&broken[..]).is_err());
+    }
+
+    #[test]
+    fn tangents_follow_u_and_flip_handedness_on_mirrored_uvs() {
+        let mut square = unit_square();
+        for vertex in &mut square.vertices {
+            vertex.uv = vertex.position.xy();
+        }
+        square.compute_tangents();
+        for vertex in &square.vertices {
+            assert!((vertex.tangent - Vec4::new(1.0, 0.0, 0.0, 1.0)).magnitude() < 1e-5);
+        }
+
+        // UV reflejadas en u: la tangente apunta hacia -X y la bitangente no cambia.
+        for vertex in &mut square.vertices {
+            vertex.uv.x = 1.0 - vertex.uv.x;
+        }
+        square.compute_tangents();
+        for vertex in &square.vertices {
+            assert!((vertex.tangent - Vec4::new(-1.0, 0.0, 0.0, -1.0)).magnitude() < 1e-5);
+        }
+
+        // En la esfera, cada tangente es unitaria y perpendicular a su normal.
+        let sphere = ObjMesh::create_sphere(1.0, 8, 12);
+        for vertex in &sphere.vertices {
+            let tangent = vertex.tangent.xyz();
+            assert!((tangent.magnitude() - 1.0).abs() < 1e-4);
+            assert!(tangent.dot(&vertex.normal).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn laplacian_smoothing_relaxes_bumps_and_keeps_seams_closed() {
+        // Rejilla de 3x3 en z = 0 con el centro levantado; la mitad de los triángulos usa una
+        // copia del centro (índice 9), como en una costura de UV.
+        let mut positions: Vec<[f32; 3]> = (0..9)
+            .map(|i| {
+                [
+                    (i % 3) as f32,
+                    (i / 3) as f32,
+                    if i == 4 { 1.0 } else { 0.0 },
+                ]
+            })
+            .collect();
+        positions.push([1.0, 1.0, 1.0]);
+        let mut indices = Vec::new();
+        for cell in [0, 1, 3, 4] {
+            let quad = [cell, cell + 1, cell + 4, cell + 3];
+            indices.extend([quad[0], quad[1], quad[2], quad[0], quad[2], quad[3]]);
+        }
+        for index in indices.iter_mut().skip(12) {
+            if *index == 4 {
+                *index = 9;
+            }
+        }
+        let mut mesh = mesh_from(&positions, &indices);
+
+        let original: Vec<Vec3> = mesh.vertices.iter().map(|v| v.position).collect();
+        mesh.smooth_laplacian(0, 0.5);
+        assert!(
+            mesh.vertices
+                .iter()
+                .zip(&original)
+                .all(|(v, p)| v.position == *p),
+            "cero iteraciones no cambian nada"
+        );
+
+        // El centro tiene seis vecinos en z = 0: a mitad de camino queda en z = 0.5, y sus
+        // copias (la costura y las que crea el recálculo de normales) lo acompañan.
+        mesh.smooth_laplacian(1, 0.5);
+        let raised: Vec<&Vertex> = mesh
+            .vertices
+            .iter()
+            .filter(|v| v.position.z != 0.0)
+            .collect();
+        assert!(!raised.is_empty());
+        for vertex in raised {
+            assert_eq!(vertex.position, Vec3::new(1.0, 1.0, 0.5));
+            assert!((vertex.normal - Vec3::z()).magnitude() < 1e-5);
+        }
+
+        // El borde solo se relaja a lo largo de sí mismo: no se levanta y el punto medio de
+        // un lado recto no se mueve.
+        assert!(
+            mesh.vertices
+                .iter()
+                .any(|v| v.position == Vec3::new(1.0, 0.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn load_errors_are_classified() {
+        let missing = ObjMesh::load_from_obj(&asset("does_not_exist.obj")).err();
+        assert!(
+            matches!(&missing, Some(MeshError::Io(e)) if e.kind() == io::ErrorKind::NotFound),
+            "{missing:?}"
+        );
+
+        let empty = ObjMesh::load_from_reader(&b""[..]).err();
+        assert!(matches!(empty, Some(MeshError::Empty)), "{empty:?}");
+        let no_faces = ObjMesh::load_from_reader(&b"v 0 0 0\nv 1 0 0\n"[..]).err();
+        assert!(matches!(no_faces, Some(MeshError::Empty)), "{no_faces:?}");
+
+        let out_of_range = ObjMesh::load_from_reader(&b"v 0 0 0\nf 1 2 7\n"[..]).err();
+        assert!(
+            matches!(out_of_range, Some(MeshError::InvalidIndex(_))),
+            "{out_of_range:?}"
+        );
+
+        let scale =
+            ObjMesh::load_from_obj_transformed(&asset("sphere.obj"), -1.0, Vec3::zeros()).err();
+        assert!(
+            matches!(scale, Some(MeshError::InvalidScale(s)) if s == -1.0),
+            "{scale:?}"
+        );
+
+        let mut mesh = unit_square();
+        mesh.indices.pop();
+        let error = mesh.validate().unwrap_err();
+        assert!(matches!(error, MeshError::InvalidIndex(_)));
+        assert!(
+            error.to_string().starts_with("Invalid mesh indices"),
+            "{error}"
+        );
+    }
 }
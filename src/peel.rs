@@ -0,0 +1,303 @@
+//! Depth peeling para componer capas translúcidas en orden.
+//
+// La mezcla *over* de `BlendMode::Alpha` solo es correcta si los fragmentos llegan de atrás
+// hacia adelante, algo que no se cumple cuando dos cáscaras translúcidas se solapan (la onda
+// de choque de una supernova sobre el núcleo, por ejemplo). Este archivo separa esas mallas
+// en capas por profundidad: cada pasada reutiliza el rasterizador con un destino que solo
+// acepta fragmentos más lejanos que la capa anterior y más cercanos que la geometría opaca,
+// y se queda con el más cercano de ellos. Las capas se acumulan de delante hacia atrás
+// (operador *under*) y el resultado se compone una sola vez sobre el framebuffer.
+
+use crate::framebuffer::{Color, Framebuffer, RenderTarget};
+use nalgebra_glm::{Vec3, Vec4};
+
+/// Número de capas que [`render_frame`](crate::scene::render_frame) separa por defecto.
+///
+/// Con tres capas se resuelven la cara frontal de una cáscara y las dos de otra que quede
+/// dentro; lo que haya más allá se descarta.
+pub const DEFAULT_PEEL_PASSES: usize = 3;
+
+/// Destino de una pasada de depth peeling.
+///
+/// Guarda el fragmento translúcido más cercano de cada píxel entre dos límites: la capa
+/// anterior (exclusiva) y la geometría opaca del framebuffer.
+pub struct PeelLayer {
+    /// Ancho de la capa en píxeles.
+    pub width: usize,
+    /// Alto de la capa en píxeles.
+    pub height: usize,
+    /// Color y opacidad del fragmento de la capa en cada píxel.
+    pub color: Vec<Color>,
+    /// Profundidad del fragmento de la capa (infinita donde no hay ninguno).
+    pub depth: Vec<f32>,
+    /// Profundidad de la capa anterior; solo pasan fragmentos estrictamente más lejanos.
+    peeled: Vec<f32>,
+    /// Profundidad de la geometría opaca; solo pasan fragmentos más cercanos.
+    opaque: Vec<f32>,
+}
+
+impl PeelLayer {
+    /// Crea la primera capa sobre el z-buffer de `framebuffer`.
+    ///
+    /// Los píxeles fuera del recorte (`Framebuffer::scissor`) no aceptan ningún fragmento.
+    pub fn new(framebuffer: &Framebuffer) -> Self {
+        let (width, height) = (framebuffer.width, framebuffer.height);
+        let opaque = (0..width * height)
+            .map(|index| {
+                if framebuffer.writable(index % width, index / width) {
+                    framebuffer.zbuffer[index]
+                } else {
+                    f32::NEG_INFINITY
+                }
+            })
+            .collect();
+        PeelLayer {
+            width,
+            height,
+            color: vec![Color::rgba(0, 0, 0, 0); width * height],
+            depth: vec![f32::INFINITY; width * height],
+            peeled: vec![f32::NEG_INFINITY; width * height],
+            opaque,
+        }
+    }
+
+    /// Pasa a la capa siguiente: la actual se convierte en el límite cercano y se vacía.
+    ///
+    /// Los píxeles sin fragmento en la capa actual quedan cerrados, porque tampoco habrá
+    /// capas más lejanas en ellos.
+    fn advance(&mut self) {
+        std::mem::swap(&mut self.peeled, &mut self.depth);
+        self.depth.fill(f32::INFINITY);
+    }
+}
+
+impl RenderTarget for PeelLayer {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn set_pixel(&mut self, x: usize, y: usize, color: Color, depth: f32) -> bool {
+        if !RenderTarget::depth_test(self, x, y, depth) {
+            return false;
+        }
+        let index = y * self.width + x;
+        self.depth[index] = depth;
+        self.color[index] = color;
+        true
+    }
+
+    fn depth_test(&self, x: usize, y: usize, depth: f32) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        let index = y * self.width + x;
+        depth > self.peeled[index] && depth < self.opaque[index] && depth < self.depth[index]
+    }
+
+    /// Los fragmentos totalmente transparentes no ocupan capa.
+    fn blend_over(&mut self, x: usize, y: usize, color: Color, depth: f32) {
+        if color.a > 0 {
+            self.set_pixel(x, y, color, depth);
+        }
+    }
+}
+
+/// Dibuja mallas translúcidas separadas en hasta `passes` capas y las compone sobre
+/// `framebuffer` de delante hacia atrás.
+///
+/// `draw` se llama una vez por pasada y debe dibujar siempre lo mismo en la capa recibida,
+/// normalmente con [`BlendMode::Alpha`](crate::renderer::BlendMode::Alpha); los fragmentos
+/// con exactamente la misma profundidad que la capa anterior se pierden. Las pasadas
+/// terminan antes si una capa queda vacía. El z-buffer del framebuffer no se modifica.
+///
+/// # Retorna
+/// El número de capas con algún fragmento.
+pub fn render_peeled<F>(framebuffer: &mut Framebuffer, passes: usize, mut draw: F) -> usize
+where
+    F: FnMut(&mut PeelLayer),
+{
+    let mut layer = PeelLayer::new(framebuffer);
+    // Color lineal premultiplicado (xyz) y opacidad (w) acumulados delante de cada píxel.
+    let mut accum = vec![Vec4::zeros(); framebuffer.width * framebuffer.height];
+    let mut layers = 0;
+
+    for _ in 0..passes {
+        draw(&mut layer);
+
+        let mut empty = true;
+        for ((total, &depth), &color) in accum.iter_mut().zip(&layer.depth).zip(&layer.color) {
+            if depth == f32::INFINITY {
+                continue;
+            }
+            empty = false;
+            // Operador *under*: la capa solo aporta a través de lo que aún deja pasar lo
+            // acumulado delante.
+            let alpha = color.a as f32 / 255.0 * (1.0 - total.w);
+            let rgb = color.to_linear() * alpha;
+            *total += Vec4::new(rgb.x, rgb.y, rgb.z, alpha);
+        }
+        if empty {
+            break;
+        }
+        layers += 1;
+        layer.advance();
+    }
+
+    for (index, total) in accum.iter().enumerate() {
+        if total.w <= 0.0 {
+            continue;
+        }
+        let idx = index * 4;
+        let pixel = &mut framebuffer.buffer[idx..idx + 4];
+        let behind = Color::new(pixel[0], pixel[1], pixel[2]).to_linear();
+        let blended =
+            Color::from_linear(Vec3::new(total.x, total.y, total.z) + behind * (1.0 - total.w));
+        pixel[0] = blended.r;
+        pixel[1] = blended.g;
+        pixel[2] = blended.b;
+        let current_alpha = pixel[3] as f32;
+        pixel[3] = (current_alpha + (255.0 - current_alpha) * total.w).round() as u8;
+    }
+    layers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::{ObjMesh, Vertex};
+    use crate::renderer::{BlendMode, Renderer};
+    use crate::shaders::{FragmentInput, StarShader};
+    use nalgebra_glm::{Mat4, Vec2};
+
+    /// Shader de color constante.
+    struct Solid(Color);
+
+    impl StarShader for Solid {
+        fn fragment(&self, _input: &FragmentInput) -> Color {
+            self.0
+        }
+    }
+
+    /// Cuadrado que cubre toda la pantalla a la profundidad `z` (en NDC).
+    fn screen_quad(z: f32) -> ObjMesh {
+        ObjMesh {
+            vertices: [[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]]
+                .iter()
+                .map(|&[x, y]| Vertex {
+                    position: Vec3::new(x, y, z),
+                    normal: Vec3::z(),
+                    uv: Vec2::zeros(),
+                    tangent: Vec4::zeros(),
+                })
+                .collect(),
+            indices: vec![0, 1, 2, 0, 2, 3],
+            submeshes: Vec::new(),
+        }
+    }
+
+    /// Dibuja las capas translúcidas `layers` (profundidad y color), en ese orden, en `target`.
+    fn draw_layers<T: RenderTarget + ?Sized>(target: &mut T, layers: &[(f32, Color)]) {
+        let renderer = Renderer {
+            blend_mode: BlendMode::Alpha,
+            ..Renderer::new(4, 4).with_culling(false)
+        };
+        let identity = Mat4::identity();
+        for &(z, color) in layers {
+            renderer.render_mesh(
+                target,
+                &screen_quad(z),
+                &Solid(color),
+                &identity,
+                &identity,
+                &identity,
+                0.0,
+            );
+        }
+    }
+
+    fn rgb(framebuffer: &Framebuffer) -> [u8; 3] {
+        [
+            framebuffer.buffer[0],
+            framebuffer.buffer[1],
+            framebuffer.buffer[2],
+        ]
+    }
+
+    #[test]
+    fn peeled_layers_compose_in_depth_order_regardless_of_draw_order() {
+        let front = (0.2, Color::rgba(255, 0, 0, 128));
+        let back = (0.5, Color::rgba(0, 0, 255, 128));
+
+        // Referencia: mezcla *over* de atrás hacia adelante, el orden correcto.
+        let mut sorted = Framebuffer::new(4, 4);
+        sorted.clear(Color::new(0, 0, 0));
+        draw_layers(&mut sorted, &[back, front]);
+        // Sin peeling, dibujar en el orden contrario da otro resultado.
+        let mut unsorted = Framebuffer::new(4, 4);
+        unsorted.clear(Color::new(0, 0, 0));
+        draw_layers(&mut unsorted, &[front, back]);
+        assert_ne!(rgb(&sorted), rgb(&unsorted));
+
+        for order in [[front, back], [back, front]] {
+            let mut framebuffer = Framebuffer::new(4, 4);
+            framebuffer.clear(Color::new(0, 0, 0));
+            let layers = render_peeled(&mut framebuffer, DEFAULT_PEEL_PASSES, |layer| {
+                draw_layers(layer, &order)
+            });
+            assert_eq!(layers, 2);
+            for (a, b) in rgb(&framebuffer).iter().zip(rgb(&sorted)) {
+                assert!(
+                    a.abs_diff(b) <= 1,
+                    "{:?} != {:?}",
+                    rgb(&framebuffer),
+                    rgb(&sorted)
+                );
+            }
+            assert!(
+                framebuffer.zbuffer.iter().all(|d| d.is_infinite()),
+                "no escribe profundidad"
+            );
+        }
+    }
+
+    #[test]
+    fn opaque_geometry_hides_layers_behind_it_and_passes_limit_layers() {
+        let mut framebuffer = Framebuffer::new(4, 4);
+        framebuffer.clear(Color::new(0, 0, 0));
+        // Superficie opaca verde entre las dos capas.
+        for index in 0..16 {
+            framebuffer.set_pixel(index % 4, index / 4, Color::new(0, 255, 0), 0.4);
+        }
+        let layers = render_peeled(&mut framebuffer, DEFAULT_PEEL_PASSES, |layer| {
+            draw_layers(
+                layer,
+                &[
+                    (0.5, Color::rgba(0, 0, 255, 255)),
+                    (0.2, Color::rgba(255, 0, 0, 128)),
+                ],
+            )
+        });
+        assert_eq!(layers, 1, "la capa azul queda detrás de la superficie");
+        // Alfa 128/255 en espacio lineal: un poco más de rojo que de verde.
+        assert_eq!(rgb(&framebuffer), [188, 187, 0]);
+
+        // Con una sola pasada solo se compone la capa más cercana.
+        let mut framebuffer = Framebuffer::new(4, 4);
+        framebuffer.clear(Color::new(0, 0, 0));
+        let layers = render_peeled(&mut framebuffer, 1, |layer| {
+            draw_layers(
+                layer,
+                &[
+                    (0.5, Color::rgba(0, 0, 255, 255)),
+                    (0.2, Color::rgba(255, 0, 0, 255)),
+                ],
+            )
+        });
+        assert_eq!(layers, 1);
+        assert_eq!(rgb(&framebuffer), [255, 0, 0]);
+    }
+}
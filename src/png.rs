@@ -0,0 +1,183 @@
+//! Codificación mínima de imágenes PNG.
+//
+// Este archivo escribe el framebuffer como PNG sin dependencias externas. Los datos se
+// guardan en bloques DEFLATE sin comprimir: los archivos son más grandes que con un
+// compresor real, pero cualquier visor los abre y el código cabe en unas pocas funciones.
+
+use crate::framebuffer::Framebuffer; // Origen de los píxeles a exportar.
+
+/// Firma de 8 bytes con la que empieza todo archivo PNG.
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+/// Tamaño máximo de un bloque DEFLATE sin comprimir.
+const MAX_STORED_BLOCK: usize = 65_535;
+
+/// Codifica el framebuffer como PNG RGBA de 8 bits por canal.
+pub fn encode_framebuffer(fb: &Framebuffer) -> Vec<u8> {
+    encode_rgba(fb.width, fb.height, &fb.buffer)
+}
+
+/// Codifica una imagen RGBA de 8 bits por canal, ordenada por filas de arriba hacia abajo.
+///
+/// # Argumentos
+/// * `width`, `height` - Dimensiones en píxeles.
+/// * `rgba` - Píxeles, `width * height * 4` bytes.
+///
+/// # Retorna
+/// El contenido completo del archivo PNG.
+pub fn encode_rgba(width: usize, height: usize, rgba: &[u8]) -> Vec<u8> {
+    assert_eq!(rgba.len(), width * height * 4, "RGBA buffer size mismatch");
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    // Profundidad 8, color RGBA (6), compresión, filtro y entrelazado por defecto.
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    // Cada fila va precedida por su tipo de filtro (0: ninguno).
+    let mut raw = Vec::with_capacity(height * (width * 4 + 1));
+    for row in rgba.chunks_exact(width * 4).take(height) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    let mut png = SIGNATURE.to_vec();
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+/// Añade un chunk PNG: longitud, tipo, datos y CRC del tipo y los datos.
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Envuelve los datos en un flujo zlib con bloques DEFLATE sin comprimir.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let blocks = data.len().div_ceil(MAX_STORED_BLOCK).max(1);
+    let mut out = Vec::with_capacity(data.len() + blocks * 5 + 6);
+    // Cabecera zlib: DEFLATE con ventana de 32 KiB, sin diccionario.
+    out.extend_from_slice(&[0x78, 0x01]);
+
+    let mut chunks = data.chunks(MAX_STORED_BLOCK).peekable();
+    if chunks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(chunk) = chunks.next() {
+        let last = chunks.peek().is_none();
+        let len = chunk.len() as u16;
+        out.push(last as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(chunk);
+    }
+
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+/// Suma de verificación CRC-32 (polinomio 0xEDB88320) usada por los chunks PNG.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// Suma de verificación Adler-32 del flujo zlib.
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65_521;
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksums_match_reference_values() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    /// Recupera los datos de un flujo zlib formado solo por bloques sin comprimir.
+    fn inflate_stored(zlib: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut pos = 2;
+        loop {
+            let last = zlib[pos] & 1 == 1;
+            let len = u16::from_le_bytes([zlib[pos + 1], zlib[pos + 2]]) as usize;
+            let nlen = u16::from_le_bytes([zlib[pos + 3], zlib[pos + 4]]);
+            assert_eq!(nlen, !(len as u16));
+            data.extend_from_slice(&zlib[pos + 5..pos + 5 + len]);
+            pos += 5 + len;
+            if last {
+                break;
+            }
+        }
+        assert_eq!(&zlib[pos..], adler32(&data).to_be_bytes());
+        data
+    }
+
+    #[test]
+    fn encoded_png_has_valid_chunks_and_round_trips_pixels() {
+        // Suficientes filas para que los datos ocupen varios bloques DEFLATE.
+        let (width, height) = (130, 140);
+        let rgba: Vec<u8> = (0..width * height * 4)
+            .map(|i| (i * 7 % 251) as u8)
+            .collect();
+        let png = encode_rgba(width, height, &rgba);
+        assert_eq!(png[..8], SIGNATURE);
+
+        let mut pos = 8;
+        let mut chunks = Vec::new();
+        let mut idat = Vec::new();
+        while pos < png.len() {
+            let len = u32::from_be_bytes(png[pos..pos + 4].try_into().unwrap()) as usize;
+            let body = &png[pos + 4..pos + 8 + len];
+            let crc = u32::from_be_bytes(png[pos + 8 + len..pos + 12 + len].try_into().unwrap());
+            assert_eq!(crc, crc32(body), "CRC del chunk");
+            let kind = std::str::from_utf8(&body[..4]).unwrap().to_string();
+            if kind == "IHDR" {
+                assert_eq!(body[4..8], (width as u32).to_be_bytes());
+                assert_eq!(body[8..12], (height as u32).to_be_bytes());
+            } else if kind == "IDAT" {
+                idat = body[4..].to_vec();
+            }
+            chunks.push(kind);
+            pos += 12 + len;
+        }
+        assert_eq!(chunks, ["IHDR", "IDAT", "IEND"]);
+
+        let raw = inflate_stored(&idat);
+        assert_eq!(raw.len(), height * (width * 4 + 1));
+        for (row, pixels) in raw
+            .chunks_exact(width * 4 + 1)
+            .zip(rgba.chunks_exact(width * 4))
+        {
+            assert_eq!(row[0], 0, "sin filtro");
+            assert_eq!(&row[1..], pixels);
+        }
+    }
+}
@@ -0,0 +1,474 @@
+//! Módulo de post-procesado sobre el framebuffer.
+//
+// Este archivo reúne filtros que operan directamente sobre el búfer RGBA ya renderizado,
+// sin pasar por el pipeline 3D. Todos trabajan in situ y dejan intacto el canal alfa.
+
+use crate::framebuffer::Framebuffer; // Framebuffer con el búfer de color a modificar.
+
+/// Pesos de luminancia Rec. 709 para los canales R, G y B.
+const REC709_WEIGHTS: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+/// Calcula la luminancia Rec. 709 de un color RGB de 8 bits.
+#[inline]
+pub fn luminance(r: u8, g: u8, b: u8) -> u8 {
+    let y =
+        REC709_WEIGHTS[0] * r as f32 + REC709_WEIGHTS[1] * g as f32 + REC709_WEIGHTS[2] * b as f32;
+    y.round().clamp(0.0, 255.0) as u8
+}
+
+/// Convierte cada píxel a escala de grises usando su luminancia Rec. 709.
+pub fn grayscale(fb: &mut Framebuffer) {
+    for pixel in fb.buffer.chunks_exact_mut(4) {
+        let y = luminance(pixel[0], pixel[1], pixel[2]);
+        pixel[0] = y;
+        pixel[1] = y;
+        pixel[2] = y;
+    }
+}
+
+/// Invierte cada canal de color (`255 - c`). Aplicarlo dos veces deja la imagen original.
+pub fn invert(fb: &mut Framebuffer) {
+    for pixel in fb.buffer.chunks_exact_mut(4) {
+        pixel[0] = 255 - pixel[0];
+        pixel[1] = 255 - pixel[1];
+        pixel[2] = 255 - pixel[2];
+    }
+}
+
+/// Aplica aberración cromática radial: separa los canales R, G y B hacia los bordes.
+///
+/// Para cada píxel, el rojo se lee desplazado hacia afuera del centro y el azul hacia
+/// adentro, mientras el verde se mantiene. El desplazamiento crece linealmente con la
+/// distancia al centro, por lo que el centro de la imagen no se altera.
+///
+/// # Argumentos
+/// * `fb` - Framebuffer a modificar.
+/// * `amount` - Desplazamiento máximo en píxeles, alcanzado en las esquinas.
+pub fn chromatic_aberration(fb: &mut Framebuffer, amount: f32) {
+    if amount == 0.0 || fb.width == 0 || fb.height == 0 {
+        return;
+    }
+
+    // Copia de origen: los canales se leen de posiciones desplazadas.
+    let source = fb.buffer.clone();
+    let (width, height) = (fb.width, fb.height);
+    let center_x = width as f32 * 0.5;
+    let center_y = height as f32 * 0.5;
+    let max_radius = (center_x * center_x + center_y * center_y).sqrt();
+
+    // Lee un canal del origen en la posición más cercana, limitada a la imagen.
+    let sample = |x: f32, y: f32, channel: usize| -> u8 {
+        let sx = (x.round().max(0.0) as usize).min(width - 1);
+        let sy = (y.round().max(0.0) as usize).min(height - 1);
+        source[(sy * width + sx) * 4 + channel]
+    };
+
+    for y in 0..height {
+        for x in 0..width {
+            let dx = (x as f32 - center_x) / max_radius * amount;
+            let dy = (y as f32 - center_y) / max_radius * amount;
+
+            let idx = (y * width + x) * 4;
+            fb.buffer[idx] = sample(x as f32 + dx, y as f32 + dy, 0);
+            fb.buffer[idx + 2] = sample(x as f32 - dx, y as f32 - dy, 2);
+        }
+    }
+}
+
+/// Matriz de Bayer 4x4 para dithering ordenado (valores 0-15).
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Aplica dithering ordenado para disimular el banding de los degradados en 8 bits.
+///
+/// Suma a cada canal un patrón de Bayer de ±1 LSB indexado por la posición del píxel.
+/// El patrón rompe los escalones visibles de degradados suaves (como la corona del sol)
+/// sin añadir ruido perceptible.
+pub fn dither(fb: &mut Framebuffer) {
+    let width = fb.width;
+    for (i, pixel) in fb.buffer.chunks_exact_mut(4).enumerate() {
+        let (x, y) = (i % width, i / width);
+
+        // Umbral centrado en [-1, 1) LSB; tras redondear da -1, 0 o +1.
+        let threshold = (BAYER_4X4[y % 4][x % 4] as f32 + 0.5) / 16.0 * 2.0 - 1.0;
+        for channel in pixel.iter_mut().take(3) {
+            *channel = (*channel as f32 + threshold).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+/// Sesgo angular de la oclusión ambiental, para que las superficies casi planas
+/// (con pequeñas variaciones por la interpolación) no se oscurezcan.
+const SSAO_BIAS: f32 = 0.1;
+
+/// Oscurece las cavidades con oclusión ambiental en espacio de pantalla (SSAO).
+///
+/// Requiere el G-buffer del framebuffer ([`Framebuffer::enable_gbuffer`]); sin él no hace
+/// nada. Para cada píxel con geometría, muestrea vecinos en ocho direcciones a dos
+/// distancias y acumula cuánto sobresalen por encima del plano tangente del píxel: una
+/// superficie plana no se ocluye, mientras que un pliegue cóncavo se oscurece.
+///
+/// # Argumentos
+/// * `fb` - Framebuffer con G-buffer a modificar.
+/// * `radius` - Radio de muestreo en píxeles.
+/// * `strength` - Intensidad del oscurecimiento [0.0, 1.0].
+pub fn ssao(fb: &mut Framebuffer, radius: usize, strength: f32) {
+    let Some(gbuffer) = &fb.gbuffer else {
+        return;
+    };
+    if radius == 0 || strength <= 0.0 {
+        return;
+    }
+
+    let (width, height) = (fb.width as i64, fb.height as i64);
+    let steps = [(radius as i64 / 2).max(1), radius as i64];
+    let directions = [
+        (1, 0),
+        (1, 1),
+        (0, 1),
+        (-1, 1),
+        (-1, 0),
+        (-1, -1),
+        (0, -1),
+        (1, -1),
+    ];
+
+    // Calcula primero el factor de cada píxel, leyendo el G-buffer sin modificarlo.
+    let mut factors = vec![1.0f32; fb.width * fb.height];
+    for y in 0..height {
+        for x in 0..width {
+            let index = (y * width + x) as usize;
+            if fb.zbuffer[index] == f32::INFINITY {
+                continue;
+            }
+            let position = gbuffer.positions[index];
+            let normal = gbuffer.normals[index];
+
+            let mut occlusion = 0.0;
+            let mut samples = 0;
+            for (dx, dy) in directions {
+                for step in steps {
+                    let (sx, sy) = (x + dx * step, y + dy * step);
+                    if sx < 0 || sy < 0 || sx >= width || sy >= height {
+                        continue;
+                    }
+                    let neighbor = (sy * width + sx) as usize;
+                    if fb.zbuffer[neighbor] == f32::INFINITY {
+                        continue;
+                    }
+
+                    samples += 1;
+                    let offset = gbuffer.positions[neighbor] - position;
+                    let distance = offset.magnitude();
+                    if distance > 1e-6 {
+                        // Vecinos por encima del plano tangente ocluyen; los lejanos pesan menos.
+                        let elevation = normal.dot(&offset) / distance - SSAO_BIAS;
+                        occlusion += elevation.max(0.0) / (1.0 + distance * distance);
+                    }
+                }
+            }
+
+            if samples > 0 {
+                factors[index] = (1.0 - strength * occlusion / samples as f32).clamp(0.0, 1.0);
+            }
+        }
+    }
+
+    for (pixel, factor) in fb.buffer.chunks_exact_mut(4).zip(factors) {
+        for channel in &mut pixel[..3] {
+            *channel = (*channel as f32 * factor).round() as u8;
+        }
+    }
+}
+
+/// Contraste mínimo de luminancia (en [0, 1]) para que FXAA trate un píxel como borde.
+const FXAA_EDGE_THRESHOLD_MIN: f32 = 0.0312;
+/// Contraste mínimo relativo a la luminancia máxima del vecindario.
+const FXAA_EDGE_THRESHOLD: f32 = 0.125;
+/// Peso máximo del suavizado de subpíxel (detalles más finos que un píxel).
+const FXAA_SUBPIXEL_QUALITY: f32 = 0.75;
+/// Pasos máximos al buscar los extremos de un borde a cada lado del píxel.
+const FXAA_SEARCH_STEPS: i64 = 12;
+
+/// Suaviza los bordes con FXAA (*Fast Approximate Anti-Aliasing*).
+///
+/// Versión simplificada de FXAA 3.11 de Timothy Lottes. Para cada píxel con suficiente
+/// contraste de luminancia en su vecindario de 3x3:
+/// 1. Decide si el borde es horizontal o vertical y hacia qué vecino cruza.
+/// 2. Recorre el borde en ambos sentidos hasta encontrar sus extremos; cuanto más cerca
+///    esté el píxel del extremo donde el escalón cambia de lado, más se mezcla con el
+///    vecino del otro lado del borde.
+/// 3. Añade un suavizado de subpíxel según cuánto difiere el píxel del promedio de sus
+///    vecinos, para detalles más finos que un píxel.
+///
+/// Se lee de una copia del búfer, de modo que el resultado no depende del orden de
+/// recorrido. No necesita el z-buffer: suaviza tanto siluetas como transiciones bruscas
+/// dentro de los shaders.
+pub fn fxaa(fb: &mut Framebuffer) {
+    let (width, height) = (fb.width as i64, fb.height as i64);
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let source = fb.buffer.clone();
+    let lumas: Vec<f32> = source
+        .chunks_exact(4)
+        .map(|p| luminance(p[0], p[1], p[2]) as f32 / 255.0)
+        .collect();
+    let index = |x: i64, y: i64| (y.clamp(0, height - 1) * width + x.clamp(0, width - 1)) as usize;
+    let luma = |x: i64, y: i64| lumas[index(x, y)];
+
+    for y in 0..height {
+        for x in 0..width {
+            let center = luma(x, y);
+            let (north, south) = (luma(x, y - 1), luma(x, y + 1));
+            let (west, east) = (luma(x - 1, y), luma(x + 1, y));
+
+            let luma_max = center.max(north).max(south).max(west).max(east);
+            let luma_min = center.min(north).min(south).min(west).min(east);
+            let range = luma_max - luma_min;
+            if range < FXAA_EDGE_THRESHOLD_MIN.max(luma_max * FXAA_EDGE_THRESHOLD) {
+                continue;
+            }
+
+            let (north_west, north_east) = (luma(x - 1, y - 1), luma(x + 1, y - 1));
+            let (south_west, south_east) = (luma(x - 1, y + 1), luma(x + 1, y + 1));
+
+            // Suavizado de subpíxel: diferencia con el promedio ponderado de los vecinos.
+            let average = (2.0 * (north + south + west + east)
+                + north_west
+                + north_east
+                + south_west
+                + south_east)
+                / 12.0;
+            let subpixel = ((average - center).abs() / range).clamp(0.0, 1.0);
+            let subpixel = subpixel * subpixel * (3.0 - 2.0 * subpixel);
+            let subpixel_blend = subpixel * subpixel * FXAA_SUBPIXEL_QUALITY;
+
+            // Orientación del borde: un borde horizontal varía sobre todo en vertical.
+            let edge_horizontal = (north_west - 2.0 * west + south_west).abs()
+                + 2.0 * (north - 2.0 * center + south).abs()
+                + (north_east - 2.0 * east + south_east).abs();
+            let edge_vertical = (north_west - 2.0 * north + north_east).abs()
+                + 2.0 * (west - 2.0 * center + east).abs()
+                + (south_west - 2.0 * south + south_east).abs();
+            let horizontal = edge_horizontal >= edge_vertical;
+
+            // Lado del borde con mayor gradiente: el vecino con el que se mezcla.
+            let (luma_negative, luma_positive) = if horizontal {
+                (north, south)
+            } else {
+                (west, east)
+            };
+            let gradient_negative = luma_negative - center;
+            let gradient_positive = luma_positive - center;
+            let (side, luma_side) = if gradient_negative.abs() >= gradient_positive.abs() {
+                (-1, luma_negative)
+            } else {
+                (1, luma_positive)
+            };
+            let gradient_scaled = 0.25 * gradient_negative.abs().max(gradient_positive.abs());
+            let local_average = 0.5 * (center + luma_side);
+
+            // Recorre el borde (entre el píxel y su vecino) en ambos sentidos hasta que la
+            // luminancia media deja de parecerse a la del borde.
+            let (across_x, across_y) = if horizontal { (0, side) } else { (side, 0) };
+            let (along_x, along_y) = if horizontal { (1, 0) } else { (0, 1) };
+            let edge_luma = |step: i64| {
+                let (sx, sy) = (x + along_x * step, y + along_y * step);
+                0.5 * (luma(sx, sy) + luma(sx + across_x, sy + across_y)) - local_average
+            };
+            let search = |direction: i64| {
+                let mut end_luma = 0.0;
+                for step in 1..=FXAA_SEARCH_STEPS {
+                    end_luma = edge_luma(step * direction);
+                    if end_luma.abs() >= gradient_scaled {
+                        return (step, end_luma);
+                    }
+                }
+                (FXAA_SEARCH_STEPS, end_luma)
+            };
+            let (distance_negative, end_negative) = search(-1);
+            let (distance_positive, end_positive) = search(1);
+
+            // Desplazamiento hacia el vecino según la distancia al extremo más cercano. Solo
+            // se aplica si en ese extremo el escalón queda del lado contrario al píxel.
+            let (distance, end_luma) = if distance_negative < distance_positive {
+                (distance_negative, end_negative)
+            } else {
+                (distance_positive, end_positive)
+            };
+            let edge_length = (distance_negative + distance_positive) as f32;
+            let center_smaller = center < local_average;
+            let edge_blend = if (end_luma < 0.0) != center_smaller {
+                0.5 - distance as f32 / edge_length
+            } else {
+                0.0
+            };
+
+            let blend = edge_blend.max(subpixel_blend);
+            if blend <= 0.0 {
+                continue;
+            }
+            let target = index(x, y) * 4;
+            let neighbor = index(x + across_x, y + across_y) * 4;
+            for channel in 0..3 {
+                let a = source[target + channel] as f32;
+                let b = source[neighbor + channel] as f32;
+                fb.buffer[target + channel] = (a + (b - a) * blend).round() as u8;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::Color;
+    use nalgebra_glm::Vec3;
+
+    /// Framebuffer con un patrón de colores variado en cada píxel.
+    fn patterned(width: usize, height: usize) -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(width, height);
+        for (i, byte) in framebuffer.buffer.iter_mut().enumerate() {
+            *byte = (i * 37 % 256) as u8;
+        }
+        framebuffer
+    }
+
+    #[test]
+    fn pure_red_luminance_uses_rec709_weight() {
+        // 0.2126 * 255 = 54.2
+        assert_eq!(luminance(255, 0, 0), 54);
+        let mut framebuffer = Framebuffer::new(1, 1);
+        framebuffer.buffer.copy_from_slice(&[255, 0, 0, 255]);
+        grayscale(&mut framebuffer);
+        assert_eq!(framebuffer.buffer, [54, 54, 54, 255]);
+    }
+
+    #[test]
+    fn double_invert_is_identity() {
+        let original = patterned(7, 5);
+        let mut framebuffer = patterned(7, 5);
+        invert(&mut framebuffer);
+        assert_ne!(framebuffer.buffer, original.buffer);
+        invert(&mut framebuffer);
+        assert_eq!(framebuffer.buffer, original.buffer);
+    }
+
+    #[test]
+    fn zero_chromatic_aberration_leaves_buffer_unchanged() {
+        let original = patterned(9, 6);
+        let mut framebuffer = patterned(9, 6);
+        chromatic_aberration(&mut framebuffer, 0.0);
+        assert_eq!(framebuffer.buffer, original.buffer);
+
+        chromatic_aberration(&mut framebuffer, 4.0);
+        assert_ne!(framebuffer.buffer, original.buffer);
+    }
+
+    #[test]
+    fn dither_changes_some_but_not_all_gradient_pixels() {
+        let (width, height) = (64, 8);
+        let mut framebuffer = Framebuffer::new(width, height);
+        for (i, pixel) in framebuffer.buffer.chunks_exact_mut(4).enumerate() {
+            let value = (i % width * 2) as u8;
+            pixel.copy_from_slice(&[value, value, value, 255]);
+        }
+        let original = framebuffer.buffer.clone();
+        dither(&mut framebuffer);
+
+        let pixels = framebuffer
+            .buffer
+            .chunks_exact(4)
+            .zip(original.chunks_exact(4));
+        let changed = pixels.clone().filter(|(a, b)| a != b).count();
+        assert!(changed > 0 && changed < width * height, "{changed}");
+        // El ruido nunca se aleja más de un nivel del valor original.
+        for (after, before) in pixels {
+            assert!(after[0].abs_diff(before[0]) <= 1);
+            assert_eq!(after[3], before[3]);
+        }
+    }
+
+    /// Framebuffer gris con G-buffer cuya superficie tiene altura `height(x)` por columna.
+    fn heightfield(height: impl Fn(f32) -> f32, slope: impl Fn(f32) -> f32) -> Framebuffer {
+        let mut fb = Framebuffer::new(16, 16);
+        fb.enable_gbuffer();
+        for y in 0..16 {
+            for x in 0..16 {
+                // Píxeles separados 0.1 unidades en espacio mundo.
+                let fx = x as f32 * 0.1;
+                let position = Vec3::new(fx, y as f32 * 0.1, height(fx));
+                let normal = Vec3::new(-slope(fx), 0.0, 1.0).normalize();
+                fb.set_fragment(x, y, Color::new(200, 200, 200), 0.5, position, normal);
+            }
+        }
+        fb
+    }
+
+    #[test]
+    fn ssao_darkens_creases_but_not_flat_surfaces() {
+        let mut flat = heightfield(|_| 0.0, |_| 0.0);
+        ssao(&mut flat, 4, 1.0);
+        assert!(
+            flat.buffer
+                .chunks_exact(4)
+                .all(|p| p[..3] == [200, 200, 200])
+        );
+
+        // Valle en V a lo largo de la columna 8.
+        let mut valley = heightfield(|x| (x - 0.8).abs(), |x| (x - 0.8).signum());
+        ssao(&mut valley, 4, 1.0);
+        let crease = valley.buffer[(8 * 16 + 8) * 4];
+        assert!(crease < 180, "el pliegue se oscurece: {crease}");
+
+        // Sin G-buffer el filtro no hace nada.
+        let mut plain = Framebuffer::new(4, 4);
+        plain.clear(Color::new(200, 200, 200));
+        plain.set_pixel(1, 1, Color::new(100, 100, 100), 0.5);
+        let before = plain.buffer.clone();
+        ssao(&mut plain, 4, 1.0);
+        assert_eq!(plain.buffer, before);
+    }
+
+    #[test]
+    fn fxaa_blends_jagged_edges_and_keeps_flat_areas() {
+        let mut flat = Framebuffer::new(8, 8);
+        flat.clear(Color::new(120, 40, 200));
+        let before = flat.buffer.clone();
+        fxaa(&mut flat);
+        assert_eq!(flat.buffer, before);
+
+        // Escalera blanca sobre negro: borde con pendiente 1/4 (un escalón cada 4 columnas).
+        let (width, height) = (32, 16);
+        let mut framebuffer = Framebuffer::new(width, height);
+        framebuffer.clear(Color::new(0, 0, 0));
+        for y in 0..height {
+            for x in 0..width {
+                if y > 4 + x / 4 {
+                    framebuffer.set_pixel(x, y, Color::new(255, 255, 255), 0.5);
+                }
+            }
+        }
+        let original = framebuffer.buffer.clone();
+        fxaa(&mut framebuffer);
+
+        let intermediate = framebuffer
+            .buffer
+            .chunks_exact(4)
+            .filter(|p| p[0] > 0 && p[0] < 255)
+            .count();
+        assert!(intermediate > 8, "{intermediate} píxeles suavizados");
+        for (after, before) in framebuffer
+            .buffer
+            .chunks_exact(4)
+            .zip(original.chunks_exact(4))
+        {
+            assert_eq!(after[3], before[3], "el alfa no cambia");
+            assert!(
+                after[0] == after[1] && after[1] == after[2],
+                "mezcla en gris"
+            );
+        }
+    }
+}
@@ -0,0 +1,270 @@
+//! Utilidades de rasterización compartidas.
+//
+// Este archivo reúne el cálculo de coordenadas baricéntricas usado tanto por el pipeline 3D
+// del renderizador como por el dibujo 2D directo sobre el framebuffer, y las funciones de
+// arista que permiten recorrer un triángulo de forma incremental.
+
+use nalgebra_glm::{Vec2, Vec3}; // Coordenadas 2D en pantalla y pesos baricéntricos.
+
+/// Términos constantes de un triángulo para calcular coordenadas baricéntricas.
+///
+/// `d00`, `d01`, `d11` y el denominador solo dependen de los vértices, así que se calculan
+/// una vez por triángulo; por píxel solo quedan dos productos punto. El resultado es
+/// idéntico, bit a bit, al de [`barycentric`].
+#[derive(Debug, Clone, Copy)]
+pub struct BarycentricSetup {
+    a: Vec2,
+    v0: Vec2,
+    v1: Vec2,
+    d00: f32,
+    d01: f32,
+    d11: f32,
+    denom: f32,
+}
+
+impl BarycentricSetup {
+    /// Precalcula los términos del triángulo `a`, `b`, `c`.
+    #[inline]
+    pub fn new(a: &Vec2, b: &Vec2, c: &Vec2) -> Self {
+        let v0 = *b - *a;
+        let v1 = *c - *a;
+
+        let d00 = v0.dot(&v0);
+        let d01 = v0.dot(&v1);
+        let d11 = v1.dot(&v1);
+
+        BarycentricSetup {
+            a: *a,
+            v0,
+            v1,
+            d00,
+            d01,
+            d11,
+            denom: d00 * d11 - d01 * d01,
+        }
+    }
+
+    /// Indica si el triángulo es degenerado (área prácticamente nula).
+    #[inline]
+    pub fn is_degenerate(&self) -> bool {
+        self.denom.abs() < 1e-8
+    }
+
+    /// Pesos baricéntricos (u, v, w) del punto `p`; `(0, 0, 0)` si el triángulo es degenerado.
+    #[inline]
+    pub fn weights(&self, p: &Vec2) -> (f32, f32, f32) {
+        if self.is_degenerate() {
+            return (0.0, 0.0, 0.0);
+        }
+
+        let v2 = *p - self.a;
+        let d20 = v2.dot(&self.v0);
+        let d21 = v2.dot(&self.v1);
+
+        let v = (self.d11 * d20 - self.d01 * d21) / self.denom;
+        let w = (self.d00 * d21 - self.d01 * d20) / self.denom;
+        let u = 1.0 - v - w;
+
+        (u, v, w)
+    }
+}
+
+/// Calcula las coordenadas baricéntricas de un punto respecto a un triángulo.
+///
+/// Para evaluar muchos puntos del mismo triángulo conviene [`BarycentricSetup`].
+///
+/// # Argumentos
+/// * `p` - Punto a evaluar.
+/// * `a`, `b`, `c` - Vértices del triángulo.
+///
+/// # Retorna
+/// Tupla con los pesos baricéntricos (u, v, w).
+#[inline]
+pub fn barycentric(p: &Vec2, a: &Vec2, b: &Vec2, c: &Vec2) -> (f32, f32, f32) {
+    BarycentricSetup::new(a, b, c).weights(p)
+}
+
+/// Funciones de arista de un triángulo, expresadas directamente como pesos baricéntricos.
+///
+/// Cada peso es una función lineal de la posición en pantalla, así que en lugar de resolver
+/// el sistema en cada píxel basta con evaluarla una vez y sumar un incremento constante
+/// por cada paso en X o en Y. Los pesos coinciden con los de [`barycentric`] salvo por el
+/// redondeo en punto flotante.
+#[derive(Debug, Clone, Copy)]
+pub struct EdgeFunctions {
+    /// Primer vértice del triángulo, donde los pesos valen (1, 0, 0). Evaluar respecto a él
+    /// (y no al origen de la pantalla) evita perder precisión en triángulos muy delgados.
+    anchor: Vec2,
+    /// Incremento de los pesos al avanzar un píxel en X.
+    pub step_x: Vec3,
+    /// Incremento de los pesos al avanzar un píxel en Y.
+    pub step_y: Vec3,
+}
+
+impl EdgeFunctions {
+    /// Prepara las funciones de arista del triángulo `a`, `b`, `c`.
+    ///
+    /// Un triángulo degenerado produce pesos nulos en todas partes, igual que [`barycentric`].
+    pub fn new(a: &Vec2, b: &Vec2, c: &Vec2) -> Self {
+        let ab = *b - *a;
+        let ac = *c - *a;
+        // Mismo criterio de degeneración que `barycentric`, para cubrir los mismos triángulos.
+        if BarycentricSetup::new(a, b, c).is_degenerate() {
+            return EdgeFunctions {
+                anchor: *a,
+                step_x: Vec3::zeros(),
+                step_y: Vec3::zeros(),
+            };
+        }
+
+        // Doble del área con signo. v = (p - a) x ac / area,  w = ab x (p - a) / area,
+        // u = 1 - v - w.
+        let area = ab.x * ac.y - ab.y * ac.x;
+        let dv = Vec2::new(ac.y, -ac.x) / area;
+        let dw = Vec2::new(-ab.y, ab.x) / area;
+
+        EdgeFunctions {
+            anchor: *a,
+            step_x: Vec3::new(-dv.x - dw.x, dv.x, dw.x),
+            step_y: Vec3::new(-dv.y - dw.y, dv.y, dw.y),
+        }
+    }
+
+    /// Indica si el triángulo es degenerado (área prácticamente nula).
+    #[inline]
+    pub fn is_degenerate(&self) -> bool {
+        self.step_x == Vec3::zeros() && self.step_y == Vec3::zeros()
+    }
+
+    /// Evalúa los pesos (u, v, w) en el punto `p`.
+    #[inline]
+    pub fn at(&self, p: &Vec2) -> Vec3 {
+        if self.is_degenerate() {
+            return Vec3::zeros();
+        }
+        let d = *p - self.anchor;
+        Vec3::new(1.0, 0.0, 0.0) + self.step_x * d.x + self.step_y * d.y
+    }
+
+    /// Intervalo de X, sobre la fila horizontal de altura `y`, en el que los tres pesos
+    /// cumplen `peso >= -slack`; `None` si la fila no corta el triángulo (así ampliado).
+    ///
+    /// Cada peso es lineal en X, así que cada arista acota el intervalo por un lado y basta
+    /// intersecar las tres cotas. Sirve para recorrer solo el tramo cubierto de cada fila
+    /// en lugar de todo el bounding box. Los extremos están sujetos al redondeo en punto
+    /// flotante: quien necesite cubrir exactamente los mismos píxeles debe ampliarlos.
+    pub fn row_span(&self, y: f32, slack: &Vec3) -> Option<(f32, f32)> {
+        if self.is_degenerate() {
+            return None;
+        }
+        let row_start = self.at(&Vec2::new(0.0, y)) + slack;
+        let (mut low, mut high) = (f32::NEG_INFINITY, f32::INFINITY);
+        for i in 0..3 {
+            let (value, step) = (row_start[i], self.step_x[i]);
+            if step == 0.0 {
+                // Arista horizontal: el peso es constante en toda la fila.
+                if value < 0.0 {
+                    return None;
+                }
+            } else if step > 0.0 {
+                low = low.max(-value / step);
+            } else {
+                high = high.min(-value / step);
+            }
+        }
+        (low <= high).then_some((low, high))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn barycentric_weights_at_vertices_and_centroid() {
+        let (a, b, c) = (
+            Vec2::new(0.0, 0.0),
+            Vec2::new(4.0, 0.0),
+            Vec2::new(0.0, 4.0),
+        );
+        assert_eq!(barycentric(&a, &a, &b, &c), (1.0, 0.0, 0.0));
+        assert_eq!(barycentric(&c, &a, &b, &c), (0.0, 0.0, 1.0));
+
+        let (u, v, w) = barycentric(&((a + b + c) / 3.0), &a, &b, &c);
+        for weight in [u, v, w] {
+            assert!((weight - 1.0 / 3.0).abs() < 1e-6);
+        }
+        // Fuera del triángulo algún peso es negativo.
+        let (u, _, _) = barycentric(&Vec2::new(4.0, 4.0), &a, &b, &c);
+        assert!(u < 0.0);
+    }
+
+    #[test]
+    fn setup_weights_match_the_direct_formula_bit_for_bit() {
+        let (a, b, c) = (
+            Vec2::new(3.2, 1.7),
+            Vec2::new(40.9, 12.3),
+            Vec2::new(11.4, 35.8),
+        );
+        let setup = BarycentricSetup::new(&a, &b, &c);
+        for y in 0..40 {
+            for x in 0..45 {
+                let p = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+                // Fórmula original, resolviendo el sistema completo en cada punto.
+                let (v0, v1, v2) = (b - a, c - a, p - a);
+                let (d00, d01, d11) = (v0.dot(&v0), v0.dot(&v1), v1.dot(&v1));
+                let (d20, d21) = (v2.dot(&v0), v2.dot(&v1));
+                let denom = d00 * d11 - d01 * d01;
+                let v = (d11 * d20 - d01 * d21) / denom;
+                let w = (d00 * d21 - d01 * d20) / denom;
+                assert_eq!(setup.weights(&p), (1.0 - v - w, v, w));
+            }
+        }
+        assert!(BarycentricSetup::new(&a, &a, &b).is_degenerate());
+    }
+
+    #[test]
+    fn edge_functions_step_like_direct_barycentrics() {
+        let (a, b, c) = (
+            Vec2::new(3.2, 1.7),
+            Vec2::new(40.9, 12.3),
+            Vec2::new(11.4, 35.8),
+        );
+        let edges = EdgeFunctions::new(&a, &b, &c);
+        assert_eq!(edges.at(&a), Vec3::new(1.0, 0.0, 0.0));
+
+        for y in 0..40 {
+            let mut stepped = edges.at(&Vec2::new(0.5, y as f32 + 0.5));
+            for x in 0..45 {
+                let p = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let (u, v, w) = barycentric(&p, &a, &b, &c);
+                assert!((edges.at(&p) - Vec3::new(u, v, w)).magnitude() < 1e-4);
+                assert!((stepped - Vec3::new(u, v, w)).magnitude() < 1e-4);
+                stepped += edges.step_x;
+            }
+        }
+
+        let flat = EdgeFunctions::new(&a, &b, &(a + (b - a) * 0.5));
+        assert!(flat.is_degenerate());
+        assert_eq!(flat.at(&c), Vec3::zeros());
+    }
+
+    #[test]
+    fn row_span_bounds_the_covered_part_of_each_row() {
+        let edges = EdgeFunctions::new(
+            &Vec2::new(0.0, 0.0),
+            &Vec2::new(4.0, 0.0),
+            &Vec2::new(0.0, 4.0),
+        );
+        let (low, high) = edges.row_span(1.0, &Vec3::zeros()).unwrap();
+        assert!(
+            low.abs() < 1e-5 && (high - 3.0).abs() < 1e-5,
+            "{low} {high}"
+        );
+        assert_eq!(edges.row_span(5.0, &Vec3::zeros()), None);
+
+        // La holgura amplía el triángulo y, con él, el tramo.
+        let (wide_low, wide_high) = edges.row_span(1.0, &Vec3::repeat(0.25)).unwrap();
+        assert!(wide_low < low && wide_high > high);
+    }
+}
@@ -3,10 +3,60 @@
 // Este archivo implementa el pipeline de renderizado básico, incluyendo transformación de vértices,
 // rasterización de triángulos y aplicación de shaders personalizados para cada fragmento.
 
-use crate::framebuffer::Framebuffer; // Framebuffer para almacenar color y profundidad.
-use crate::mesh::{ObjMesh, Vertex}; // Estructuras de malla y vértice.
-use crate::shaders::StarShader; // Trait para shaders de fragmento personalizados.
+use crate::framebuffer::{Color, RenderTarget}; // Color y destinos con profundidad.
+use crate::mesh::{ObjMesh, Submesh, Vertex, sanitize_normal}; // Estructuras de malla y vértice.
+use crate::raster::EdgeFunctions; // Pesos baricéntricos incrementales.
+use crate::shaders::{FragmentInput, StarShader}; // Shaders de fragmento y sus entradas.
+use crate::shadow::ShadowMap; // Mapa de profundidad desde el punto de vista de una luz.
 use nalgebra_glm::{Mat4, Vec2, Vec3, Vec4}; // Tipos matemáticos para álgebra lineal.
+#[cfg(feature = "parallel")]
+use rayon::prelude::*; // Iteradores paralelos para el pipeline.
+use std::ops::Range; // Rango de índices de cada submalla.
+
+/// Modo de sombreado usado para obtener la normal de cada fragmento.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShadingMode {
+    /// Interpola las normales de los vértices (superficie suave).
+    Smooth,
+    /// Usa una única normal por cara, calculada a partir de sus aristas (aspecto facetado).
+    Flat,
+}
+
+/// Sentido de giro con el que se ven en pantalla los triángulos que miran a la cámara.
+///
+/// Define qué caras son traseras, tanto para descartarlas (`Renderer::cull_backfaces`) como
+/// para invertir su normal en el sombreado a dos caras. Las esferas de
+/// [`ObjMesh::create_sphere`] giran en sentido horario vistas desde fuera, mientras que los
+/// OBJ siguen el convenio antihorario.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrontFace {
+    /// Las caras frontales se ven en sentido antihorario (convenio de OBJ y OpenGL).
+    Ccw,
+    /// Las caras frontales se ven en sentido horario (convenio de `create_sphere`).
+    Cw,
+}
+
+/// Forma en que los fragmentos de una malla se combinan con el contenido del framebuffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Reemplaza el color y escribe la profundidad (superficies sólidas).
+    Opaque,
+    /// Suma el color ponderado por su alfa, con prueba de profundidad pero sin escribirla.
+    ///
+    /// Pensado para capas emisivas translúcidas (brillos, ondas de choque), que deben
+    /// dibujarse después de la geometría opaca.
+    Additive,
+    /// Compone el color sobre lo que hay detrás con el operador *over*, ponderado por su
+    /// alfa, con prueba de profundidad pero sin escribirla.
+    ///
+    /// El resultado depende del orden de los fragmentos; [`render_frame`] dibuja estos
+    /// objetos con depth peeling ([`peel::render_peeled`]) para que las capas solapadas se
+    /// compongan de delante hacia atrás.
+    ///
+    /// [`render_frame`]: crate::scene::render_frame
+    /// [`peel::render_peeled`]: crate::peel::render_peeled
+    Alpha,
+}
 
 /// Renderizador principal encargado de dibujar mallas 3D en el framebuffer.
 pub struct Renderer {
@@ -14,8 +64,201 @@ pub struct Renderer {
     pub width: f32,
     /// Alto de la pantalla en píxeles.
     pub height: f32,
+    /// Modo de sombreado aplicado a los triángulos.
+    pub shading_mode: ShadingMode,
+    /// Sombreado a dos caras: invierte la normal de las caras vistas por detrás.
+    pub two_sided: bool,
+    /// Sentido de giro de las caras frontales; ver [`FrontFace`].
+    pub front_face: FrontFace,
+    /// Descarta los triángulos traseros según `front_face` antes de rasterizarlos.
+    ///
+    /// Activado por defecto: en mallas cerradas, como la esfera, evita sombrear la mitad
+    /// oculta y el z-fighting entre ambas caras en la silueta. Las mallas abiertas vistas
+    /// por detrás necesitan desactivarlo.
+    pub cull_backfaces: bool,
+    /// Combinación de los fragmentos con el framebuffer; ver [`BlendMode`].
+    pub blend_mode: BlendMode,
+    /// Desplazamiento de profundidad (polygon offset) restado a cada fragmento.
+    ///
+    /// Valores positivos acercan la malla a la cámara, útil para capas coincidentes
+    /// (por ejemplo, una corona sobre la esfera) que de otro modo producirían z-fighting.
+    /// La profundidad resultante se limita a [-1, 1].
+    pub depth_bias: f32,
+    /// Rasterización conservadora para triángulos muy pequeños o delgados.
+    ///
+    /// Cuando está activa, los triángulos con área menor a `CONSERVATIVE_AREA_THRESHOLD`
+    /// expanden sus aristas medio píxel, de modo que no desaparecen entre centros de píxel.
+    pub conservative: bool,
+    /// Suavizado de bordes por distancia con signo a las aristas.
+    ///
+    /// Los píxeles de fondo a menos de medio píxel de una arista reciben el color del
+    /// fragmento mezclado según su cobertura. Como solo afecta a píxeles sin geometría,
+    /// en la práctica suaviza la silueta sin el costo del supersampling.
+    pub edge_aa: bool,
+    /// Recorrido por tramos (scanline) en lugar de todo el bounding box.
+    ///
+    /// Para cada fila de quads calcula el tramo de X que corta el triángulo y solo evalúa
+    /// los píxeles de ese tramo. Cubre exactamente los mismos píxeles, con los mismos pesos,
+    /// que el recorrido completo, pero evita los tests inútiles de triángulos delgados o
+    /// inclinados, cuyo bounding box está casi vacío.
+    pub scanline: bool,
+    /// Multiplicador de exposición aplicado al color de cada fragmento antes de escribirlo.
+    ///
+    /// Afecta por igual a todos los shaders, de modo que permite atenuar o realzar una
+    /// estrella sin tocar sus constantes. Con 1.0 el color del shader se usa tal cual.
+    pub exposure: f32,
+}
+
+/// Constructor fluido de [`Renderer`], para fijar varias opciones en una sola expresión.
+///
+/// Parte de los valores de [`Renderer::new`]; cada método reemplaza una opción y
+/// [`RendererBuilder::build`] devuelve el renderizador configurado, por ejemplo
+/// `Renderer::builder(800, 600).cull_backfaces(true).edge_aa(true).build()`.
+pub struct RendererBuilder {
+    renderer: Renderer,
+}
+
+impl RendererBuilder {
+    /// Crea un constructor con las opciones por defecto para una pantalla de
+    /// `width` x `height` píxeles.
+    pub fn new(width: usize, height: usize) -> Self {
+        RendererBuilder {
+            renderer: Renderer::new(width, height),
+        }
+    }
+
+    /// Fija [`Renderer::shading_mode`].
+    pub fn shading_mode(mut self, shading_mode: ShadingMode) -> Self {
+        self.renderer.shading_mode = shading_mode;
+        self
+    }
+
+    /// Fija [`Renderer::two_sided`].
+    pub fn two_sided(mut self, two_sided: bool) -> Self {
+        self.renderer.two_sided = two_sided;
+        self
+    }
+
+    /// Fija [`Renderer::front_face`].
+    pub fn front_face(mut self, front_face: FrontFace) -> Self {
+        self.renderer.front_face = front_face;
+        self
+    }
+
+    /// Fija [`Renderer::cull_backfaces`].
+    pub fn cull_backfaces(mut self, cull_backfaces: bool) -> Self {
+        self.renderer.cull_backfaces = cull_backfaces;
+        self
+    }
+
+    /// Fija [`Renderer::blend_mode`].
+    pub fn blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.renderer.blend_mode = blend_mode;
+        self
+    }
+
+    /// Fija [`Renderer::depth_bias`].
+    pub fn depth_bias(mut self, depth_bias: f32) -> Self {
+        self.renderer.depth_bias = depth_bias;
+        self
+    }
+
+    /// Fija [`Renderer::conservative`].
+    pub fn conservative(mut self, conservative: bool) -> Self {
+        self.renderer.conservative = conservative;
+        self
+    }
+
+    /// Fija [`Renderer::edge_aa`].
+    pub fn edge_aa(mut self, edge_aa: bool) -> Self {
+        self.renderer.edge_aa = edge_aa;
+        self
+    }
+
+    /// Fija [`Renderer::scanline`].
+    pub fn scanline(mut self, scanline: bool) -> Self {
+        self.renderer.scanline = scanline;
+        self
+    }
+
+    /// Fija [`Renderer::exposure`].
+    pub fn exposure(mut self, exposure: f32) -> Self {
+        self.renderer.exposure = exposure;
+        self
+    }
+
+    /// Devuelve el renderizador configurado.
+    pub fn build(self) -> Renderer {
+        self.renderer
+    }
+}
+
+/// Transformaciones, tiempo y color de una llamada de dibujo.
+///
+/// Agrupa los argumentos que comparten [`Renderer::render_instanced`] y los métodos de
+/// depuración, por ejemplo
+/// `DrawParams::new(model, view, projection).with_time(time).with_color(color)`.
+#[derive(Debug, Clone, Copy)]
+pub struct DrawParams {
+    /// Matriz de transformación del modelo.
+    pub model_matrix: Mat4,
+    /// Matriz de vista de la cámara.
+    pub view_matrix: Mat4,
+    /// Matriz de proyección.
+    pub projection_matrix: Mat4,
+    /// Tiempo actual para animaciones.
+    pub time: f32,
+    /// Color de los puntos y líneas de depuración.
+    pub color: Color,
+}
+
+impl DrawParams {
+    /// Crea los parámetros de dibujo con tiempo 0 y color blanco.
+    pub fn new(model_matrix: Mat4, view_matrix: Mat4, projection_matrix: Mat4) -> Self {
+        DrawParams {
+            model_matrix,
+            view_matrix,
+            projection_matrix,
+            time: 0.0,
+            color: Color::new(255, 255, 255),
+        }
+    }
+
+    /// Establece el tiempo usado por los shaders.
+    pub fn with_time(mut self, time: f32) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// Establece el color de los puntos y líneas de depuración.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
 }
 
+/// Número mínimo de vértices para transformar en paralelo; por debajo el costo de
+/// repartir el trabajo entre hilos supera la ganancia.
+#[cfg(feature = "parallel")]
+const PARALLEL_VERTEX_THRESHOLD: usize = 4096;
+
+/// Número mínimo de triángulos para rasterizar por franjas en paralelo. Cada franja
+/// recorre todos los triángulos, así que en mallas pequeñas (billboards, quads) no compensa.
+#[cfg(feature = "parallel")]
+const PARALLEL_TRIANGLE_THRESHOLD: usize = 256;
+
+/// Filas de cada franja de la rasterización en paralelo. Debe ser par para que los quads
+/// de 2x2 píxeles no crucen de una franja a otra.
+#[cfg(feature = "parallel")]
+const PARALLEL_BAND_ROWS: usize = 16;
+
+/// Desplazamiento de profundidad extra de las aristas en
+/// [`Renderer::render_mesh_wireframe_overlay`], en unidades de Z en NDC.
+const WIREFRAME_DEPTH_BIAS: f32 = 1e-3;
+
+/// Área en píxeles² bajo la cual un triángulo se rasteriza de forma conservadora.
+const CONSERVATIVE_AREA_THRESHOLD: f32 = 2.0;
+
 impl Renderer {
     /// Crea una nueva instancia del renderizador.
     ///
@@ -26,22 +269,50 @@ impl Renderer {
         Renderer {
             width: width as f32,
             height: height as f32,
+            shading_mode: ShadingMode::Smooth,
+            two_sided: false,
+            front_face: FrontFace::Cw,
+            cull_backfaces: true,
+            blend_mode: BlendMode::Opaque,
+            depth_bias: 0.0,
+            conservative: false,
+            edge_aa: false,
+            scanline: false,
+            exposure: 1.0,
         }
     }
 
+    /// Activa o desactiva el descarte de caras traseras ([`Renderer::cull_backfaces`]).
+    ///
+    /// Para mallas con el sentido de giro contrario (como los OBJ), basta con cambiar
+    /// [`Renderer::front_face`] o invertir la malla con [`ObjMesh::flip_winding`].
+    pub fn with_culling(mut self, cull_backfaces: bool) -> Self {
+        self.cull_backfaces = cull_backfaces;
+        self
+    }
+
+    /// Empieza a configurar un renderizador con las opciones por defecto de
+    /// [`Renderer::new`]. Ver [`RendererBuilder`].
+    pub fn builder(width: usize, height: usize) -> RendererBuilder {
+        RendererBuilder::new(width, height)
+    }
+
     /// Renderiza una malla en el framebuffer usando un shader de fragmento.
     ///
     /// # Argumentos
-    /// * `framebuffer` - Framebuffer destino.
+    /// * `framebuffer` - Destino del renderizado (framebuffer u otro `RenderTarget`).
     /// * `mesh` - Malla a renderizar.
     /// * `shader` - Shader de fragmento a aplicar.
     /// * `model_matrix` - Matriz de transformación del modelo.
     /// * `view_matrix` - Matriz de vista de la cámara.
     /// * `projection_matrix` - Matriz de proyección.
     /// * `time` - Tiempo actual para animaciones.
-    pub fn render_mesh(
+    ///
+    /// El resultado es determinista: depende solo de los argumentos (el tiempo nunca se
+    /// lee del reloj), de modo que la misma llamada produce siempre los mismos píxeles.
+    pub fn render_mesh<T: RenderTarget + ?Sized>(
         &self,
-        framebuffer: &mut Framebuffer,
+        framebuffer: &mut T,
         mesh: &ObjMesh,
         shader: &dyn StarShader,
         model_matrix: &Mat4,
@@ -49,38 +320,475 @@ impl Renderer {
         projection_matrix: &Mat4,
         time: f32,
     ) {
+        self.render_mesh_with_materials(
+            framebuffer,
+            mesh,
+            &[shader],
+            &DrawParams::new(*model_matrix, *view_matrix, *projection_matrix).with_time(time),
+        );
+    }
+
+    /// Renderiza una malla usando un shader distinto por material.
+    ///
+    /// Cada submalla de `mesh` se dibuja con `shaders[material_id]`; los materiales sin
+    /// shader propio usan el último de la lista. Una malla sin submallas se trata como un
+    /// único grupo con material 0.
+    ///
+    /// # Argumentos
+    /// * `framebuffer` - Destino del renderizado (framebuffer u otro `RenderTarget`).
+    /// * `mesh` - Malla a renderizar.
+    /// * `shaders` - Shaders indexados por identificador de material (al menos uno).
+    /// * `params` - Matrices de modelo, vista y proyección, y tiempo para animaciones.
+    pub fn render_mesh_with_materials<T: RenderTarget + ?Sized>(
+        &self,
+        framebuffer: &mut T,
+        mesh: &ObjMesh,
+        shaders: &[&dyn StarShader],
+        params: &DrawParams,
+    ) {
+        if shaders.is_empty() {
+            return;
+        }
+        let (model_matrix, view_matrix, projection_matrix) = (
+            &params.model_matrix,
+            &params.view_matrix,
+            &params.projection_matrix,
+        );
+        let time = params.time;
+
         // Calcula la matriz Modelo-Vista-Proyección (MVP).
         let mvp = projection_matrix * view_matrix * model_matrix;
+        let eye = camera_eye(view_matrix, projection_matrix);
 
-        // Transforma todos los vértices de la malla al espacio de pantalla.
-        let transformed_vertices: Vec<_> = mesh
-            .vertices
+        let whole_mesh = [Submesh {
+            start: 0,
+            count: mesh.indices.len(),
+            material_id: 0,
+        }];
+        let submeshes = if mesh.submeshes.is_empty() {
+            &whole_mesh[..]
+        } else {
+            &mesh.submeshes[..]
+        };
+
+        // Vértices transformados por cada shader; la etapa de vértice depende del shader,
+        // pero se calcula una sola vez aunque varias submallas lo compartan.
+        let mut transformed_by_shader: Vec<Option<Vec<TransformedVertex>>> =
+            (0..shaders.len()).map(|_| None).collect();
+        for submesh in submeshes {
+            let shader_index = submesh.material_id.min(shaders.len() - 1);
+            transformed_by_shader[shader_index].get_or_insert_with(|| {
+                self.transform_vertices(
+                    mesh,
+                    shaders[shader_index],
+                    model_matrix,
+                    view_matrix,
+                    &mvp,
+                    time,
+                )
+            });
+        }
+
+        let draws: Vec<SubmeshDraw> = submeshes
             .iter()
-            .map(|v| self.transform_vertex(v, model_matrix, &mvp))
+            .filter_map(|submesh| {
+                let shader_index = submesh.material_id.min(shaders.len() - 1);
+                let end = (submesh.start + submesh.count).min(mesh.indices.len());
+                Some(SubmeshDraw {
+                    indices: submesh.start..end,
+                    vertices: transformed_by_shader[shader_index].as_deref()?,
+                    context: DrawContext {
+                        shader: shaders[shader_index],
+                        time,
+                        eye,
+                    },
+                })
+            })
             .collect();
 
-        // Procesa cada triángulo de la malla usando los índices.
-        for i in (0..mesh.indices.len()).step_by(3) {
-            let i0 = mesh.indices[i] as usize;
-            let i1 = mesh.indices[i + 1] as usize;
-            let i2 = mesh.indices[i + 2] as usize;
-
-            // Verifica que los índices sean válidos.
-            if i0 < transformed_vertices.len()
-                && i1 < transformed_vertices.len()
-                && i2 < transformed_vertices.len()
-            {
-                // Rasteriza el triángulo formado por los tres vértices transformados.
-                self.rasterize_triangle(
-                    framebuffer,
-                    &transformed_vertices[i0],
-                    &transformed_vertices[i1],
-                    &transformed_vertices[i2],
-                    shader,
-                    time,
-                );
+        // Con la feature `parallel`, las mallas grandes se rasterizan por franjas de filas
+        // en paralelo. Cada franja recorre los triángulos en el mismo orden y los quads
+        // quedan alineados igual, así que el resultado es idéntico al secuencial. Mientras
+        // se cuentan saturaciones se dibuja en un solo hilo, porque el recuento es por hilo.
+        #[cfg(feature = "parallel")]
+        if mesh.indices.len() / 3 >= PARALLEL_TRIANGLE_THRESHOLD
+            && !crate::framebuffer::clamp_stats_enabled()
+        {
+            let mut bands = framebuffer.bands_mut(PARALLEL_BAND_ROWS);
+            if bands.len() > 1 {
+                bands
+                    .par_iter_mut()
+                    .for_each(|band| self.draw_submeshes(band, &mesh.indices, &draws));
+                return;
+            }
+        }
+
+        self.draw_submeshes(framebuffer, &mesh.indices, &draws);
+    }
+
+    /// Recorta y rasteriza los triángulos de cada submalla en `framebuffer`, en orden.
+    fn draw_submeshes<T: RenderTarget + ?Sized>(
+        &self,
+        framebuffer: &mut T,
+        indices: &[u32],
+        draws: &[SubmeshDraw],
+    ) {
+        for draw in draws {
+            let vertices = draw.vertices;
+            // Procesa cada triángulo de la submalla usando los índices.
+            for i in draw.indices.clone().step_by(3) {
+                if i + 2 >= draw.indices.end {
+                    break;
+                }
+                let i0 = indices[i] as usize;
+                let i1 = indices[i + 1] as usize;
+                let i2 = indices[i + 2] as usize;
+
+                // Verifica que los índices sean válidos.
+                if i0 < vertices.len() && i1 < vertices.len() && i2 < vertices.len() {
+                    // Recorta el triángulo contra el frustum y rasteriza lo que queda visible.
+                    self.draw_clipped_triangle(
+                        framebuffer,
+                        [&vertices[i0], &vertices[i1], &vertices[i2]],
+                        &draw.context,
+                        (i / 3) as u32,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Renderiza la misma malla varias veces, una por cada matriz de modelo.
+    ///
+    /// Todas las instancias comparten los datos de la malla y el z-buffer del framebuffer,
+    /// por lo que se ocluyen correctamente entre sí sin clonar la geometría.
+    ///
+    /// # Argumentos
+    /// * `framebuffer` - Destino del renderizado (framebuffer u otro `RenderTarget`).
+    /// * `mesh` - Malla compartida por todas las instancias.
+    /// * `shader` - Shader de fragmento a aplicar.
+    /// * `model_matrices` - Transformación de cada instancia, aplicada antes de
+    ///   `params.model_matrix` (que coloca el grupo entero).
+    /// * `params` - Matrices de la cámara, transformación común y tiempo.
+    pub fn render_instanced<T: RenderTarget + ?Sized>(
+        &self,
+        framebuffer: &mut T,
+        mesh: &ObjMesh,
+        shader: &dyn StarShader,
+        model_matrices: &[Mat4],
+        params: &DrawParams,
+    ) {
+        for instance_matrix in model_matrices {
+            self.render_mesh(
+                framebuffer,
+                mesh,
+                shader,
+                &(params.model_matrix * instance_matrix),
+                &params.view_matrix,
+                &params.projection_matrix,
+                params.time,
+            );
+        }
+    }
+
+    /// Renderiza una malla sombreada y dibuja encima sus aristas (wireframe).
+    ///
+    /// Las aristas usan los mismos vértices que la superficie (incluida la etapa de vértice
+    /// del shader) y se acercan a la cámara `WIREFRAME_DEPTH_BIAS` además del `depth_bias`
+    /// del renderizador, de modo que quedan justo delante de la superficie sin z-fighting,
+    /// mientras que las aristas de la cara oculta siguen tapadas.
+    ///
+    /// # Argumentos
+    /// * `framebuffer` - Destino del renderizado (framebuffer u otro `RenderTarget`).
+    /// * `mesh` - Malla a renderizar.
+    /// * `shader` - Shader de fragmento de la superficie.
+    /// * `params` - Matrices, tiempo para animaciones y color de las aristas.
+    pub fn render_mesh_wireframe_overlay<T: RenderTarget + ?Sized>(
+        &self,
+        framebuffer: &mut T,
+        mesh: &ObjMesh,
+        shader: &dyn StarShader,
+        params: &DrawParams,
+    ) {
+        let DrawParams {
+            model_matrix,
+            view_matrix,
+            projection_matrix,
+            time,
+            color: line_color,
+        } = params;
+        self.render_mesh(
+            framebuffer,
+            mesh,
+            shader,
+            model_matrix,
+            view_matrix,
+            projection_matrix,
+            *time,
+        );
+
+        let mvp = projection_matrix * view_matrix * model_matrix;
+        let vertices =
+            self.transform_vertices(mesh, shader, model_matrix, view_matrix, &mvp, *time);
+        let bias = self.depth_bias + WIREFRAME_DEPTH_BIAS;
+
+        for triangle in mesh.indices.chunks_exact(3) {
+            let (i0, i1, i2) = (
+                triangle[0] as usize,
+                triangle[1] as usize,
+                triangle[2] as usize,
+            );
+            if i0 >= vertices.len() || i1 >= vertices.len() || i2 >= vertices.len() {
+                continue;
+            }
+            for (a, b) in [(i0, i1), (i1, i2), (i2, i0)] {
+                draw_line(framebuffer, &vertices[a], &vertices[b], *line_color, bias);
+            }
+        }
+    }
+
+    /// Dibuja solo los vértices de la malla como una nube de puntos.
+    ///
+    /// Cada vértice se proyecta a pantalla y se dibuja como un cuadrado de color sólido
+    /// con prueba de profundidad. Útil para depurar modelos OBJ antes de confiar en sus
+    /// triángulos. Los vértices fuera del volumen de visión (profundidad fuera de [-1, 1])
+    /// se descartan.
+    ///
+    /// # Argumentos
+    /// * `framebuffer` - Destino del renderizado (framebuffer u otro `RenderTarget`).
+    /// * `mesh` - Malla cuyos vértices se dibujan.
+    /// * `params` - Matrices de modelo, vista y proyección, y color de los puntos.
+    /// * `size` - Radio del cuadrado en píxeles (0 dibuja un único píxel).
+    pub fn render_points<T: RenderTarget + ?Sized>(
+        &self,
+        framebuffer: &mut T,
+        mesh: &ObjMesh,
+        params: &DrawParams,
+        size: usize,
+    ) {
+        let mvp = params.projection_matrix * params.view_matrix * params.model_matrix;
+        let radius = size as i64;
+        let color = params.color;
+
+        for vertex in &mesh.vertices {
+            let point =
+                self.transform_vertex(vertex, &params.model_matrix, &params.view_matrix, &mvp);
+            if !(-1.0..=1.0).contains(&point.depth) {
+                continue;
+            }
+
+            let cx = point.screen_pos.x.floor() as i64;
+            let cy = point.screen_pos.y.floor() as i64;
+            for y in (cy - radius)..=(cy + radius) {
+                for x in (cx - radius)..=(cx + radius) {
+                    if x >= 0 && y >= 0 {
+                        framebuffer.set_pixel(x as usize, y as usize, color, point.depth);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Dibuja la normal de cada vértice como un segmento corto, para depurar normales.
+    ///
+    /// Cada segmento va del vértice a `posición + normal * length` (en espacio del modelo)
+    /// con prueba de profundidad, así que las normales invertidas o desordenadas saltan a
+    /// la vista. Se usan los vértices originales, sin la etapa de vértice de ningún shader.
+    ///
+    /// # Argumentos
+    /// * `framebuffer` - Destino del renderizado (framebuffer u otro `RenderTarget`).
+    /// * `mesh` - Malla cuyas normales se dibujan.
+    /// * `params` - Matrices de modelo, vista y proyección, y color de los segmentos.
+    /// * `length` - Longitud de los segmentos en unidades del modelo.
+    ///
+    /// # Retorna
+    /// El número de segmentos dibujados (los que quedan fuera del volumen de visión se omiten).
+    pub fn render_normals<T: RenderTarget + ?Sized>(
+        &self,
+        framebuffer: &mut T,
+        mesh: &ObjMesh,
+        params: &DrawParams,
+        length: f32,
+    ) -> usize {
+        let DrawParams {
+            model_matrix,
+            view_matrix,
+            projection_matrix,
+            color,
+            ..
+        } = params;
+        let mvp = projection_matrix * view_matrix * model_matrix;
+
+        let mut drawn = 0;
+        for vertex in &mesh.vertices {
+            let tip = Vertex {
+                position: vertex.position + vertex.normal * length,
+                ..*vertex
+            };
+            let start = self.transform_vertex(vertex, model_matrix, view_matrix, &mvp);
+            let end = self.transform_vertex(&tip, model_matrix, view_matrix, &mvp);
+            if draw_line(framebuffer, &start, &end, *color, self.depth_bias) {
+                drawn += 1;
             }
         }
+        drawn
+    }
+
+    /// Dibuja un *billboard*: un cuadrado orientado hacia la cámara con un brillo radial.
+    ///
+    /// Sustituye a una esfera completa para estrellas lejanas, que ocupan pocos píxeles. El
+    /// cuadrado se construye en el plano de la cámara alrededor de `center` y se rasteriza
+    /// como dos triángulos con prueba de profundidad; su color se suma de forma aditiva
+    /// ([`BlendMode::Additive`]) con opacidad `color.a` en el centro, que cae a cero en el
+    /// círculo inscrito.
+    ///
+    /// # Argumentos
+    /// * `framebuffer` - Destino del renderizado (framebuffer u otro `RenderTarget`).
+    /// * `center` - Centro del billboard en espacio mundo.
+    /// * `size` - Lado del cuadrado (diámetro del brillo) en unidades del mundo.
+    /// * `color` - Color del brillo.
+    /// * `view_matrix` - Matriz de vista de la cámara.
+    /// * `projection_matrix` - Matriz de proyección.
+    pub fn render_billboard<T: RenderTarget + ?Sized>(
+        &self,
+        framebuffer: &mut T,
+        center: Vec3,
+        size: f32,
+        color: Color,
+        view_matrix: &Mat4,
+        projection_matrix: &Mat4,
+    ) {
+        // Las filas de la rotación de la vista son los ejes de la cámara en espacio mundo.
+        let right = view_matrix.fixed_view::<1, 3>(0, 0).transpose() * (size * 0.5);
+        let up = view_matrix.fixed_view::<1, 3>(1, 0).transpose() * (size * 0.5);
+        let toward_camera = view_matrix.fixed_view::<1, 3>(2, 0).transpose();
+
+        let corner = |sx: f32, sy: f32| Vertex {
+            position: center + right * sx + up * sy,
+            normal: toward_camera,
+            uv: Vec2::new((sx + 1.0) * 0.5, (1.0 - sy) * 0.5),
+            tangent: Vec4::new(right.x, right.y, right.z, 1.0),
+        };
+        let quad = ObjMesh {
+            vertices: vec![
+                corner(-1.0, 1.0),
+                corner(1.0, 1.0),
+                corner(1.0, -1.0),
+                corner(-1.0, -1.0),
+            ],
+            indices: vec![0, 1, 2, 0, 2, 3],
+            submeshes: Vec::new(),
+        };
+
+        let glow = Renderer {
+            blend_mode: BlendMode::Additive,
+            cull_backfaces: false,
+            ..*self
+        };
+        glow.render_mesh(
+            framebuffer,
+            &quad,
+            &BillboardGlow { color },
+            &Mat4::identity(),
+            view_matrix,
+            projection_matrix,
+            0.0,
+        );
+    }
+
+    /// Renderiza solo la profundidad de una malla desde el punto de vista de una luz.
+    ///
+    /// Reutiliza el rasterizador con un shader nulo, escribiendo en el z-buffer del mapa
+    /// de sombras con la resolución de éste. Se puede llamar varias veces para acumular
+    /// varias mallas antes de consultar el mapa.
+    ///
+    /// # Argumentos
+    /// * `shadow_map` - Mapa de sombras destino; define las matrices de la luz.
+    /// * `mesh` - Malla que proyecta sombra.
+    /// * `model_matrix` - Matriz de transformación del modelo.
+    pub fn render_depth_only(
+        &self,
+        shadow_map: &mut ShadowMap,
+        mesh: &ObjMesh,
+        model_matrix: &Mat4,
+    ) {
+        let depth_renderer = Renderer {
+            width: shadow_map.depth.width as f32,
+            height: shadow_map.depth.height as f32,
+            edge_aa: false,
+            ..*self
+        };
+        let (light_view, light_proj) = (shadow_map.light_view, shadow_map.light_proj);
+
+        depth_renderer.render_mesh(
+            &mut shadow_map.depth,
+            mesh,
+            &NullShader,
+            model_matrix,
+            &light_view,
+            &light_proj,
+            0.0,
+        );
+    }
+
+    /// Aplica la etapa de vértice y transforma todos los vértices de la malla.
+    ///
+    /// Con la feature `parallel`, las mallas grandes se transforman en paralelo (cada vértice
+    /// es independiente); en otro caso se usa el recorrido secuencial. Ambos caminos
+    /// producen exactamente el mismo resultado.
+    fn transform_vertices(
+        &self,
+        mesh: &ObjMesh,
+        shader: &dyn StarShader,
+        model_matrix: &Mat4,
+        view_matrix: &Mat4,
+        mvp: &Mat4,
+        time: f32,
+    ) -> Vec<TransformedVertex> {
+        let transform = |v: &Vertex| {
+            self.transform_vertex(&shader.vertex(v, time), model_matrix, view_matrix, mvp)
+        };
+
+        #[cfg(feature = "parallel")]
+        if mesh.vertices.len() >= PARALLEL_VERTEX_THRESHOLD {
+            let mut transformed = Vec::with_capacity(mesh.vertices.len());
+            mesh.vertices
+                .par_iter()
+                .map(transform)
+                .collect_into_vec(&mut transformed);
+            return transformed;
+        }
+
+        mesh.vertices.iter().map(transform).collect()
+    }
+
+    /// Proyecta un punto en espacio de modelo a coordenadas de pantalla.
+    ///
+    /// Aplica la misma transformación que el pipeline de rasterización, por lo que sirve
+    /// para comprobar la matemática de proyección sin renderizar.
+    ///
+    /// # Argumentos
+    /// * `p` - Punto a proyectar.
+    /// * `mvp` - Matriz Modelo-Vista-Proyección.
+    ///
+    /// # Retorna
+    /// La posición en píxeles, o `(-1000, -1000)` (fuera de pantalla) si el punto está
+    /// sobre el plano de la cámara.
+    pub fn project_point(&self, p: Vec3, mvp: &Mat4) -> Vec2 {
+        let clip_pos = mvp * Vec4::new(p.x, p.y, p.z, 1.0);
+        if clip_pos.w.abs() < 1e-6 {
+            return Vec2::new(-1000.0, -1000.0);
+        }
+        self.ndc_to_screen(&(clip_pos.xyz() / clip_pos.w))
+    }
+
+    /// Convierte coordenadas normalizadas de dispositivo (NDC) a píxeles de pantalla.
+    #[inline]
+    fn ndc_to_screen(&self, ndc: &Vec3) -> Vec2 {
+        Vec2::new(
+            (ndc.x + 1.0) * 0.5 * self.width,
+            (1.0 - ndc.y) * 0.5 * self.height, // Y invertida.
+        )
     }
 
     /// Transforma un vértice del espacio de modelo al espacio de pantalla.
@@ -88,6 +796,7 @@ impl Renderer {
     /// # Argumentos
     /// * `vertex` - Vértice original.
     /// * `model_matrix` - Matriz de modelo.
+    /// * `view_matrix` - Matriz de vista, para la profundidad lineal.
     /// * `mvp` - Matriz Modelo-Vista-Proyección.
     ///
     /// # Retorna
@@ -96,6 +805,7 @@ impl Renderer {
         &self,
         vertex: &Vertex,
         model_matrix: &Mat4,
+        view_matrix: &Mat4,
         mvp: &Mat4,
     ) -> TransformedVertex {
         let pos4 = Vec4::new(vertex.position.x, vertex.position.y, vertex.position.z, 1.0);
@@ -103,154 +813,1994 @@ impl Renderer {
         // Calcula la posición y normal en espacio mundo.
         let world_pos = model_matrix * pos4;
         let normal4 = Vec4::new(vertex.normal.x, vertex.normal.y, vertex.normal.z, 0.0);
-        let world_normal = (model_matrix * normal4).xyz().normalize();
+        // Una normal nula (p. ej. de un OBJ defectuoso) daría NaN; se sustituye por la
+        // dirección desde el origen del modelo.
+        let world_normal = sanitize_normal(
+            (model_matrix * normal4).xyz(),
+            &(world_pos.xyz() - model_matrix.column(3).xyz()),
+        );
+
+        // Profundidad lineal: distancia a lo largo del eje de la cámara (que mira hacia -Z).
+        let view_depth = -(view_matrix * world_pos).z;
 
         // Proyecta al espacio de recorte (clip space).
         let clip_pos = mvp * pos4;
 
-        // División de perspectiva para obtener NDC.
-        let w = clip_pos.w;
-        if w.abs() < 1e-6 {
-            // Descarta vértices problemáticos.
-            return TransformedVertex {
-                screen_pos: Vec2::new(-1000.0, -1000.0),
-                depth: 1.0,
-                world_pos: world_pos.xyz(),
-                world_normal,
-            };
-        }
-        let ndc = clip_pos.xyz() / w;
+        let (screen_pos, depth) = self.project_clip(&clip_pos);
+        let inv_w = inverse_w(&clip_pos);
 
-        // Convierte NDC a coordenadas de pantalla.
-        let screen = Vec2::new(
-            (ndc.x + 1.0) * 0.5 * self.width,
-            (1.0 - ndc.y) * 0.5 * self.height, // Y invertida.
-        );
+        // La tangente sigue a la superficie, así que se transforma con la matriz de modelo
+        // (no con la inversa transpuesta, como la normal); `w` guarda la orientación.
+        let tangent4 = Vec4::new(vertex.tangent.x, vertex.tangent.y, vertex.tangent.z, 0.0);
+        let world_tangent = (model_matrix * tangent4).xyz();
 
         TransformedVertex {
-            screen_pos: screen,
-            depth: ndc.z,
+            screen_pos,
+            depth,
+            view_depth,
+            clip_pos,
+            inv_w,
+            object_pos: vertex.position,
             world_pos: world_pos.xyz(),
             world_normal,
+            world_tangent: Vec4::new(
+                world_tangent.x,
+                world_tangent.y,
+                world_tangent.z,
+                vertex.tangent.w,
+            ),
+            uv: vertex.uv,
         }
     }
 
-    /// Rasteriza un triángulo interpolando atributos y aplicando el shader de fragmento.
+    /// Escala el color de un fragmento por `exposure` y lo vuelve a cuantizar a 8 bits,
+    /// saturando en 255. El canal alfa no se modifica.
+    #[inline]
+    fn apply_exposure(&self, color: Color) -> Color {
+        if self.exposure == 1.0 {
+            return color;
+        }
+        let scale = |channel: u8| (channel as f32 * self.exposure).round().clamp(0.0, 255.0) as u8;
+        Color {
+            r: scale(color.r),
+            g: scale(color.g),
+            b: scale(color.b),
+            a: color.a,
+        }
+    }
+
+    /// Calcula el bounding box en píxeles de un triángulo, recortado a la pantalla.
     ///
     /// # Argumentos
-    /// * `framebuffer` - Framebuffer destino.
-    /// * `v0`, `v1`, `v2` - Vértices transformados del triángulo.
-    /// * `shader` - Shader de fragmento.
-    /// * `time` - Tiempo actual para animaciones.
-    fn rasterize_triangle(
+    /// * `margin` - Píxeles extra a cada lado, para los modos que cubren fuera del triángulo.
+    ///
+    /// # Retorna
+    /// `(min_x, max_x, min_y, max_y)` inclusivos, o `None` si alguna coordenada no es finita
+    /// (p. ej. por una transformación degenerada) o si el triángulo queda fuera de la
+    /// pantalla. Sin esta comprobación, un `NaN` o infinito se convertiría en un índice
+    /// arbitrario al pasar a `usize`.
+    fn screen_bounds(
         &self,
-        framebuffer: &mut Framebuffer,
         v0: &TransformedVertex,
         v1: &TransformedVertex,
         v2: &TransformedVertex,
-        shader: &dyn StarShader,
-        time: f32,
-    ) {
-        // Calcula el bounding box del triángulo para limitar el área de rasterización.
-        let min_x = v0
-            .screen_pos
-            .x
-            .min(v1.screen_pos.x)
-            .min(v2.screen_pos.x)
-            .floor()
-            .max(0.0) as usize;
-        let max_x = v0
-            .screen_pos
-            .x
-            .max(v1.screen_pos.x)
-            .max(v2.screen_pos.x)
-            .ceil()
-            .min(self.width - 1.0) as usize;
-        let min_y = v0
-            .screen_pos
-            .y
-            .min(v1.screen_pos.y)
-            .min(v2.screen_pos.y)
-            .floor()
-            .max(0.0) as usize;
-        let max_y = v0
-            .screen_pos
-            .y
-            .max(v1.screen_pos.y)
-            .max(v2.screen_pos.y)
-            .ceil()
-            .min(self.height - 1.0) as usize;
-
-        // Recorre cada píxel dentro del bounding box.
-        for y in min_y..=max_y {
-            for x in min_x..=max_x {
-                let p = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
-
-                // Calcula coordenadas baricéntricas para interpolación.
-                let (w0, w1, w2) = barycentric(&p, &v0.screen_pos, &v1.screen_pos, &v2.screen_pos);
-
-                // Si el píxel está dentro del triángulo.
-                if w0 >= 0.0 && w1 >= 0.0 && w2 >= 0.0 {
-                    // Interpola profundidad, posición y normal.
-                    let depth = w0 * v0.depth + w1 * v1.depth + w2 * v2.depth;
-                    let world_pos = v0.world_pos * w0 + v1.world_pos * w1 + v2.world_pos * w2;
-                    let world_normal =
-                        (v0.world_normal * w0 + v1.world_normal * w1 + v2.world_normal * w2)
-                            .normalize();
-
-                    // Aplica el shader de fragmento para obtener el color final.
-                    let color = shader.fragment(&world_pos, &world_normal, time);
-
-                    // Escribe el píxel en el framebuffer con prueba de profundidad.
-                    framebuffer.set_pixel(x, y, color, depth);
-                }
-            }
+        margin: f32,
+    ) -> Option<(usize, usize, usize, usize)> {
+        let points = [v0.screen_pos, v1.screen_pos, v2.screen_pos];
+        if points.iter().any(|p| !p.x.is_finite() || !p.y.is_finite()) {
+            return None;
         }
-    }
-}
-
-/// Estructura auxiliar para almacenar los atributos interpolables de un vértice transformado.
-struct TransformedVertex {
-    /// Posición en pantalla (2D).
-    screen_pos: Vec2,
-    /// Profundidad (Z en NDC).
-    depth: f32,
-    /// Posición en espacio mundo (3D).
-    world_pos: Vec3,
-    /// Normal en espacio mundo (3D).
-    world_normal: Vec3,
-}
 
-/// Calcula las coordenadas baricéntricas de un punto respecto a un triángulo.
-///
-/// # Argumentos
-/// * `p` - Punto a evaluar.
-/// * `a`, `b`, `c` - Vértices del triángulo.
-///
-/// # Retorna
-/// Tupla con los pesos baricéntricos (u, v, w).
-#[inline]
-fn barycentric(p: &Vec2, a: &Vec2, b: &Vec2, c: &Vec2) -> (f32, f32, f32) {
-    let v0 = *b - *a;
-    let v1 = *c - *a;
-    let v2 = *p - *a;
+        let min_x = (points[0].x.min(points[1].x).min(points[2].x).floor() - margin).max(0.0);
+        let max_x =
+            (points[0].x.max(points[1].x).max(points[2].x).ceil() + margin).min(self.width - 1.0);
+        let min_y = (points[0].y.min(points[1].y).min(points[2].y).floor() - margin).max(0.0);
+        let max_y =
+            (points[0].y.max(points[1].y).max(points[2].y).ceil() + margin).min(self.height - 1.0);
 
-    let d00 = v0.dot(&v0);
-    let d01 = v0.dot(&v1);
-    let d11 = v1.dot(&v1);
-    let d20 = v2.dot(&v0);
-    let d21 = v2.dot(&v1);
-
-    let denom = d00 * d11 - d01 * d01;
-
-    if denom.abs() < 1e-8 {
-        // Triángulo degenerado.
-        return (0.0, 0.0, 0.0);
+        if min_x > max_x || min_y > max_y {
+            return None;
+        }
+        Some((
+            min_x as usize,
+            max_x as usize,
+            min_y as usize,
+            max_y as usize,
+        ))
     }
 
-    let v = (d11 * d20 - d01 * d21) / denom;
-    let w = (d00 * d21 - d01 * d20) / denom;
-    let u = 1.0 - v - w;
-
-    (u, v, w)
+    /// Aplica la división de perspectiva a una posición en espacio de recorte.
+    ///
+    /// # Retorna
+    /// La posición en pantalla y la profundidad en NDC. Si `w` es casi nulo, devuelve un
+    /// punto fuera de pantalla con profundidad 1.0.
+    fn project_clip(&self, clip_pos: &Vec4) -> (Vec2, f32) {
+        let w = clip_pos.w;
+        if w.abs() < 1e-6 {
+            // Descarta vértices problemáticos.
+            return (Vec2::new(-1000.0, -1000.0), 1.0);
+        }
+        let ndc = clip_pos.xyz() / w;
+        (self.ndc_to_screen(&ndc), ndc.z)
+    }
+
+    /// Recorta un triángulo contra los seis planos del frustum y rasteriza el resultado.
+    ///
+    /// Los triángulos completamente dentro se rasterizan tal cual y los que quedan fuera de
+    /// un mismo plano se descartan. El resto se recorta en espacio de recorte con
+    /// Sutherland–Hodgman y el polígono resultante se dibuja como un abanico de triángulos,
+    /// de modo que el rasterizador solo recibe coordenadas dentro de la pantalla.
+    fn draw_clipped_triangle<T: RenderTarget + ?Sized>(
+        &self,
+        framebuffer: &mut T,
+        triangle: [&TransformedVertex; 3],
+        context: &DrawContext,
+        triangle_id: u32,
+    ) {
+        let [v0, v1, v2] = triangle;
+        let (c0, c1, c2) = (
+            outcode(&v0.clip_pos),
+            outcode(&v1.clip_pos),
+            outcode(&v2.clip_pos),
+        );
+        if c0 & c1 & c2 != 0 {
+            return;
+        }
+        if c0 | c1 | c2 == 0 {
+            self.rasterize_triangle(framebuffer, v0, v1, v2, context, triangle_id);
+            return;
+        }
+
+        let mut polygon = vec![*v0, *v1, *v2];
+        let mut scratch = Vec::with_capacity(9);
+        for plane in 0..CLIP_PLANES {
+            if polygon.is_empty() {
+                return;
+            }
+            scratch.clear();
+            for (index, current) in polygon.iter().enumerate() {
+                let next = &polygon[(index + 1) % polygon.len()];
+                let d_current = plane_distance(&current.clip_pos, plane);
+                let d_next = plane_distance(&next.clip_pos, plane);
+                if d_current >= 0.0 {
+                    scratch.push(*current);
+                }
+                if (d_current >= 0.0) != (d_next >= 0.0) {
+                    let t = d_current / (d_current - d_next);
+                    scratch.push(self.intersect_plane(current, next, t, plane));
+                }
+            }
+            std::mem::swap(&mut polygon, &mut scratch);
+        }
+
+        for i in 1..polygon.len().saturating_sub(1) {
+            self.rasterize_triangle(
+                framebuffer,
+                &polygon[0],
+                &polygon[i],
+                &polygon[i + 1],
+                context,
+                triangle_id,
+            );
+        }
+    }
+
+    /// Punto donde la arista `a`-`b` cruza el plano `plane`, en la fracción `t` de la arista.
+    ///
+    /// Interpola todos los atributos y vuelve a proyectar el resultado a pantalla. La
+    /// coordenada recortada se fija exactamente sobre el plano: con triángulos enormes la
+    /// interpolación en `f32` podría dejarla a varios píxeles del borde de la pantalla.
+    fn intersect_plane(
+        &self,
+        a: &TransformedVertex,
+        b: &TransformedVertex,
+        t: f32,
+        plane: usize,
+    ) -> TransformedVertex {
+        let mut clip_pos = a.clip_pos.lerp(&b.clip_pos, t);
+        // Los planos pares son los de signo negativo (-w) de cada eje.
+        clip_pos[plane / 2] = match plane % 2 {
+            0 => -clip_pos.w,
+            _ => clip_pos.w,
+        };
+        let (screen_pos, depth) = self.project_clip(&clip_pos);
+        TransformedVertex {
+            screen_pos,
+            depth,
+            view_depth: a.view_depth + (b.view_depth - a.view_depth) * t,
+            clip_pos,
+            inv_w: inverse_w(&clip_pos),
+            object_pos: a.object_pos.lerp(&b.object_pos, t),
+            world_pos: a.world_pos.lerp(&b.world_pos, t),
+            world_normal: a.world_normal.lerp(&b.world_normal, t),
+            world_tangent: a.world_tangent.lerp(&b.world_tangent, t),
+            uv: a.uv.lerp(&b.uv, t),
+        }
+    }
+
+    /// Rasteriza un triángulo interpolando atributos y aplicando el shader de fragmento.
+    ///
+    /// # Argumentos
+    /// * `framebuffer` - Destino del renderizado (framebuffer u otro `RenderTarget`).
+    /// * `v0`, `v1`, `v2` - Vértices transformados del triángulo.
+    /// * `context` - Shader, tiempo y cámara de la llamada de dibujo.
+    /// * `triangle_id` - Índice del triángulo dentro de la malla.
+    fn rasterize_triangle<T: RenderTarget + ?Sized>(
+        &self,
+        framebuffer: &mut T,
+        v0: &TransformedVertex,
+        v1: &TransformedVertex,
+        v2: &TransformedVertex,
+        context: &DrawContext,
+        triangle_id: u32,
+    ) {
+        // Descarta pronto los triángulos fuera de las filas del destino (las franjas de la
+        // rasterización en paralelo), con el mismo margen máximo que `screen_bounds`.
+        let rows = framebuffer.rows();
+        let top = v0.screen_pos.y.min(v1.screen_pos.y).min(v2.screen_pos.y);
+        let bottom = v0.screen_pos.y.max(v1.screen_pos.y).max(v2.screen_pos.y);
+        if bottom.ceil() + 1.0 < rows.start as f32 || top.floor() - 1.0 >= rows.end as f32 {
+            return;
+        }
+
+        // Tolerancia de cobertura por arista (en unidades baricéntricas). Es cero salvo en
+        // modo conservador con triángulos pequeños, donde equivale a medio píxel.
+        let tolerance = if self.conservative {
+            conservative_tolerance(v0, v1, v2)
+        } else {
+            None
+        };
+        let (t0, t1, t2) = tolerance.unwrap_or((0.0, 0.0, 0.0));
+
+        // Alturas del triángulo sobre cada arista, para convertir pesos en distancias.
+        let altitudes = if self.edge_aa {
+            edge_altitudes(v0, v1, v2)
+        } else {
+            None
+        };
+
+        // Ambos modos pueden cubrir píxeles fuera del triángulo: se amplía el bounding box.
+        let margin = if tolerance.is_some() || altitudes.is_some() {
+            1.0
+        } else {
+            0.0
+        };
+
+        // Calcula el bounding box del triángulo para limitar el área de rasterización,
+        // recortado a las filas del destino. Las franjas empiezan en filas pares, así que
+        // los quads quedan alineados igual que en el destino completo.
+        let Some((min_x, max_x, min_y, max_y)) = self.screen_bounds(v0, v1, v2, margin) else {
+            return;
+        };
+        let (min_y, max_y) = (min_y.max(rows.start), max_y.min(rows.end.saturating_sub(1)));
+        if min_y > max_y {
+            return;
+        }
+
+        // En modo plano, la normal es constante para todo el triángulo.
+        let face_normal = match self.shading_mode {
+            ShadingMode::Flat => Some(face_normal(v0, v1, v2)),
+            ShadingMode::Smooth => None,
+        };
+
+        // Las caras traseras se descartan o, con sombreado a dos caras, invierten su normal
+        // para iluminarse correctamente desde atrás.
+        let back_facing = is_back_facing(v0, v1, v2, self.front_face);
+        if back_facing && self.cull_backfaces {
+            return;
+        }
+        let normal_sign = if self.two_sided && back_facing {
+            -1.0
+        } else {
+            1.0
+        };
+
+        // Funciones de arista del triángulo: los pesos baricéntricos se obtienen sumando
+        // incrementos constantes en vez de resolverlos en cada píxel.
+        let edges = EdgeFunctions::new(&v0.screen_pos, &v1.screen_pos, &v2.screen_pos);
+        if edges.is_degenerate() {
+            // Sin área no cubre ningún centro de píxel; sus pesos nulos solo darían
+            // fragmentos espurios a lo largo de la caja.
+            return;
+        }
+        let quad_offsets =
+            QUAD_OFFSETS.map(|(dx, dy)| edges.step_x * dx as f32 + edges.step_y * dy as f32);
+        let quad_step = edges.step_x * 2.0;
+
+        // Holgura de cada peso dentro de la cual un píxel puede tener cobertura: la
+        // tolerancia conservadora o, con suavizado de bordes, medio píxel de distancia.
+        let slack = {
+            let aa = altitudes.map_or(Vec3::zeros(), |(h0, h1, h2)| {
+                Vec3::new(0.5 / h0, 0.5 / h1, 0.5 / h2)
+            });
+            Vec3::new(t0, t1, t2).sup(&aa)
+        };
+
+        // Recorre el bounding box en bloques de 2x2 píxeles (quads), igual que una GPU,
+        // para estimar las derivadas en pantalla a partir de los píxeles vecinos.
+        let first_quad_x = min_x & !1;
+        for quad_y in ((min_y & !1)..=max_y).step_by(2) {
+            // En modo scanline solo se recorre el tramo que corta alguna de las dos filas.
+            let (span_start, span_end) = if self.scanline {
+                match quad_row_span(&edges, quad_y, &slack, first_quad_x, max_x) {
+                    Some(span) => span,
+                    None => continue,
+                }
+            } else {
+                (first_quad_x, max_x)
+            };
+
+            // Cada fila parte de una evaluación directa, así el error de redondeo de los
+            // incrementos no se acumula a lo largo de toda la caja. Los quads saltados se
+            // avanzan con el mismo incremento para obtener los mismos pesos, bit a bit,
+            // que el recorrido completo.
+            let mut quad_weights =
+                edges.at(&Vec2::new(first_quad_x as f32 + 0.5, quad_y as f32 + 0.5));
+            for _ in (first_quad_x..span_start).step_by(2) {
+                quad_weights += quad_step;
+            }
+
+            for quad_x in (span_start..=span_end).step_by(2) {
+                let origin_weights = quad_weights;
+                quad_weights += quad_step;
+
+                // Evalúa los cuatro píxeles del quad, aunque alguno quede fuera del
+                // triángulo: sus atributos extrapolados sirven para las derivadas. Los
+                // pesos en pantalla deciden la cobertura y la profundidad; los atributos
+                // usan los pesos corregidos en perspectiva.
+                let mut weights = [(0.0, 0.0, 0.0); 4];
+                let mut perspective = [(0.0, 0.0, 0.0); 4];
+                let mut positions = [Vec3::zeros(); 4];
+                let mut object_positions = [Vec3::zeros(); 4];
+                let mut uvs = [Vec2::zeros(); 4];
+                // Cobertura: 1.0 dentro del triángulo, (0, 1) en el borde suavizado, 0 fuera.
+                let mut coverage = [0.0f32; 4];
+
+                for (i, offset) in quad_offsets.iter().enumerate() {
+                    // Pesos baricéntricos del centro del píxel, para interpolación.
+                    let w = origin_weights + offset;
+                    let (w0, w1, w2) = (w.x, w.y, w.z);
+
+                    weights[i] = (w0, w1, w2);
+                    perspective[i] =
+                        perspective_weights(weights[i], (v0.inv_w, v1.inv_w, v2.inv_w));
+                    let (p0, p1, p2) = perspective[i];
+                    positions[i] = v0.world_pos * p0 + v1.world_pos * p1 + v2.world_pos * p2;
+                    object_positions[i] =
+                        v0.object_pos * p0 + v1.object_pos * p1 + v2.object_pos * p2;
+                    uvs[i] = v0.uv * p0 + v1.uv * p1 + v2.uv * p2;
+                    coverage[i] = if w0 >= -t0 && w1 >= -t1 && w2 >= -t2 {
+                        1.0
+                    } else {
+                        altitudes.map_or(0.0, |h| edge_coverage(weights[i], h))
+                    };
+                }
+
+                if coverage.iter().all(|&c| c <= 0.0) {
+                    continue;
+                }
+
+                // Diferencias finitas dentro del quad (derivadas "gruesas", una por quad).
+                let dpdx = positions[1] - positions[0];
+                let dpdy = positions[2] - positions[0];
+                let object_dpdx = object_positions[1] - object_positions[0];
+                let object_dpdy = object_positions[2] - object_positions[0];
+                let duvdx = uvs[1] - uvs[0];
+                let duvdy = uvs[2] - uvs[0];
+
+                for (i, (dx, dy)) in QUAD_OFFSETS.iter().enumerate() {
+                    let (x, y) = (quad_x + dx, quad_y + dy);
+
+                    // Solo sombrea los píxeles cubiertos dentro del bounding box recortado.
+                    if coverage[i] <= 0.0 || x > max_x || y > max_y {
+                        continue;
+                    }
+
+                    // Interpola la profundidad (con el desplazamiento aplicado) y descarta
+                    // el fragmento antes de sombrearlo si ya hay algo más cerca. Los bordes
+                    // suavizados solo se mezclan sobre el fondo vacío, que siempre pasa.
+                    // La Z en NDC (z / w) ya es lineal en pantalla, así que se interpola
+                    // con los pesos sin corregir.
+                    let (w0, w1, w2) = weights[i];
+                    let depth = clamp_depth(
+                        w0 * v0.depth + w1 * v1.depth + w2 * v2.depth - self.depth_bias,
+                    );
+                    if !framebuffer.depth_test(x, y, depth) {
+                        continue;
+                    }
+
+                    let (p0, p1, p2) = perspective[i];
+                    let world_normal = face_normal.unwrap_or_else(|| {
+                        (v0.world_normal * p0 + v1.world_normal * p1 + v2.world_normal * p2)
+                            .normalize()
+                    }) * normal_sign;
+                    let tangent =
+                        v0.world_tangent * p0 + v1.world_tangent * p1 + v2.world_tangent * p2;
+
+                    let input = FragmentInput {
+                        world_pos: positions[i],
+                        object_pos: object_positions[i],
+                        normal: world_normal,
+                        view_dir: view_direction(&context.eye, &positions[i]),
+                        tangent,
+                        time: context.time,
+                        dpdx,
+                        dpdy,
+                        object_dpdx,
+                        object_dpdy,
+                        uv: uvs[i],
+                        duvdx,
+                        duvdy,
+                        view_depth: p0 * v0.view_depth + p1 * v1.view_depth + p2 * v2.view_depth,
+                        barycentric: Vec3::new(w0, w1, w2),
+                        triangle_id,
+                    };
+
+                    // Aplica el shader de fragmento para obtener el color final; un
+                    // fragmento descartado deja el píxel como estaba.
+                    let Some(color) = context.shader.fragment_or_discard(&input) else {
+                        continue;
+                    };
+                    let color = self.apply_exposure(color);
+
+                    if self.blend_mode != BlendMode::Opaque {
+                        // Capa translúcida: la cobertura del borde atenúa su opacidad.
+                        let alpha = (color.a as f32 * coverage[i]).round() as u8;
+                        let color = Color { a: alpha, ..color };
+                        if self.blend_mode == BlendMode::Additive {
+                            framebuffer.blend_additive(x, y, color, depth);
+                        } else {
+                            framebuffer.blend_over(x, y, color, depth);
+                        }
+                    } else if coverage[i] < 1.0 {
+                        // Borde suavizado: mezcla con el fondo sin ocupar el z-buffer.
+                        framebuffer.blend_background(x, y, color, coverage[i]);
+                    } else {
+                        // Escribe el píxel (y su geometría, si hay G-buffer) con prueba
+                        // de profundidad.
+                        framebuffer.set_fragment(x, y, color, depth, positions[i], world_normal);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Dibuja un segmento entre dos vértices transformados con prueba de profundidad.
+///
+/// Avanza un píxel por paso a lo largo del eje mayor (DDA) e interpola la profundidad
+/// linealmente en pantalla, igual que el rasterizador. Los segmentos con algún extremo
+/// fuera del volumen de visión se descartan; devuelve `false` en ese caso.
+fn draw_line<T: RenderTarget + ?Sized>(
+    framebuffer: &mut T,
+    a: &TransformedVertex,
+    b: &TransformedVertex,
+    color: Color,
+    depth_bias: f32,
+) -> bool {
+    if !(-1.0..=1.0).contains(&a.depth) || !(-1.0..=1.0).contains(&b.depth) {
+        return false;
+    }
+    // Una posición no finita haría que el número de pasos se convirtiera en `usize::MAX`.
+    let finite = |p: &Vec2| p.x.is_finite() && p.y.is_finite();
+    if !finite(&a.screen_pos) || !finite(&b.screen_pos) {
+        return false;
+    }
+
+    let delta = b.screen_pos - a.screen_pos;
+    let steps = delta.x.abs().max(delta.y.abs()).ceil().max(1.0) as usize;
+    for step in 0..=steps {
+        let t = step as f32 / steps as f32;
+        let p = a.screen_pos + delta * t;
+        let (x, y) = (p.x.floor(), p.y.floor());
+        if x < 0.0 || y < 0.0 {
+            continue;
+        }
+        let depth = clamp_depth(a.depth + (b.depth - a.depth) * t - depth_bias);
+        framebuffer.set_pixel(x as usize, y as usize, color, depth);
+    }
+    true
+}
+
+/// Limita una profundidad interpolada al rango de Z en NDC, [-1, 1].
+///
+/// Tras el recorte, los vértices ya están dentro de ese rango, pero el `depth_bias` y los
+/// pesos extrapolados (bordes conservadores o suavizados, píxeles del quad fuera del
+/// triángulo) pueden sacar el valor de él; sin el límite, un fragmento con profundidad muy
+/// negativa taparía para siempre todo lo que se dibuje después en ese píxel.
+#[inline]
+fn clamp_depth(depth: f32) -> f32 {
+    depth.clamp(-1.0, 1.0)
+}
+
+/// Desplazamientos de los píxeles dentro de un quad 2x2: (0,0), (1,0), (0,1), (1,1).
+const QUAD_OFFSETS: [(usize, usize); 4] = [(0, 0), (1, 0), (0, 1), (1, 1)];
+
+/// Estado compartido por todos los triángulos de una llamada de dibujo.
+struct DrawContext<'a> {
+    /// Shader aplicado a la malla.
+    shader: &'a dyn StarShader,
+    /// Tiempo actual para animaciones.
+    time: f32,
+    /// Cámara de la llamada; ver [`camera_eye`].
+    eye: Vec4,
+}
+
+/// Triángulos de una submalla listos para rasterizar.
+struct SubmeshDraw<'a> {
+    /// Rango de `ObjMesh::indices` de la submalla, ya ajustado a la malla.
+    indices: Range<usize>,
+    /// Vértices transformados por el shader de la submalla.
+    vertices: &'a [TransformedVertex],
+    /// Shader, tiempo y cámara de la submalla.
+    context: DrawContext<'a>,
+}
+
+/// Shader que no calcula color, usado en las pasadas de solo profundidad.
+struct NullShader;
+
+impl StarShader for NullShader {
+    fn fragment(&self, _input: &FragmentInput) -> Color {
+        Color::new(0, 0, 0)
+    }
+}
+
+/// Brillo radial de [`Renderer::render_billboard`]: la opacidad cae de forma cuadrática
+/// desde el centro de las UV hasta el círculo inscrito.
+struct BillboardGlow {
+    color: Color,
+}
+
+impl StarShader for BillboardGlow {
+    fn fragment(&self, input: &FragmentInput) -> Color {
+        let distance = (input.uv - Vec2::new(0.5, 0.5)).magnitude() * 2.0;
+        let falloff = (1.0 - distance).clamp(0.0, 1.0);
+        Color {
+            a: (self.color.a as f32 * falloff * falloff).round() as u8,
+            ..self.color
+        }
+    }
+}
+
+/// Estructura auxiliar para almacenar los atributos interpolables de un vértice transformado.
+#[derive(Clone, Copy)]
+struct TransformedVertex {
+    /// Posición en pantalla (2D).
+    screen_pos: Vec2,
+    /// Profundidad (Z en NDC).
+    depth: f32,
+    /// Profundidad lineal en espacio de vista.
+    view_depth: f32,
+    /// Posición en espacio de recorte (antes de la división de perspectiva).
+    clip_pos: Vec4,
+    /// Inverso de `clip_pos.w`, con el que se corrige la interpolación en perspectiva.
+    inv_w: f32,
+    /// Posición en espacio del objeto, tras el shader de vértices y antes de la matriz de
+    /// modelo.
+    object_pos: Vec3,
+    /// Posición en espacio mundo (3D).
+    world_pos: Vec3,
+    /// Normal en espacio mundo (3D).
+    world_normal: Vec3,
+    /// Tangente en espacio mundo (`xyz`) y orientación de la bitangente (`w`).
+    world_tangent: Vec4,
+    /// Coordenadas de textura.
+    uv: Vec2,
+}
+
+/// Posición de la cámara en espacio mundo (`w = 1`) o, con proyección ortográfica, la
+/// dirección hacia ella (`w = 0`), deducidas de las matrices de la llamada de dibujo.
+fn camera_eye(view_matrix: &Mat4, projection_matrix: &Mat4) -> Vec4 {
+    let inverse_view = view_matrix.try_inverse().unwrap_or_else(Mat4::identity);
+    // La última fila de una perspectiva es (0, 0, -1, 0) y la de una ortográfica,
+    // (0, 0, 0, 1). La cámara mira hacia -Z en espacio de vista.
+    if projection_matrix[(3, 3)] != 0.0 {
+        inverse_view * Vec4::new(0.0, 0.0, 1.0, 0.0)
+    } else {
+        inverse_view * Vec4::new(0.0, 0.0, 0.0, 1.0)
+    }
+}
+
+/// Dirección normalizada desde `world_pos` hacia la cámara `eye` (ver [`camera_eye`]).
+///
+/// Un fragmento justo en la posición de la cámara no tiene dirección; se usa +Z.
+#[inline]
+fn view_direction(eye: &Vec4, world_pos: &Vec3) -> Vec3 {
+    let direction = if eye.w == 0.0 {
+        eye.xyz()
+    } else {
+        eye.xyz() - world_pos
+    };
+    if direction.magnitude_squared() > 1e-12 {
+        direction.normalize()
+    } else {
+        Vec3::new(0.0, 0.0, 1.0)
+    }
+}
+
+/// Inverso de la `w` de recorte, o 0 si es casi nula (vértices que se descartan igual al
+/// proyectarlos; ver `Renderer::project_clip`).
+#[inline]
+fn inverse_w(clip_pos: &Vec4) -> f32 {
+    if clip_pos.w.abs() < 1e-6 {
+        0.0
+    } else {
+        1.0 / clip_pos.w
+    }
+}
+
+/// Convierte pesos baricéntricos en pantalla en pesos corregidos en perspectiva.
+///
+/// Los atributos divididos por `w` sí varían linealmente en pantalla: se interpolan
+/// `peso / w` y se normalizan por la suma, que es `1 / w` en el píxel. Si la suma se anula
+/// (solo lejos del triángulo, en píxeles extrapolados del quad) se devuelven los pesos sin
+/// corregir.
+#[inline]
+fn perspective_weights(weights: (f32, f32, f32), inv_w: (f32, f32, f32)) -> (f32, f32, f32) {
+    let (p0, p1, p2) = (
+        weights.0 * inv_w.0,
+        weights.1 * inv_w.1,
+        weights.2 * inv_w.2,
+    );
+    let sum = p0 + p1 + p2;
+    if sum.abs() < 1e-12 || !sum.is_finite() {
+        return weights;
+    }
+    (p0 / sum, p1 / sum, p2 / sum)
+}
+
+/// Número de planos del frustum: izquierdo, derecho, inferior, superior, cercano y lejano.
+const CLIP_PLANES: usize = 6;
+
+/// Distancia con signo (sin normalizar) de una posición en espacio de recorte al plano
+/// `plane` del frustum; es no negativa dentro de él (`-w <= x, y, z <= w`).
+#[inline]
+fn plane_distance(clip: &Vec4, plane: usize) -> f32 {
+    match plane {
+        0 => clip.w + clip.x,
+        1 => clip.w - clip.x,
+        2 => clip.w + clip.y,
+        3 => clip.w - clip.y,
+        4 => clip.w + clip.z,
+        _ => clip.w - clip.z,
+    }
+}
+
+/// Máscara con un bit por cada plano del frustum que deja fuera a la posición.
+#[inline]
+fn outcode(clip: &Vec4) -> u8 {
+    (0..CLIP_PLANES).fold(0, |code, plane| {
+        if plane_distance(clip, plane) < 0.0 {
+            code | (1 << plane)
+        } else {
+            code
+        }
+    })
+}
+
+/// Calcula la normal geométrica de un triángulo en espacio mundo.
+///
+/// La normal se orienta hacia el mismo lado que las normales de los vértices, de modo que
+/// el resultado no depende del orden de los vértices del triángulo.
+fn face_normal(v0: &TransformedVertex, v1: &TransformedVertex, v2: &TransformedVertex) -> Vec3 {
+    let edge1 = v1.world_pos - v0.world_pos;
+    let edge2 = v2.world_pos - v0.world_pos;
+    let normal = edge1.cross(&edge2);
+
+    if normal.magnitude_squared() < 1e-12 {
+        // Triángulo degenerado: recurre al promedio de las normales de los vértices.
+        return ((v0.world_normal + v1.world_normal + v2.world_normal) / 3.0).normalize();
+    }
+
+    let normal = normal.normalize();
+    let average = v0.world_normal + v1.world_normal + v2.world_normal;
+    if normal.dot(&average) < 0.0 {
+        -normal
+    } else {
+        normal
+    }
+}
+
+/// Calcula la tolerancia baricéntrica de cada arista para la rasterización conservadora.
+///
+/// El peso `w_i` de un píxel es su distancia a la arista opuesta al vértice `i` dividida por
+/// la altura del triángulo sobre esa arista. Permitir `w_i >= -0.5 / altura` equivale a
+/// expandir cada arista medio píxel hacia afuera.
+///
+/// # Retorna
+/// `None` si el triángulo es degenerado o su área supera `CONSERVATIVE_AREA_THRESHOLD`.
+fn conservative_tolerance(
+    v0: &TransformedVertex,
+    v1: &TransformedVertex,
+    v2: &TransformedVertex,
+) -> Option<(f32, f32, f32)> {
+    let (a, b, c) = (v0.screen_pos, v1.screen_pos, v2.screen_pos);
+    let double_area = ((b - a).x * (c - a).y - (b - a).y * (c - a).x).abs();
+
+    if double_area < 1e-6 || double_area * 0.5 >= CONSERVATIVE_AREA_THRESHOLD {
+        return None;
+    }
+
+    // 0.5 / altura = 0.5 * longitud_arista / (2 * área)
+    let tolerance = |edge: Vec2| 0.5 * edge.magnitude() / double_area;
+    Some((tolerance(c - b), tolerance(a - c), tolerance(b - a)))
+}
+
+/// Calcula la altura del triángulo (en píxeles) sobre la arista opuesta a cada vértice.
+///
+/// Multiplicar un peso baricéntrico por su altura da la distancia con signo del píxel a
+/// esa arista. Devuelve `None` para triángulos degenerados.
+fn edge_altitudes(
+    v0: &TransformedVertex,
+    v1: &TransformedVertex,
+    v2: &TransformedVertex,
+) -> Option<(f32, f32, f32)> {
+    let (a, b, c) = (v0.screen_pos, v1.screen_pos, v2.screen_pos);
+    let double_area = ((b - a).x * (c - a).y - (b - a).y * (c - a).x).abs();
+    if double_area < 1e-6 {
+        return None;
+    }
+
+    let altitude = |edge: Vec2| double_area / edge.magnitude().max(1e-6);
+    Some((altitude(c - b), altitude(a - c), altitude(b - a)))
+}
+
+/// Cobertura aproximada de un píxel a partir de su distancia con signo a la arista más
+/// cercana: 0.5 justo sobre la arista, 0 a medio píxel hacia afuera.
+#[inline]
+fn edge_coverage(weights: (f32, f32, f32), altitudes: (f32, f32, f32)) -> f32 {
+    let distance = (weights.0 * altitudes.0)
+        .min(weights.1 * altitudes.1)
+        .min(weights.2 * altitudes.2);
+    (distance + 0.5).clamp(0.0, 1.0)
+}
+
+/// Tramo de quads `(primer x, último x)` de la fila de quads que empieza en `quad_y` que
+/// puede contener píxeles cubiertos, dentro de `[first_quad_x, max_x]`.
+///
+/// Une los tramos de las dos filas de píxeles del quad y los amplía un píxel por lado, de
+/// modo que el redondeo nunca deja fuera un píxel que el recorrido completo cubriría. El
+/// primer x conserva la alineación par de los quads.
+fn quad_row_span(
+    edges: &EdgeFunctions,
+    quad_y: usize,
+    slack: &Vec3,
+    first_quad_x: usize,
+    max_x: usize,
+) -> Option<(usize, usize)> {
+    let (low, high) = [0.5, 1.5]
+        .iter()
+        .filter_map(|dy| edges.row_span(quad_y as f32 + dy, slack))
+        .reduce(|(l0, h0), (l1, h1)| (l0.min(l1), h0.max(h1)))?;
+
+    // Píxeles cuyo centro (x + 0.5) cae en el tramo, con un píxel de margen.
+    let start = (low - 1.5).floor().max(first_quad_x as f32);
+    let end = (high + 0.5).ceil().min(max_x as f32);
+    if start > end {
+        return None;
+    }
+    Some((start as usize & !1, end as usize))
+}
+
+/// Indica si un triángulo se ve por detrás según el sentido de giro de sus vértices en
+/// pantalla.
+fn is_back_facing(
+    v0: &TransformedVertex,
+    v1: &TransformedVertex,
+    v2: &TransformedVertex,
+    front_face: FrontFace,
+) -> bool {
+    let (a, b, c) = (v0.screen_pos, v1.screen_pos, v2.screen_pos);
+    // Área con signo en píxeles. Como la Y de pantalla crece hacia abajo, es positiva para
+    // los triángulos que se ven en sentido horario.
+    let area = (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x);
+    match front_face {
+        FrontFace::Cw => area < 0.0,
+        FrontFace::Ccw => area > 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::{Color, Framebuffer, HdrFramebuffer};
+    use std::sync::Mutex;
+
+    /// Shader que registra la entrada de cada fragmento que recibe.
+    #[derive(Default)]
+    struct RecordingShader {
+        inputs: Mutex<Vec<FragmentInput>>,
+    }
+
+    impl RecordingShader {
+        fn normals(&self) -> Vec<Vec3> {
+            self.inputs
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|i| i.normal)
+                .collect()
+        }
+    }
+
+    impl StarShader for RecordingShader {
+        fn fragment(&self, input: &FragmentInput) -> Color {
+            self.inputs.lock().unwrap().push(*input);
+            Color::new(255, 255, 255)
+        }
+    }
+
+    /// Triángulo en NDC con una normal distinta en cada vértice.
+    fn triangle(positions: [Vec3; 3], normals: [Vec3; 3]) -> ObjMesh {
+        ObjMesh {
+            vertices: positions
+                .iter()
+                .zip(&normals)
+                .map(|(&position, &normal)| Vertex {
+                    position,
+                    normal,
+                    uv: Vec2::zeros(),
+                    tangent: Vec4::zeros(),
+                })
+                .collect(),
+            indices: vec![0, 1, 2],
+            submeshes: Vec::new(),
+        }
+    }
+
+    fn tilted_triangle() -> ObjMesh {
+        triangle(
+            [
+                Vec3::new(-0.8, -0.8, 0.0),
+                Vec3::new(0.8, -0.8, 0.0),
+                Vec3::new(0.0, 0.8, 0.0),
+            ],
+            [
+                Vec3::new(-0.6, 0.0, 0.8),
+                Vec3::new(0.6, 0.0, 0.8),
+                Vec3::new(0.0, 0.6, 0.8),
+            ],
+        )
+    }
+
+    /// Shader de color constante.
+    struct Solid(Color);
+
+    impl StarShader for Solid {
+        fn fragment(&self, _input: &FragmentInput) -> Color {
+            self.0
+        }
+    }
+
+    /// Dibuja `mesh` con matrices identidad (las posiciones se interpretan como NDC).
+    fn draw(
+        renderer: &Renderer,
+        framebuffer: &mut Framebuffer,
+        mesh: &ObjMesh,
+        shader: &dyn StarShader,
+    ) {
+        let identity = Mat4::identity();
+        renderer.render_mesh(
+            framebuffer,
+            mesh,
+            shader,
+            &identity,
+            &identity,
+            &identity,
+            0.0,
+        );
+    }
+
+    fn render(renderer: &Renderer, mesh: &ObjMesh, shader: &dyn StarShader) -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(renderer.width as usize, renderer.height as usize);
+        draw(renderer, &mut framebuffer, mesh, shader);
+        framebuffer
+    }
+
+    /// Color RGB del píxel `(x, y)`.
+    fn pixel(framebuffer: &Framebuffer, x: usize, y: usize) -> [u8; 3] {
+        let idx = (y * framebuffer.width + x) * 4;
+        [
+            framebuffer.buffer[idx],
+            framebuffer.buffer[idx + 1],
+            framebuffer.buffer[idx + 2],
+        ]
+    }
+
+    #[test]
+    fn flat_shading_uses_one_normal_per_triangle() {
+        let mut renderer = Renderer::new(32, 32).with_culling(false);
+        renderer.shading_mode = ShadingMode::Flat;
+        let shader = RecordingShader::default();
+        render(&renderer, &tilted_triangle(), &shader);
+
+        let normals = shader.normals();
+        assert!(normals.len() > 10);
+        assert!(normals.iter().all(|n| (n - normals[0]).magnitude() < 1e-6));
+        // La normal de la cara apunta hacia el mismo lado que las de los vértices.
+        assert!((normals[0] - Vec3::z()).magnitude() < 1e-5);
+
+        renderer.shading_mode = ShadingMode::Smooth;
+        let shader = RecordingShader::default();
+        render(&renderer, &tilted_triangle(), &shader);
+        let normals = shader.normals();
+        assert!(normals.iter().any(|n| (n - normals[0]).magnitude() > 1e-3));
+    }
+
+    #[test]
+    fn two_sided_flips_back_facing_normals() {
+        // Triángulo frente a la cámara (en el origen) con las normales hacia atrás.
+        let away = Vec3::new(0.0, 0.0, -1.0);
+        let mesh = triangle(
+            [
+                Vec3::new(-0.8, -0.8, 0.0),
+                Vec3::new(0.8, -0.8, 0.0),
+                Vec3::new(0.0, 0.8, 0.0),
+            ],
+            [away; 3],
+        );
+        let model = nalgebra_glm::translation(&Vec3::new(0.0, 0.0, -0.5));
+        let identity = Mat4::identity();
+        let normals_with = |two_sided: bool| {
+            let mut renderer = Renderer::new(32, 32).with_culling(false);
+            renderer.two_sided = two_sided;
+            let shader = RecordingShader::default();
+            let mut framebuffer = Framebuffer::new(32, 32);
+            renderer.render_mesh(
+                &mut framebuffer,
+                &mesh,
+                &shader,
+                &model,
+                &identity,
+                &identity,
+                0.0,
+            );
+            shader.normals()
+        };
+
+        let one_sided = normals_with(false);
+        assert!(!one_sided.is_empty());
+        assert!(one_sided.iter().all(|n| (n - away).magnitude() < 1e-5));
+        let two_sided = normals_with(true);
+        assert!(two_sided.iter().all(|n| (n + away).magnitude() < 1e-5));
+    }
+
+    #[test]
+    fn derivatives_match_linear_gradient_slope() {
+        // Con matrices identidad la posición es lineal en pantalla: x avanza 2/ancho por
+        // píxel y y retrocede 2/alto (el eje Y de pantalla está invertido).
+        let (width, height) = (40.0, 30.0);
+        let renderer = Renderer::new(width as usize, height as usize).with_culling(false);
+        let shader = RecordingShader::default();
+        render(&renderer, &tilted_triangle(), &shader);
+
+        let inputs = shader.inputs.lock().unwrap();
+        assert!(!inputs.is_empty());
+        for input in inputs.iter() {
+            assert!((input.dpdx - Vec3::new(2.0 / width, 0.0, 0.0)).magnitude() < 1e-5);
+            assert!((input.dpdy - Vec3::new(0.0, -2.0 / height, 0.0)).magnitude() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn depth_bias_wins_against_coincident_triangle() {
+        let mesh = tilted_triangle();
+        let mut renderer = Renderer::new(32, 32).with_culling(false);
+        let mut framebuffer = Framebuffer::new(32, 32);
+        draw(
+            &renderer,
+            &mut framebuffer,
+            &mesh,
+            &Solid(Color::new(255, 0, 0)),
+        );
+
+        // Sin desplazamiento, el triángulo coincidente no pasa la prueba de profundidad.
+        draw(
+            &renderer,
+            &mut framebuffer,
+            &mesh,
+            &Solid(Color::new(0, 255, 0)),
+        );
+        assert_eq!(pixel(&framebuffer, 16, 16), [255, 0, 0]);
+
+        renderer.depth_bias = 1e-4;
+        draw(
+            &renderer,
+            &mut framebuffer,
+            &mesh,
+            &Solid(Color::new(0, 0, 255)),
+        );
+        assert_eq!(pixel(&framebuffer, 16, 16), [0, 0, 255]);
+    }
+
+    #[test]
+    fn conservative_mode_keeps_sub_pixel_triangles() {
+        // Triángulo de 3 píxeles de ancho y 0.3 de alto que no contiene ningún centro de píxel.
+        let ndc = |x: f32, y: f32| Vec3::new(x / 16.0 - 1.0, 1.0 - y / 16.0, 0.0);
+        let mesh = triangle(
+            [ndc(5.0, 10.1), ndc(8.0, 10.1), ndc(6.5, 10.4)],
+            [Vec3::z(); 3],
+        );
+        let covered = |framebuffer: &Framebuffer| {
+            framebuffer
+                .zbuffer
+                .iter()
+                .filter(|depth| depth.is_finite())
+                .count()
+        };
+
+        let mut renderer = Renderer::new(32, 32).with_culling(false);
+        let shader = Solid(Color::new(255, 255, 255));
+        assert_eq!(covered(&render(&renderer, &mesh, &shader)), 0);
+        renderer.conservative = true;
+        assert!(covered(&render(&renderer, &mesh, &shader)) >= 1);
+    }
+
+    #[test]
+    fn vertices_report_unit_barycentric_weights() {
+        let ndc = |x: f32, y: f32| Vec3::new(x / 16.0 - 1.0, 1.0 - y / 16.0, 0.0);
+        let corners = [ndc(4.5, 4.5), ndc(26.5, 6.5), ndc(10.5, 27.5)];
+        let shader = RecordingShader::default();
+        render(
+            &Renderer::new(32, 32).with_culling(false),
+            &triangle(corners, [Vec3::z(); 3]),
+            &shader,
+        );
+
+        let inputs = shader.inputs.lock().unwrap();
+        // Los pesos reconstruyen la posición de cada fragmento a partir de los vértices.
+        for input in inputs.iter() {
+            let w = input.barycentric;
+            let rebuilt = corners[0] * w.x + corners[1] * w.y + corners[2] * w.z;
+            assert!((rebuilt - input.world_pos).magnitude() < 1e-4);
+            assert!((w.sum() - 1.0).abs() < 1e-5);
+            assert_eq!(input.triangle_id, 0);
+        }
+        // El fragmento más cercano a cada vértice tiene casi todo el peso en él.
+        for (k, corner) in corners.iter().enumerate() {
+            let nearest = inputs
+                .iter()
+                .min_by(|a, b| {
+                    let da = (a.world_pos - corner).magnitude();
+                    da.total_cmp(&(b.world_pos - corner).magnitude())
+                })
+                .unwrap();
+            assert!(nearest.barycentric[k] > 0.9, "{:?}", nearest.barycentric);
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_vertex_transform_matches_serial() {
+        let mesh = ObjMesh::create_sphere(1.0, 100, 100);
+        assert!(mesh.vertices.len() >= PARALLEL_VERTEX_THRESHOLD);
+        let renderer = Renderer::new(64, 64).with_culling(false);
+        let shader = crate::shaders::ProminenceSunShader::default();
+        let model = nalgebra_glm::rotation(0.7, &Vec3::new(0.3, 1.0, 0.2));
+        let view = nalgebra_glm::translation(&Vec3::new(0.0, 0.0, -3.0));
+        let mvp = nalgebra_glm::perspective(1.0, 1.0, 0.1, 10.0) * view * model;
+
+        let parallel = renderer.transform_vertices(&mesh, &shader, &model, &view, &mvp, 1.5);
+        let key = |v: &TransformedVertex| {
+            (
+                v.screen_pos,
+                v.depth,
+                v.view_depth,
+                v.world_pos,
+                v.world_normal,
+            )
+        };
+        for (vertex, transformed) in mesh.vertices.iter().zip(&parallel) {
+            let serial =
+                renderer.transform_vertex(&shader.vertex(vertex, 1.5), &model, &view, &mvp);
+            assert_eq!(key(&serial), key(transformed));
+        }
+    }
+
+    /// Colorea según la profundidad en espacio mundo: rojo delante (z < 0), verde detrás.
+    struct DepthColor;
+
+    impl StarShader for DepthColor {
+        fn fragment(&self, input: &FragmentInput) -> Color {
+            if input.world_pos.z < 0.0 {
+                Color::new(255, 0, 0)
+            } else {
+                Color::new(0, 255, 0)
+            }
+        }
+    }
+
+    #[test]
+    fn instances_occlude_each_other() {
+        let renderer = Renderer::new(32, 32).with_culling(false);
+        let near = nalgebra_glm::translation(&Vec3::new(0.0, 0.0, -0.2));
+        let far = nalgebra_glm::translation(&Vec3::new(0.1, 0.0, 0.2));
+        let identity = Mat4::identity();
+
+        for instances in [[near, far], [far, near]] {
+            let mut framebuffer = Framebuffer::new(32, 32);
+            renderer.render_instanced(
+                &mut framebuffer,
+                &tilted_triangle(),
+                &DepthColor,
+                &instances,
+                &DrawParams::new(identity, identity, identity),
+            );
+            // La instancia cercana tapa a la lejana sin importar el orden de dibujo.
+            assert_eq!(pixel(&framebuffer, 16, 16), [255, 0, 0]);
+            // Donde solo llega la lejana (desplazada a la derecha), se ve la lejana.
+            assert_eq!(pixel(&framebuffer, 29, 27), [0, 255, 0]);
+        }
+    }
+
+    #[test]
+    fn shared_model_matrix_is_applied_after_each_instance() {
+        let renderer = Renderer::new(32, 32).with_culling(false);
+        let group = nalgebra_glm::translation(&Vec3::new(0.25, -0.125, 0.0));
+        let instance = nalgebra_glm::rotation(0.5, &Vec3::z());
+        let view = nalgebra_glm::translation(&Vec3::new(0.0, 0.0, -0.1));
+        let identity = Mat4::identity();
+
+        let mut instanced = Framebuffer::new(32, 32);
+        let params = DrawParams::new(group, view, identity).with_time(1.5);
+        renderer.render_instanced(
+            &mut instanced,
+            &tilted_triangle(),
+            &DepthColor,
+            &[instance],
+            &params,
+        );
+
+        let mut single = Framebuffer::new(32, 32);
+        renderer.render_mesh(
+            &mut single,
+            &tilted_triangle(),
+            &DepthColor,
+            &(group * instance),
+            &view,
+            &identity,
+            1.5,
+        );
+        assert_eq!(instanced.as_bytes(), single.as_bytes());
+        assert_ne!(instanced.as_bytes(), Framebuffer::new(32, 32).as_bytes());
+    }
+
+    #[test]
+    fn edge_aa_blends_silhouette_pixels() {
+        // Arista vertical en x = 16.25: el centro del píxel 16 queda a 0.25 px por fuera.
+        let ndc = |x: f32, y: f32| Vec3::new(x / 16.0 - 1.0, 1.0 - y / 16.0, 0.0);
+        let mesh = triangle(
+            [ndc(2.0, 30.0), ndc(16.25, 2.0), ndc(16.25, 30.0)],
+            [Vec3::z(); 3],
+        );
+        let white = Solid(Color::new(255, 255, 255));
+        let mut renderer = Renderer::new(32, 32).with_culling(false);
+        assert_eq!(pixel(&render(&renderer, &mesh, &white), 16, 20), [0, 0, 0]);
+
+        renderer.edge_aa = true;
+        let framebuffer = render(&renderer, &mesh, &white);
+        let edge = pixel(&framebuffer, 16, 20)[0];
+        assert!(edge > 0 && edge < 255, "borde {edge}");
+        // El interior sigue siendo opaco y el exterior lejano intacto.
+        assert_eq!(pixel(&framebuffer, 15, 20), [255, 255, 255]);
+        assert_eq!(pixel(&framebuffer, 18, 20), [0, 0, 0]);
+    }
+
+    #[test]
+    fn render_points_draws_squares_and_skips_clipped_vertices() {
+        let renderer = Renderer::new(32, 32).with_culling(false);
+        let mut fb = Framebuffer::new(32, 32);
+        let mut mesh = triangle(
+            [
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(0.5, 0.5, 5.0),
+                Vec3::new(0.5, 0.5, 5.0),
+            ],
+            [Vec3::z(); 3],
+        );
+        mesh.indices.clear();
+        let identity = Mat4::identity();
+        let red = Color::new(255, 0, 0);
+        let params = DrawParams::new(identity, identity, identity).with_color(red);
+        renderer.render_points(&mut fb, &mesh, &params, 1);
+
+        let drawn: Vec<(usize, usize)> = (0..32)
+            .flat_map(|y| (0..32).map(move |x| (x, y)))
+            .filter(|&(x, y)| pixel(&fb, x, y) == [255, 0, 0])
+            .collect();
+        assert_eq!(
+            drawn.len(),
+            9,
+            "un cuadrado de 3x3 para el único vértice visible"
+        );
+        assert!(drawn.contains(&(16, 16)));
+    }
+
+    #[test]
+    fn project_point_matches_rasterizer_screen_position() {
+        let renderer = Renderer::new(64, 48);
+        let view = nalgebra_glm::look_at(&Vec3::new(0.0, 0.0, 3.0), &Vec3::zeros(), &Vec3::y());
+        let projection = nalgebra_glm::perspective(64.0 / 48.0, 1.0, 0.1, 100.0);
+        let model = Mat4::identity();
+        let mvp = projection * view * model;
+
+        assert!(
+            (renderer.project_point(Vec3::zeros(), &mvp) - Vec2::new(32.0, 24.0)).magnitude()
+                < 1e-4
+        );
+
+        let vertex = Vertex {
+            position: Vec3::new(0.4, -0.3, 0.2),
+            normal: Vec3::z(),
+            uv: Vec2::zeros(),
+            tangent: Vec4::zeros(),
+        };
+        let transformed = renderer.transform_vertex(&vertex, &model, &view, &mvp);
+        assert_eq!(
+            renderer.project_point(vertex.position, &mvp),
+            transformed.screen_pos
+        );
+        // Un punto sobre el plano de la cámara queda fuera de pantalla.
+        assert_eq!(
+            renderer.project_point(Vec3::new(0.0, 0.0, 3.0), &mvp),
+            Vec2::new(-1000.0, -1000.0)
+        );
+    }
+
+    /// Renderiza una esfera con el sol clásico en el instante `time`.
+    fn render_sun(time: f32) -> Framebuffer {
+        let renderer = Renderer::new(48, 48);
+        let mut framebuffer = Framebuffer::new(48, 48);
+        let sphere = ObjMesh::create_sphere(0.8, 12, 16);
+        let shader = crate::shaders::ClassicSunShader::default();
+        let identity = Mat4::identity();
+        renderer.render_mesh(
+            &mut framebuffer,
+            &sphere,
+            &shader,
+            &identity,
+            &identity,
+            &identity,
+            time,
+        );
+        framebuffer
+    }
+
+    #[test]
+    fn same_time_renders_identical_frames() {
+        let first = render_sun(2.5);
+        assert_eq!(first.buffer, render_sun(2.5).buffer);
+        assert_ne!(
+            first.buffer,
+            render_sun(7.0).buffer,
+            "el tiempo sí cambia la imagen"
+        );
+    }
+
+    #[test]
+    fn each_submesh_uses_its_material_shader() {
+        // Dos triángulos lado a lado: el izquierdo con material 0 y el derecho con material 1.
+        let mut mesh = triangle(
+            [
+                Vec3::new(-0.9, -0.5, 0.0),
+                Vec3::new(-0.1, -0.5, 0.0),
+                Vec3::new(-0.5, 0.5, 0.0),
+            ],
+            [Vec3::z(); 3],
+        );
+        let right: Vec<Vertex> = mesh
+            .vertices
+            .iter()
+            .map(|v| Vertex {
+                position: v.position + Vec3::new(1.0, 0.0, 0.0),
+                ..*v
+            })
+            .collect();
+        mesh.vertices.extend(right);
+        mesh.indices.extend([3, 4, 5]);
+        mesh.submeshes = vec![
+            Submesh {
+                start: 0,
+                count: 3,
+                material_id: 0,
+            },
+            Submesh {
+                start: 3,
+                count: 3,
+                material_id: 1,
+            },
+        ];
+
+        let renderer = Renderer::new(32, 32).with_culling(false);
+        let mut framebuffer = Framebuffer::new(32, 32);
+        let (red, green) = (Solid(Color::new(255, 0, 0)), Solid(Color::new(0, 255, 0)));
+        let identity = Mat4::identity();
+        renderer.render_mesh_with_materials(
+            &mut framebuffer,
+            &mesh,
+            &[&red, &green],
+            &DrawParams::new(identity, identity, identity),
+        );
+        assert_eq!(pixel(&framebuffer, 8, 16), [255, 0, 0]);
+        assert_eq!(pixel(&framebuffer, 24, 16), [0, 255, 0]);
+
+        // Los materiales sin shader propio usan el último de la lista.
+        let mut framebuffer = Framebuffer::new(32, 32);
+        renderer.render_mesh_with_materials(
+            &mut framebuffer,
+            &mesh,
+            &[&red],
+            &DrawParams::new(identity, identity, identity),
+        );
+        assert_eq!(pixel(&framebuffer, 24, 16), [255, 0, 0]);
+    }
+
+    #[test]
+    fn view_depth_is_linear_distance_along_camera_axis() {
+        let renderer = Renderer::new(32, 32).with_culling(false);
+        let mut framebuffer = Framebuffer::new(32, 32);
+        let shader = RecordingShader::default();
+        let view = nalgebra_glm::look_at(&Vec3::new(0.0, 0.0, 3.0), &Vec3::zeros(), &Vec3::y());
+        let projection = nalgebra_glm::perspective(1.0, 1.0, 0.1, 100.0);
+        // Triángulo inclinado: su profundidad varía de 2 a 4 unidades de la cámara.
+        let mesh = triangle(
+            [
+                Vec3::new(-0.5, -0.5, 1.0),
+                Vec3::new(0.5, -0.5, 1.0),
+                Vec3::new(0.0, 0.5, -1.0),
+            ],
+            [Vec3::z(); 3],
+        );
+        renderer.render_mesh(
+            &mut framebuffer,
+            &mesh,
+            &shader,
+            &Mat4::identity(),
+            &view,
+            &projection,
+            0.0,
+        );
+
+        let inputs = shader.inputs.lock().unwrap();
+        assert!(!inputs.is_empty());
+        for input in inputs.iter() {
+            let expected = 3.0 - input.world_pos.z;
+            assert!((input.view_depth - expected).abs() < 1e-3, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn renders_into_any_render_target() {
+        let renderer = Renderer::new(32, 32).with_culling(false);
+        let identity = Mat4::identity();
+        let shader = Solid(Color::new(255, 0, 0));
+
+        let mut hdr = HdrFramebuffer::new(32, 32);
+        renderer.render_mesh(
+            &mut hdr,
+            &tilted_triangle(),
+            &shader,
+            &identity,
+            &identity,
+            &identity,
+            0.0,
+        );
+        let reference = render(&renderer, &tilted_triangle(), &shader);
+        let rgb = |bytes: &[u8]| -> Vec<u8> {
+            bytes
+                .chunks_exact(4)
+                .flat_map(|p| p[..3].to_vec())
+                .collect()
+        };
+        assert_eq!(
+            rgb(&hdr.resolve()),
+            rgb(&reference.buffer),
+            "mismo resultado que el framebuffer"
+        );
+        assert_eq!(hdr.zbuffer, reference.zbuffer);
+    }
+
+    #[test]
+    fn occluded_fragments_skip_the_shader() {
+        let renderer = Renderer::new(32, 32).with_culling(false);
+        let mut framebuffer = Framebuffer::new(32, 32);
+        let near = Solid(Color::new(255, 0, 0));
+        draw(&renderer, &mut framebuffer, &tilted_triangle(), &near);
+
+        // El mismo triángulo más lejos (z = 0.5) queda oculto por completo.
+        let mut far = tilted_triangle();
+        for vertex in &mut far.vertices {
+            vertex.position.z = 0.5;
+        }
+        let shader = RecordingShader::default();
+        draw(&renderer, &mut framebuffer, &far, &shader);
+        assert!(
+            shader.normals().is_empty(),
+            "ningún fragmento oculto se sombrea"
+        );
+        assert!(framebuffer.depth_test(16, 16, -0.5));
+        assert!(!framebuffer.depth_test(16, 16, 0.5));
+    }
+
+    #[test]
+    fn wireframe_overlay_draws_edges_over_the_surface() {
+        let renderer = Renderer::new(32, 32).with_culling(false);
+        let mut framebuffer = Framebuffer::new(32, 32);
+        let identity = Mat4::identity();
+        renderer.render_mesh_wireframe_overlay(
+            &mut framebuffer,
+            &tilted_triangle(),
+            &Solid(Color::new(255, 0, 0)),
+            &DrawParams::new(identity, identity, identity).with_color(Color::new(0, 255, 0)),
+        );
+
+        // La base del triángulo está en y = -0.8, es decir, en la fila 28 del búfer.
+        assert_eq!(pixel(&framebuffer, 16, 28), [0, 255, 0]);
+        assert_eq!(
+            pixel(&framebuffer, 16, 16),
+            [255, 0, 0],
+            "el interior no cambia"
+        );
+    }
+
+    #[test]
+    fn render_normals_draws_one_segment_per_visible_vertex() {
+        let renderer = Renderer::new(32, 32);
+        let mut framebuffer = Framebuffer::new(32, 32);
+        // Normales en +X: cada segmento sale horizontalmente hacia la derecha.
+        let mut mesh = triangle(
+            [
+                Vec3::new(-0.5, 0.0, 0.0),
+                Vec3::new(0.0, 0.5, 0.0),
+                Vec3::new(0.0, 0.0, 5.0),
+            ],
+            [Vec3::x(); 3],
+        );
+        mesh.indices.clear();
+        let identity = Mat4::identity();
+        let drawn = renderer.render_normals(
+            &mut framebuffer,
+            &mesh,
+            &DrawParams::new(identity, identity, identity).with_color(Color::new(0, 0, 255)),
+            0.25,
+        );
+        assert_eq!(drawn, 2, "el vértice fuera del volumen se omite");
+
+        // De x = -0.5 a -0.25 en NDC: columnas 8 a 12 de la fila 16.
+        for x in 8..=12 {
+            assert_eq!(pixel(&framebuffer, x, 16), [0, 0, 255], "columna {x}");
+        }
+        assert_eq!(pixel(&framebuffer, 14, 16), [0, 0, 0]);
+    }
+
+    #[test]
+    fn clipping_keeps_only_the_visible_part_of_crossing_triangles() {
+        let renderer = Renderer::new(32, 32).with_culling(false);
+
+        // Un triángulo mucho mayor que la pantalla la cubre por completo.
+        let huge = triangle(
+            [
+                Vec3::new(-50.0, -50.0, 0.0),
+                Vec3::new(50.0, -50.0, 0.0),
+                Vec3::new(0.0, 50.0, 0.0),
+            ],
+            [Vec3::z(); 3],
+        );
+        let framebuffer = render(&renderer, &huge, &Solid(Color::new(255, 0, 0)));
+        assert!(
+            framebuffer
+                .buffer
+                .chunks_exact(4)
+                .all(|p| p[..3] == [255, 0, 0])
+        );
+
+        // Un vértice queda detrás de la cámara: solo se sombrea lo que está delante del
+        // plano cercano.
+        let near = 0.1;
+        let behind = triangle(
+            [
+                Vec3::new(-1.0, -1.0, -3.0),
+                Vec3::new(1.0, -1.0, -3.0),
+                Vec3::new(0.0, -1.0, 1.0),
+            ],
+            [Vec3::y(); 3],
+        );
+        let shader = RecordingShader::default();
+        let mut framebuffer = Framebuffer::new(32, 32);
+        let identity = Mat4::identity();
+        let projection = nalgebra_glm::perspective(1.0, 1.2, near, 100.0);
+        renderer.render_mesh(
+            &mut framebuffer,
+            &behind,
+            &shader,
+            &identity,
+            &identity,
+            &projection,
+            0.0,
+        );
+        let inputs = shader.inputs.lock().unwrap();
+        assert!(inputs.len() > 20, "{} fragmentos", inputs.len());
+        assert!(inputs.iter().all(|i| i.world_pos.z <= -near + 1e-3));
+    }
+
+    #[test]
+    fn non_finite_vertices_are_skipped() {
+        let renderer = Renderer::new(32, 32).with_culling(false);
+        let mesh = triangle(
+            [
+                Vec3::new(-0.8, -0.8, 0.0),
+                Vec3::new(0.8, -0.8, 0.0),
+                Vec3::new(f32::NAN, 0.8, 0.0),
+            ],
+            [Vec3::z(); 3],
+        );
+        let shader = RecordingShader::default();
+        let framebuffer = render(&renderer, &mesh, &shader);
+        assert!(shader.inputs.lock().unwrap().is_empty());
+        assert!(
+            framebuffer
+                .buffer
+                .chunks_exact(4)
+                .all(|p| p[..3] == [0, 0, 0])
+        );
+
+        let mut framebuffer = Framebuffer::new(32, 32);
+        let identity = Mat4::identity();
+        let drawn = renderer.render_normals(
+            &mut framebuffer,
+            &mesh,
+            &DrawParams::new(identity, identity, identity).with_color(Color::new(0, 0, 255)),
+            0.25,
+        );
+        assert_eq!(drawn, 2, "solo el vértice no finito se omite");
+    }
+
+    #[test]
+    fn exposure_scales_fragment_color_and_saturates() {
+        let mut renderer = Renderer::new(32, 32).with_culling(false);
+        let mesh = tilted_triangle();
+        let shader = Solid(Color::new(100, 200, 10));
+
+        let base = render(&renderer, &mesh, &shader);
+        assert_eq!(pixel(&base, 16, 16), [100, 200, 10]);
+
+        renderer.exposure = 0.5;
+        assert_eq!(
+            pixel(&render(&renderer, &mesh, &shader), 16, 16),
+            [50, 100, 5]
+        );
+
+        renderer.exposure = 2.0;
+        let bright = render(&renderer, &mesh, &shader);
+        assert_eq!(pixel(&bright, 16, 16), [200, 255, 20], "el verde satura");
+        // El fondo no se ve afectado.
+        assert_eq!(pixel(&bright, 0, 0), [0, 0, 0]);
+    }
+
+    #[test]
+    fn backface_culling_follows_front_face_winding() {
+        let mut renderer = Renderer::new(32, 32);
+        renderer.cull_backfaces = true;
+        let shader = Solid(Color::new(255, 0, 0));
+
+        // En pantalla el triángulo se ve en sentido antihorario.
+        renderer.front_face = FrontFace::Ccw;
+        let drawn = render(&renderer, &tilted_triangle(), &shader);
+        assert_eq!(pixel(&drawn, 16, 16), [255, 0, 0]);
+        renderer.front_face = FrontFace::Cw;
+        let culled = render(&renderer, &tilted_triangle(), &shader);
+        assert!(culled.buffer.chunks_exact(4).all(|p| p[..3] == [0, 0, 0]));
+
+        // En una esfera cerrada vista con una cámara real, descartar las caras traseras no
+        // cambia la imagen y el shader solo recibe fragmentos de la mitad visible.
+        let sphere = ObjMesh::create_sphere(1.0, 12, 16);
+        let view = nalgebra_glm::look_at(&Vec3::new(0.0, 0.0, 3.0), &Vec3::zeros(), &Vec3::y());
+        let projection = nalgebra_glm::perspective(1.0, 1.0, 0.1, 100.0);
+        let draw_sphere = |renderer: &Renderer, shader: &dyn StarShader| {
+            let mut framebuffer = Framebuffer::new(32, 32);
+            renderer.render_mesh(
+                &mut framebuffer,
+                &sphere,
+                shader,
+                &Mat4::identity(),
+                &view,
+                &projection,
+                0.0,
+            );
+            framebuffer
+        };
+        renderer.cull_backfaces = false;
+        let full = draw_sphere(&renderer, &DepthColor);
+        renderer.cull_backfaces = true;
+        assert_eq!(draw_sphere(&renderer, &DepthColor).buffer, full.buffer);
+        let recorder = RecordingShader::default();
+        draw_sphere(&renderer, &recorder);
+        assert!(!recorder.normals().is_empty());
+        assert!(
+            recorder.normals().iter().all(|n| n.z > -0.2),
+            "solo caras frontales"
+        );
+    }
+
+    #[test]
+    fn billboard_faces_the_camera_and_fades_radially() {
+        let renderer = Renderer::new(32, 32);
+        let view = nalgebra_glm::look_at(&Vec3::new(3.0, 2.0, 4.0), &Vec3::zeros(), &Vec3::y());
+        let projection = nalgebra_glm::perspective(1.0, 1.0, 0.1, 100.0);
+        let mut framebuffer = Framebuffer::new(32, 32);
+        framebuffer.clear(Color::new(0, 0, 0));
+        renderer.render_billboard(
+            &mut framebuffer,
+            Vec3::zeros(),
+            2.0,
+            Color::new(255, 255, 255),
+            &view,
+            &projection,
+        );
+
+        // Visto de frente desde una cámara oblicua: el brillo es simétrico alrededor del centro
+        // de la pantalla, en el borde entre las columnas 15 y 16.
+        let center = pixel(&framebuffer, 16, 16)[0];
+        assert!(center > 200, "{center}");
+        let (left, right) = (
+            pixel(&framebuffer, 12, 16)[0],
+            pixel(&framebuffer, 19, 16)[0],
+        );
+        assert!(left < center && left.abs_diff(right) <= 8, "{left} {right}");
+        assert_eq!(
+            pixel(&framebuffer, 2, 2),
+            [0, 0, 0],
+            "esquina fuera del círculo"
+        );
+        assert!(framebuffer.depth_slice().iter().all(|d| d.is_infinite()));
+    }
+
+    #[test]
+    fn biased_depth_is_clamped_to_ndc_range() {
+        let mut renderer = Renderer::new(32, 32).with_culling(false);
+        renderer.depth_bias = 5.0;
+        let framebuffer = render(&renderer, &tilted_triangle(), &Solid(Color::new(255, 0, 0)));
+        assert_eq!(pixel(&framebuffer, 16, 16), [255, 0, 0]);
+        assert_eq!(
+            framebuffer.zbuffer[16 * 32 + 16],
+            -1.0,
+            "z - 5 se limita a -1"
+        );
+
+        renderer.depth_bias = -5.0;
+        let framebuffer = render(&renderer, &tilted_triangle(), &Solid(Color::new(255, 0, 0)));
+        assert_eq!(
+            framebuffer.zbuffer[16 * 32 + 16],
+            1.0,
+            "z + 5 se limita a 1"
+        );
+        assert!(
+            framebuffer
+                .zbuffer
+                .iter()
+                .all(|d| d.is_infinite() || (-1.0..=1.0).contains(d))
+        );
+    }
+
+    /// Shader que codifica los pesos baricéntricos en el color.
+    struct Barycentric;
+
+    impl StarShader for Barycentric {
+        fn fragment(&self, input: &FragmentInput) -> Color {
+            Color::from_vec3(input.barycentric)
+        }
+    }
+
+    #[test]
+    fn scanline_covers_the_same_pixels_with_the_same_weights() {
+        let sliver = triangle(
+            [
+                Vec3::new(-0.95, -0.9, 0.2),
+                Vec3::new(0.9, 0.85, -0.3),
+                Vec3::new(0.85, 0.95, 0.1),
+            ],
+            [Vec3::z(); 3],
+        );
+        for mesh in [tilted_triangle(), sliver] {
+            for (conservative, edge_aa) in [(false, false), (true, false), (false, true)] {
+                let mut renderer = Renderer::new(37, 29).with_culling(false);
+                renderer.conservative = conservative;
+                renderer.edge_aa = edge_aa;
+                let full = render(&renderer, &mesh, &Barycentric);
+                renderer.scanline = true;
+                let spans = render(&renderer, &mesh, &Barycentric);
+
+                assert!(full.buffer.chunks_exact(4).any(|p| p[..3] != [0, 0, 0]));
+                assert_eq!(full.buffer, spans.buffer, "{conservative} {edge_aa}");
+                assert!(
+                    full.zbuffer
+                        .iter()
+                        .zip(&spans.zbuffer)
+                        .all(|(a, b)| a.to_bits() == b.to_bits())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn builder_sets_every_option_and_defaults_to_new() {
+        let default = Renderer::builder(40, 30).build();
+        let new = Renderer::new(40, 30);
+        assert_eq!((default.width, default.height), (40.0, 30.0));
+        assert_eq!(default.shading_mode, new.shading_mode);
+        assert_eq!(default.front_face, new.front_face);
+        assert_eq!(default.blend_mode, new.blend_mode);
+        assert_eq!(
+            (
+                default.two_sided,
+                default.cull_backfaces,
+                default.conservative
+            ),
+            (new.two_sided, new.cull_backfaces, new.conservative)
+        );
+        assert_eq!(
+            (default.edge_aa, default.scanline),
+            (new.edge_aa, new.scanline)
+        );
+        assert_eq!(
+            (default.depth_bias, default.exposure),
+            (new.depth_bias, new.exposure)
+        );
+
+        let renderer = Renderer::builder(40, 30)
+            .shading_mode(ShadingMode::Flat)
+            .two_sided(true)
+            .front_face(FrontFace::Ccw)
+            .cull_backfaces(true)
+            .blend_mode(BlendMode::Additive)
+            .depth_bias(0.01)
+            .conservative(true)
+            .edge_aa(true)
+            .scanline(true)
+            .exposure(2.0)
+            .build();
+        assert_eq!(renderer.shading_mode, ShadingMode::Flat);
+        assert_eq!(renderer.front_face, FrontFace::Ccw);
+        assert_eq!(renderer.blend_mode, BlendMode::Additive);
+        assert!(renderer.two_sided && renderer.cull_backfaces && renderer.conservative);
+        assert!(renderer.edge_aa && renderer.scanline);
+        assert_eq!((renderer.depth_bias, renderer.exposure), (0.01, 2.0));
+    }
+
+    /// Shader que guarda la posición en espacio mundo y del objeto de cada fragmento.
+    struct PositionRecorder(Mutex<Vec<(Vec3, Vec3)>>);
+
+    impl StarShader for PositionRecorder {
+        fn fragment(&self, input: &FragmentInput) -> Color {
+            self.0
+                .lock()
+                .unwrap()
+                .push((input.world_pos, input.object_pos));
+            Color::new(255, 255, 255)
+        }
+    }
+
+    #[test]
+    fn object_position_is_interpolated_before_the_model_matrix() {
+        let renderer = Renderer::new(32, 32).with_culling(false);
+        let mut framebuffer = Framebuffer::new(32, 32);
+        let recorder = PositionRecorder(Mutex::new(Vec::new()));
+        let offset = Vec3::new(0.1, -0.05, 0.0);
+        let model = nalgebra_glm::translate(&Mat4::identity(), &offset);
+        let identity = Mat4::identity();
+        renderer.render_mesh(
+            &mut framebuffer,
+            &tilted_triangle(),
+            &recorder,
+            &model,
+            &identity,
+            &identity,
+            0.0,
+        );
+
+        let positions = recorder.0.into_inner().unwrap();
+        assert!(!positions.is_empty());
+        for (world, object) in positions {
+            assert!(
+                (world - offset - object).magnitude() < 1e-5,
+                "{world} {object}"
+            );
+        }
+    }
+
+    /// Guarda las derivadas en espacio mundo y del objeto de cada fragmento.
+    struct DerivativeRecorder(Mutex<Vec<(Vec3, Vec3)>>);
+
+    impl StarShader for DerivativeRecorder {
+        fn fragment(&self, input: &FragmentInput) -> Color {
+            self.0.lock().unwrap().push((input.dpdx, input.object_dpdx));
+            Color::new(255, 255, 255)
+        }
+    }
+
+    #[test]
+    fn object_derivatives_undo_the_model_scale() {
+        let renderer = Renderer::new(32, 32).with_culling(false);
+        let mut framebuffer = Framebuffer::new(32, 32);
+        let recorder = DerivativeRecorder(Mutex::new(Vec::new()));
+        let model = nalgebra_glm::scale(&Mat4::identity(), &Vec3::repeat(0.5));
+        let identity = Mat4::identity();
+        renderer.render_mesh(
+            &mut framebuffer,
+            &tilted_triangle(),
+            &recorder,
+            &model,
+            &identity,
+            &identity,
+            0.0,
+        );
+
+        let derivatives = recorder.0.into_inner().unwrap();
+        assert!(!derivatives.is_empty());
+        for (world, object) in derivatives {
+            assert!(object.magnitude() > 0.0);
+            assert!(
+                (world - object * 0.5).magnitude() < 1e-5,
+                "{world} {object}"
+            );
+        }
+    }
+
+    #[test]
+    fn attributes_are_interpolated_with_perspective_correction() {
+        let renderer = Renderer::new(48, 48).with_culling(false);
+        let mut framebuffer = Framebuffer::new(48, 48);
+        let shader = RecordingShader::default();
+        let view = nalgebra_glm::look_at(&Vec3::new(0.0, 0.0, 3.0), &Vec3::zeros(), &Vec3::y());
+        let projection = nalgebra_glm::perspective(1.0, 1.2, 0.1, 100.0);
+        // Triángulo muy inclinado: su profundidad va de 2 a 9 unidades de la cámara.
+        let mut mesh = triangle(
+            [
+                Vec3::new(-1.0, -1.0, 1.0),
+                Vec3::new(1.0, -1.0, 1.0),
+                Vec3::new(0.0, 3.0, -6.0),
+            ],
+            [Vec3::z(); 3],
+        );
+        // Las UV repiten X e Y del mundo, así que deben seguirlas en todo el triángulo.
+        for vertex in &mut mesh.vertices {
+            vertex.uv = vertex.position.xy();
+        }
+        renderer.render_mesh(
+            &mut framebuffer,
+            &mesh,
+            &shader,
+            &Mat4::identity(),
+            &view,
+            &projection,
+            0.0,
+        );
+
+        let mvp = projection * view;
+        let inputs = shader.inputs.lock().unwrap();
+        assert!(inputs.len() > 100);
+        for input in inputs.iter() {
+            // La posición interpolada se proyecta justo en el centro de su píxel; sin la
+            // corrección se desviaría varios píxeles en la parte lejana.
+            let screen = renderer.project_point(input.world_pos, &mvp);
+            let offset = screen.map(|c| c.fract() - 0.5);
+            assert!(offset.magnitude() < 0.02, "{screen}");
+            assert!((input.uv - input.world_pos.xy()).magnitude() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn perspective_weights_normalize_and_keep_equal_w_unchanged() {
+        let weights = (0.2, 0.3, 0.5);
+        let same = perspective_weights(weights, (0.5, 0.5, 0.5));
+        assert!((same.0 - 0.2).abs() < 1e-6 && (same.1 - 0.3).abs() < 1e-6);
+
+        // Un vértice más lejano (menor 1 / w) pierde peso; la suma sigue siendo 1.
+        let (p0, p1, p2) = perspective_weights(weights, (1.0, 1.0, 0.1));
+        assert!(p2 < 0.5 && p0 > 0.2);
+        assert!((p0 + p1 + p2 - 1.0).abs() < 1e-6);
+        // Sin información de w se devuelven los pesos de pantalla.
+        assert_eq!(perspective_weights(weights, (0.0, 0.0, 0.0)), weights);
+    }
+
+    #[test]
+    fn back_faces_are_culled_by_default() {
+        let renderer = Renderer::new(32, 32);
+        assert!(renderer.cull_backfaces);
+        // `tilted_triangle` gira en sentido antihorario en pantalla: con `FrontFace::Cw`,
+        // el valor por defecto, es una cara trasera.
+        let culled = render(&renderer, &tilted_triangle(), &Solid(Color::new(255, 0, 0)));
+        assert!(culled.zbuffer.iter().all(|d| d.is_infinite()));
+
+        let renderer = renderer.with_culling(false);
+        assert!(!renderer.cull_backfaces);
+        let drawn = render(&renderer, &tilted_triangle(), &Solid(Color::new(255, 0, 0)));
+        assert_eq!(pixel(&drawn, 16, 16), [255, 0, 0]);
+    }
+
+    #[test]
+    fn fragments_receive_the_direction_towards_the_camera() {
+        let eye = Vec3::new(0.5, 0.2, 3.0);
+        let view = nalgebra_glm::look_at(&eye, &Vec3::zeros(), &Vec3::y());
+        let mesh = tilted_triangle();
+        let perspective = nalgebra_glm::perspective(1.0, 1.0, 0.1, 100.0);
+        let orthographic = nalgebra_glm::ortho(-1.0, 1.0, -1.0, 1.0, 0.1, 100.0);
+
+        for (projection, orthographic) in [(perspective, false), (orthographic, true)] {
+            let renderer = Renderer::new(32, 32).with_culling(false);
+            let mut framebuffer = Framebuffer::new(32, 32);
+            let shader = RecordingShader::default();
+            renderer.render_mesh(
+                &mut framebuffer,
+                &mesh,
+                &shader,
+                &Mat4::identity(),
+                &view,
+                &projection,
+                0.0,
+            );
+
+            let inputs = shader.inputs.lock().unwrap();
+            assert!(!inputs.is_empty());
+            for input in inputs.iter() {
+                // En ortográfica todos los rayos son paralelos al eje de la cámara.
+                let expected = if orthographic {
+                    eye.normalize()
+                } else {
+                    (eye - input.world_pos).normalize()
+                };
+                assert!((input.view_dir - expected).magnitude() < 1e-4, "{input:?}");
+            }
+        }
+    }
+
+    /// Esfera con el sol clásico vista en perspectiva, con suavizado de bordes.
+    fn draw_sun<T: RenderTarget + ?Sized>(target: &mut T) {
+        let renderer = Renderer {
+            edge_aa: true,
+            ..Renderer::new(50, 38)
+        };
+        let view = nalgebra_glm::look_at(&Vec3::new(0.3, 0.4, 3.0), &Vec3::zeros(), &Vec3::y());
+        let projection = nalgebra_glm::perspective(50.0 / 38.0, 1.0, 0.1, 100.0);
+        renderer.render_mesh(
+            target,
+            &ObjMesh::create_sphere(1.0, 24, 32),
+            &crate::shaders::ClassicSunShader::default(),
+            &Mat4::identity(),
+            &view,
+            &projection,
+            1.5,
+        );
+    }
+
+    #[test]
+    fn banded_rasterization_matches_the_full_framebuffer() {
+        let mut full = Framebuffer::new(50, 38);
+        full.clear(Color::new(0, 0, 0));
+        // Sin la feature `parallel`, `render_mesh` siempre dibuja en un solo destino.
+        crate::framebuffer::set_clamp_stats_enabled(true);
+        draw_sun(&mut full);
+        crate::framebuffer::set_clamp_stats_enabled(false);
+
+        let mut banded = Framebuffer::new(50, 38);
+        banded.clear(Color::new(0, 0, 0));
+        for band in &mut banded.bands_mut(16) {
+            draw_sun(band);
+        }
+        assert!(full.zbuffer.iter().any(|d| d.is_finite()));
+        assert_eq!(banded.buffer, full.buffer);
+        assert!(
+            banded
+                .zbuffer
+                .iter()
+                .zip(&full.zbuffer)
+                .all(|(a, b)| a.to_bits() == b.to_bits())
+        );
+
+        // Con la feature `parallel` la esfera (más de 256 triángulos) usa las franjas por sí sola.
+        let mut automatic = Framebuffer::new(50, 38);
+        automatic.clear(Color::new(0, 0, 0));
+        draw_sun(&mut automatic);
+        assert_eq!(automatic.buffer, full.buffer);
+    }
 }
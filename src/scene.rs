@@ -0,0 +1,608 @@
+//! Escena renderizable y dibujo de un cuadro completo.
+//
+// Este archivo agrupa los objetos de la escena, la cámara orbital y el dibujo de un cuadro
+// en un framebuffer. No depende de raylib, de modo que el mismo código sirve para la
+// ventana interactiva y para renderizar sin interfaz (pruebas, grabación o imágenes fijas).
+
+use crate::camera;
+use crate::framebuffer::{Color, Framebuffer, RenderTarget};
+use crate::mesh::ObjMesh;
+use crate::peel::{self, DEFAULT_PEEL_PASSES};
+use crate::renderer::{BlendMode, DrawParams, Renderer};
+use crate::shaders::StarShader;
+use crate::skybox::Skybox;
+use crate::texture::{SampleFilter, Texture};
+use nalgebra_glm::{Mat4, Vec3, look_at, rotate};
+use std::rc::Rc;
+
+/// Planos de recorte cercano y lejano de la cámara.
+const NEAR_PLANE: f32 = 0.1;
+const FAR_PLANE: f32 = 100.0;
+
+/// Colores del degradado de fondo, más claro hacia abajo.
+const BACKGROUND_TOP: Color = Color {
+    r: 2,
+    g: 2,
+    b: 8,
+    a: 255,
+};
+const BACKGROUND_BOTTOM: Color = Color {
+    r: 12,
+    g: 10,
+    b: 32,
+    a: 255,
+};
+
+/// Color de los segmentos dibujados por `FrameOptions::show_normals`.
+const NORMAL_COLOR: Color = Color {
+    r: 90,
+    g: 160,
+    b: 255,
+    a: 255,
+};
+
+/// Longitud, en unidades del modelo, de los segmentos de normales.
+const NORMAL_LENGTH: f32 = 0.1;
+
+/// Color de las aristas cuando el wireframe se activa para toda la escena.
+pub const WIREFRAME_COLOR: Color = Color {
+    r: 80,
+    g: 255,
+    b: 160,
+    a: 255,
+};
+
+/// Estructura que representa un objeto renderizable con malla, shader y transformaciones.
+///
+/// La malla se comparte mediante `Rc` para que varios objetos (o cambios de shader/modelo)
+/// reutilicen los mismos datos sin clonarlos.
+pub struct RenderObject {
+    pub mesh: Rc<ObjMesh>,
+    pub shader: Box<dyn StarShader>,
+    pub position: Vec3,
+    pub scale: f32,
+    pub rotation_speed: f32,
+    pub rotation_axis: Vec3,
+    /// Nombre mostrado en la interfaz (normalmente el del shader).
+    pub name: &'static str,
+    /// Si está seleccionado, se dibujan sus aristas en `highlight_color` sobre la superficie.
+    pub selected: bool,
+    /// Color de las aristas del objeto seleccionado.
+    pub highlight_color: Color,
+    /// Combinación con el framebuffer. Los objetos translúcidos se dibujan después de
+    /// todos los opacos, para que la prueba de profundidad los oculte tras ellos.
+    pub blend_mode: BlendMode,
+    /// Si es `false`, [`render_frame`] omite el objeto sin quitarlo de la escena.
+    pub visible: bool,
+}
+
+impl RenderObject {
+    /// Crea un nuevo objeto renderizable.
+    pub fn new(mesh: Rc<ObjMesh>, shader: Box<dyn StarShader>, position: Vec3, scale: f32) -> Self {
+        RenderObject {
+            mesh,
+            shader,
+            position,
+            scale,
+            rotation_speed: 0.3,
+            rotation_axis: Vec3::new(0.0, 1.0, 0.0),
+            name: "",
+            selected: false,
+            highlight_color: Color::new(255, 220, 60),
+            blend_mode: BlendMode::Opaque,
+            visible: true,
+        }
+    }
+
+    /// Establece la velocidad de rotación en radianes por segundo.
+    pub fn with_rotation_speed(mut self, rotation_speed: f32) -> Self {
+        self.rotation_speed = rotation_speed;
+        self
+    }
+
+    /// Establece el eje de rotación (se normaliza; un eje nulo se ignora).
+    pub fn with_rotation_axis(mut self, rotation_axis: Vec3) -> Self {
+        if rotation_axis.magnitude_squared() > 0.0 {
+            self.rotation_axis = rotation_axis.normalize();
+        }
+        self
+    }
+
+    /// Establece la posición del objeto en espacio mundo.
+    pub fn with_position(mut self, position: Vec3) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Establece la escala uniforme del objeto.
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Establece la semilla del ruido procedural del shader ([`StarShader::set_seed`]);
+    /// objetos con el mismo shader y distinta semilla se ven diferentes.
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.shader.set_seed(seed);
+        self
+    }
+
+    /// Establece el nombre mostrado en la interfaz.
+    pub fn with_name(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// Establece la combinación del objeto con el framebuffer.
+    pub fn with_blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
+    /// Establece si el objeto se dibuja.
+    pub fn with_visible(mut self, visible: bool) -> Self {
+        self.visible = visible;
+        self
+    }
+
+    /// Calcula la matriz de modelo (transformación) animada por tiempo.
+    ///
+    /// La escala incluye el factor animado del shader ([`StarShader::scale_factor`]).
+    pub fn get_model_matrix(&self, time: f32) -> Mat4 {
+        let scale = self.scale * self.shader.scale_factor(time);
+        let mut transform = Mat4::identity();
+        transform = nalgebra_glm::translate(&transform, &self.position);
+        transform = rotate(&transform, time * self.rotation_speed, &self.rotation_axis);
+        transform = nalgebra_glm::scale(&transform, &Vec3::new(scale, scale, scale));
+        transform
+    }
+}
+
+/// Cámara de la escena, que mira desde `eye` hacia `target` con el eje Y hacia arriba.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SceneCamera {
+    /// Posición de la cámara.
+    pub eye: Vec3,
+    /// Punto enfocado.
+    pub target: Vec3,
+    /// Campo de visión vertical, en grados.
+    pub fov_y_deg: f32,
+    /// Usa una proyección ortográfica con el mismo encuadre que la perspectiva a la
+    /// distancia del punto enfocado.
+    pub orthographic: bool,
+}
+
+impl SceneCamera {
+    /// Cámara sobre el eje +Z que mira al origen desde `distance`.
+    pub fn orbit(distance: f32, fov_y_deg: f32, orthographic: bool) -> Self {
+        SceneCamera {
+            eye: Vec3::new(0.0, 0.0, distance),
+            target: Vec3::zeros(),
+            fov_y_deg,
+            orthographic,
+        }
+    }
+
+    /// Matriz de vista de la cámara.
+    pub fn view_matrix(&self) -> Mat4 {
+        look_at(&self.eye, &self.target, &Vec3::new(0.0, 1.0, 0.0))
+    }
+
+    /// Matriz de proyección (perspectiva u ortográfica) para la relación de aspecto dada.
+    ///
+    /// El campo de visión se ajusta a [`camera::MIN_FOV_DEG`, `camera::MAX_FOV_DEG`]; ver
+    /// [`camera::make_projection`].
+    pub fn projection_matrix(&self, aspect: f32) -> Mat4 {
+        if self.orthographic {
+            let fov_y = camera::clamp_fov_deg(self.fov_y_deg).to_radians();
+            let distance = (self.eye - self.target).magnitude();
+            camera::orthographic_framing(aspect, fov_y, distance, NEAR_PLANE, FAR_PLANE)
+        } else {
+            camera::make_projection(aspect, self.fov_y_deg, NEAR_PLANE, FAR_PLANE)
+        }
+    }
+}
+
+/// Opciones de depuración y fondo aplicadas al dibujar un cuadro.
+#[derive(Clone, Copy, Default)]
+pub struct FrameOptions<'a> {
+    /// Dibuja las aristas de todos los objetos en `WIREFRAME_COLOR`.
+    pub wireframe: bool,
+    /// Dibuja la normal de cada vértice como un segmento corto.
+    pub show_normals: bool,
+    /// Fondo de entorno dibujado detrás de la escena, si lo hay.
+    pub skybox: Option<&'a Skybox>,
+    /// Imagen de fondo que reemplaza al degradado, escalada para cubrir el cuadro.
+    pub backplate: Option<&'a Texture>,
+}
+
+/// Resumen de lo dibujado por [`render_frame`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    /// Objetos visibles enviados al renderizador.
+    pub drawn_objects: usize,
+    /// Objetos omitidos por estar ocultos (`RenderObject::visible` en `false`).
+    pub skipped_objects: usize,
+    /// Triángulos de las mallas dibujadas, antes del recorte y el descarte de caras.
+    pub triangles: usize,
+}
+
+/// Dibuja un cuadro completo de la escena en `framebuffer`.
+///
+/// Limpia el fondo con la imagen de fondo o, si no hay, con un degradado (y encima el
+/// skybox, si está activo) y renderiza cada objeto con su shader, animado al instante
+/// `time`: primero los opacos, después los de [`BlendMode::Alpha`] (separados en capas
+/// con [`peel::render_peeled`], hasta [`DEFAULT_PEEL_PASSES`]) y al final los aditivos,
+/// cada grupo en el orden de `scene`. Los objetos ocultos se omiten (y se cuentan en las
+/// estadísticas devueltas). Los objetos seleccionados muestran sus aristas en su color de
+/// resaltado. No aplica post-procesado.
+///
+/// # Argumentos
+/// * `renderer` - Renderizador con el modo de sombreado y demás opciones.
+/// * `framebuffer` - Destino del cuadro; sus dimensiones fijan la relación de aspecto.
+/// * `scene` - Objetos a dibujar, en orden.
+/// * `camera` - Cámara de la escena.
+/// * `time` - Tiempo de simulación, en segundos.
+/// * `options` - Opciones de depuración y fondo.
+pub fn render_frame(
+    renderer: &Renderer,
+    framebuffer: &mut Framebuffer,
+    scene: &[RenderObject],
+    camera: &SceneCamera,
+    time: f32,
+    options: &FrameOptions,
+) -> FrameStats {
+    let view_matrix = camera.view_matrix();
+    let aspect = framebuffer.width as f32 / framebuffer.height.max(1) as f32;
+    let projection_matrix = camera.projection_matrix(aspect);
+
+    match options.backplate {
+        Some(image) => framebuffer.clear_image(image, SampleFilter::Bilinear),
+        None => framebuffer.clear_gradient(BACKGROUND_TOP, BACKGROUND_BOTTOM),
+    }
+    if let Some(skybox) = options.skybox {
+        skybox.draw(framebuffer, &view_matrix, &projection_matrix);
+    }
+
+    let mut stats = FrameStats {
+        skipped_objects: scene.iter().filter(|o| !o.visible).count(),
+        ..FrameStats::default()
+    };
+    let visible = scene.iter().filter(|o| o.visible);
+    let opaque = visible
+        .clone()
+        .filter(|o| o.blend_mode == BlendMode::Opaque);
+    let peeled: Vec<&RenderObject> = visible
+        .clone()
+        .filter(|o| o.blend_mode == BlendMode::Alpha)
+        .collect();
+    let additive = visible.filter(|o| o.blend_mode == BlendMode::Additive);
+    for object in opaque
+        .clone()
+        .chain(peeled.iter().copied())
+        .chain(additive.clone())
+    {
+        stats.drawn_objects += 1;
+        stats.triangles += object.mesh.indices.len() / 3;
+    }
+
+    let draw = |target: &mut dyn RenderTarget, object: &RenderObject| {
+        draw_object(
+            renderer,
+            target,
+            object,
+            &view_matrix,
+            &projection_matrix,
+            time,
+            options,
+        )
+    };
+    for object in opaque {
+        draw(framebuffer, object);
+    }
+    if !peeled.is_empty() {
+        peel::render_peeled(framebuffer, DEFAULT_PEEL_PASSES, |layer| {
+            for object in &peeled {
+                draw(layer, object);
+            }
+        });
+    }
+    for object in additive {
+        draw(framebuffer, object);
+    }
+    stats
+}
+
+/// Dibuja un objeto de la escena (con sus aristas y normales, si corresponde) en `target`.
+fn draw_object(
+    renderer: &Renderer,
+    target: &mut dyn RenderTarget,
+    object: &RenderObject,
+    view_matrix: &Mat4,
+    projection_matrix: &Mat4,
+    time: f32,
+    options: &FrameOptions,
+) {
+    // Calcula la transformación del modelo animada.
+    let model_matrix = object.get_model_matrix(time);
+    let params = DrawParams::new(model_matrix, *view_matrix, *projection_matrix).with_time(time);
+    let renderer = &Renderer {
+        blend_mode: object.blend_mode,
+        ..*renderer
+    };
+
+    // El objeto seleccionado muestra sus aristas en su color de resaltado, encima
+    // del wireframe general si éste está activo.
+    let line_color = if object.selected {
+        Some(object.highlight_color)
+    } else if options.wireframe {
+        Some(WIREFRAME_COLOR)
+    } else {
+        None
+    };
+
+    // Renderiza la malla del objeto con su shader.
+    match line_color {
+        Some(line_color) => renderer.render_mesh_wireframe_overlay(
+            target,
+            &object.mesh,
+            object.shader.as_ref(),
+            &params.with_color(line_color),
+        ),
+        None => renderer.render_mesh(
+            target,
+            &object.mesh,
+            object.shader.as_ref(),
+            &model_matrix,
+            view_matrix,
+            projection_matrix,
+            time,
+        ),
+    }
+
+    if options.show_normals {
+        renderer.render_normals(
+            target,
+            &object.mesh,
+            &params.with_color(NORMAL_COLOR),
+            NORMAL_LENGTH,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shaders::{ClassicSunShader, FragmentInput};
+
+    /// Shader de color constante.
+    struct Solid(Color);
+
+    impl StarShader for Solid {
+        fn fragment(&self, _input: &FragmentInput) -> Color {
+            self.0
+        }
+    }
+
+    fn star(color: Color) -> RenderObject {
+        RenderObject::new(
+            Rc::new(ObjMesh::create_sphere(1.0, 12, 16)),
+            Box::new(Solid(color)),
+            Vec3::zeros(),
+            1.0,
+        )
+    }
+
+    #[test]
+    fn rotation_speed_changes_model_matrix() {
+        let slow = star(Color::new(255, 0, 0))
+            .with_position(Vec3::new(1.0, 0.0, 0.0))
+            .with_rotation_speed(0.3);
+        let fast = star(Color::new(255, 0, 0))
+            .with_position(Vec3::new(1.0, 0.0, 0.0))
+            .with_rotation_speed(1.2);
+        assert_ne!(slow.get_model_matrix(2.0), fast.get_model_matrix(2.0));
+        // En t = 0 la rotación es nula, sin importar la velocidad.
+        assert_eq!(slow.get_model_matrix(0.0), fast.get_model_matrix(0.0));
+    }
+
+    /// Color RGB del píxel `(x, y)`.
+    fn rgb(framebuffer: &Framebuffer, x: usize, y: usize) -> [u8; 3] {
+        let idx = (y * framebuffer.width + x) * 4;
+        [
+            framebuffer.buffer[idx],
+            framebuffer.buffer[idx + 1],
+            framebuffer.buffer[idx + 2],
+        ]
+    }
+
+    #[test]
+    fn render_frame_draws_scene_over_background_without_a_window() {
+        let (width, height) = (64, 48);
+        let renderer = Renderer::new(width, height);
+        let camera = SceneCamera::orbit(3.5, 60.0, false);
+        let red = Color::new(255, 0, 0);
+        let mut scene = vec![star(red)];
+
+        let mut framebuffer = Framebuffer::new(width, height);
+        render_frame(
+            &renderer,
+            &mut framebuffer,
+            &scene,
+            &camera,
+            0.0,
+            &FrameOptions::default(),
+        );
+        assert_eq!(rgb(&framebuffer, 32, 24), [255, 0, 0]);
+        assert_eq!(rgb(&framebuffer, 0, 0), [2, 2, 8], "degradado de fondo");
+
+        // El objeto seleccionado añade sus aristas en el color de resaltado.
+        scene[0].selected = true;
+        render_frame(
+            &renderer,
+            &mut framebuffer,
+            &scene,
+            &camera,
+            0.0,
+            &FrameOptions::default(),
+        );
+        let highlight = scene[0].highlight_color;
+        assert!(
+            framebuffer
+                .buffer
+                .chunks_exact(4)
+                .any(|p| p[..3] == [highlight.r, highlight.g, highlight.b])
+        );
+    }
+
+    #[test]
+    fn additive_objects_draw_after_opaque_ones() {
+        let renderer = Renderer::new(32, 32);
+        let camera = SceneCamera::orbit(3.5, 60.0, false);
+        // La cáscara aditiva va primero en la escena y por delante del núcleo opaco.
+        let shell = RenderObject::new(
+            Rc::new(ObjMesh::create_sphere(1.0, 12, 16)),
+            Box::new(Solid(Color::rgba(0, 0, 255, 255))),
+            Vec3::new(0.0, 0.0, 1.0),
+            0.5,
+        )
+        .with_blend_mode(BlendMode::Additive);
+        let scene = vec![shell, star(Color::new(255, 0, 0))];
+
+        let mut framebuffer = Framebuffer::new(32, 32);
+        render_frame(
+            &renderer,
+            &mut framebuffer,
+            &scene,
+            &camera,
+            0.0,
+            &FrameOptions::default(),
+        );
+        assert_eq!(rgb(&framebuffer, 16, 16), [255, 0, 255]);
+    }
+
+    #[test]
+    fn camera_projection_stays_finite_for_out_of_range_fov() {
+        for orthographic in [false, true] {
+            let zero = SceneCamera::orbit(5.0, 0.0, orthographic).projection_matrix(1.5);
+            let minimum =
+                SceneCamera::orbit(5.0, camera::MIN_FOV_DEG, orthographic).projection_matrix(1.5);
+            assert!(zero.iter().all(|v| v.is_finite()));
+            assert_eq!(zero, minimum, "un FOV de 0° se ajusta al mínimo");
+        }
+    }
+
+    /// Ancho en NDC de un segmento horizontal de ancho `width` centrado en `center`.
+    fn screen_width(camera: &SceneCamera, center: Vec3, width: f32) -> f32 {
+        let clip = camera.projection_matrix(1.5) * camera.view_matrix();
+        let ndc_x = |p: Vec3| {
+            let p = clip * p.push(1.0);
+            p.x / p.w
+        };
+        let half = Vec3::new(width * 0.5, 0.0, 0.0);
+        ndc_x(center + half) - ndc_x(center - half)
+    }
+
+    #[test]
+    fn orthographic_camera_ignores_depth_and_keeps_the_focused_framing() {
+        let perspective = SceneCamera::orbit(5.0, 60.0, false);
+        let orthographic = SceneCamera::orbit(5.0, 60.0, true);
+        let (near, far) = (Vec3::new(0.8, 0.0, 2.0), Vec3::new(-0.8, 0.0, -3.0));
+
+        // En ortográfica dos objetos iguales ocupan lo mismo a cualquier profundidad...
+        let width = screen_width(&orthographic, near, 1.0);
+        assert!((width - screen_width(&orthographic, far, 1.0)).abs() < 1e-5);
+        // ...mientras que en perspectiva el cercano se ve más grande.
+        assert!(screen_width(&perspective, near, 1.0) > screen_width(&perspective, far, 1.0) * 2.0);
+
+        // A la distancia del punto enfocado ambas proyecciones coinciden.
+        for center in [Vec3::zeros(), Vec3::new(1.2, -0.4, 0.0)] {
+            let ortho = screen_width(&orthographic, center, 1.0);
+            let persp = screen_width(&perspective, center, 1.0);
+            assert!((ortho - persp).abs() < 1e-5, "{ortho} vs {persp}");
+        }
+        assert!((width - screen_width(&perspective, Vec3::zeros(), 1.0)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn hidden_objects_are_skipped_and_counted() {
+        let renderer = Renderer::new(32, 32);
+        let camera = SceneCamera::orbit(3.5, 60.0, false);
+        // La esfera azul, oculta, queda por delante de la roja.
+        let front = star(Color::new(0, 0, 255))
+            .with_position(Vec3::new(0.0, 0.0, 1.0))
+            .with_visible(false);
+        let mut scene = vec![star(Color::new(255, 0, 0)), front];
+        let triangles = scene[0].mesh.indices.len() / 3;
+
+        let mut framebuffer = Framebuffer::new(32, 32);
+        let stats = render_frame(
+            &renderer,
+            &mut framebuffer,
+            &scene,
+            &camera,
+            0.0,
+            &FrameOptions::default(),
+        );
+        assert_eq!(rgb(&framebuffer, 16, 16), [255, 0, 0]);
+        assert_eq!(
+            stats,
+            FrameStats {
+                drawn_objects: 1,
+                skipped_objects: 1,
+                triangles,
+            }
+        );
+
+        scene[1].visible = true;
+        let stats = render_frame(
+            &renderer,
+            &mut framebuffer,
+            &scene,
+            &camera,
+            0.0,
+            &FrameOptions::default(),
+        );
+        assert_eq!(rgb(&framebuffer, 16, 16), [0, 0, 255]);
+        assert_eq!((stats.drawn_objects, stats.skipped_objects), (2, 0));
+        assert_eq!(stats.triangles, triangles * 2);
+    }
+
+    #[test]
+    fn seed_reseeds_the_object_shader() {
+        let camera = SceneCamera::orbit(3.5, 60.0, false);
+        let sun = || {
+            RenderObject::new(
+                Rc::new(ObjMesh::create_sphere(1.0, 12, 16)),
+                Box::new(ClassicSunShader::default()),
+                Vec3::zeros(),
+                1.0,
+            )
+        };
+        let frame = |object: RenderObject| {
+            let mut framebuffer = Framebuffer::new(32, 32);
+            render_frame(
+                &Renderer::new(32, 32),
+                &mut framebuffer,
+                &[object],
+                &camera,
+                1.0,
+                &FrameOptions::default(),
+            );
+            framebuffer.buffer
+        };
+
+        assert_eq!(
+            frame(sun().with_seed(0)),
+            frame(sun()),
+            "la semilla 0 no cambia nada"
+        );
+        let seeded = frame(sun().with_seed(5));
+        assert_ne!(seeded, frame(sun()));
+
+        // Cambiar la semilla de un objeto ya creado (tecla E) equivale a crearlo con ella.
+        let mut reseeded = sun();
+        reseeded.shader.set_seed(5);
+        assert_eq!(frame(reseeded), seeded);
+    }
+}
@@ -0,0 +1,226 @@
+//! Shader que funde dos shaders para transiciones suaves entre ellos.
+//
+// `BlendShader` evalúa ambos shaders internos y mezcla linealmente sus colores en punto
+// flotante antes de cuantizar, de modo que al animar `t` de 0 a 1 una estrella se
+// transforma en otra (por ejemplo, el sol clásico en una supernova). La etapa de vértice y
+// las sugerencias de encuadre se interpolan igual, para que la geometría desplazada y la
+// cámara acompañen la transición.
+
+use super::{FragmentInput, StarShader};
+use crate::framebuffer::Color;
+use crate::mesh::Vertex;
+use nalgebra_glm::Vec3;
+
+/// Shader que interpola entre `a` (con `t = 0`) y `b` (con `t = 1`).
+pub struct BlendShader {
+    /// Shader de origen, usado tal cual con `t <= 0`.
+    pub a: Box<dyn StarShader>,
+    /// Shader de destino, usado tal cual con `t >= 1`.
+    pub b: Box<dyn StarShader>,
+    /// Avance de la transición, en [0.0, 1.0].
+    pub t: f32,
+}
+
+impl BlendShader {
+    /// Crea la mezcla de `a` y `b` con el avance `t`, ajustado a [0.0, 1.0].
+    pub fn new(a: Box<dyn StarShader>, b: Box<dyn StarShader>, t: f32) -> Self {
+        BlendShader {
+            a,
+            b,
+            t: t.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Mezcla dos colores en punto flotante (incluida la opacidad).
+    fn mix(&self, a: Color, b: Color) -> Color {
+        let lerp = |x: f32, y: f32| x + (y - x) * self.t;
+        let rgb = a.to_vec3().lerp(&b.to_vec3(), self.t);
+        Color {
+            a: lerp(a.a as f32, b.a as f32).round() as u8,
+            ..Color::from_vec3(rgb)
+        }
+    }
+}
+
+impl StarShader for BlendShader {
+    fn fragment(&self, input: &FragmentInput) -> Color {
+        // En los extremos se delega directamente, sin pasar por la cuantización.
+        if self.t <= 0.0 {
+            return self.a.fragment(input);
+        }
+        if self.t >= 1.0 {
+            return self.b.fragment(input);
+        }
+        self.mix(self.a.fragment(input), self.b.fragment(input))
+    }
+
+    /// Si solo uno de los shaders descarta el fragmento, decide el de mayor peso: los
+    /// huecos de `a` se mantienen hasta la mitad de la transición y los de `b` desde ella.
+    fn fragment_or_discard(&self, input: &FragmentInput) -> Option<Color> {
+        if self.t <= 0.0 {
+            return self.a.fragment_or_discard(input);
+        }
+        if self.t >= 1.0 {
+            return self.b.fragment_or_discard(input);
+        }
+        match (
+            self.a.fragment_or_discard(input),
+            self.b.fragment_or_discard(input),
+        ) {
+            (Some(a), Some(b)) => Some(self.mix(a, b)),
+            (Some(a), None) if self.t < 0.5 => Some(a),
+            (None, Some(b)) if self.t >= 0.5 => Some(b),
+            _ => None,
+        }
+    }
+
+    fn vertex(&self, vertex: &Vertex, time: f32) -> Vertex {
+        if self.t <= 0.0 {
+            return self.a.vertex(vertex, time);
+        }
+        if self.t >= 1.0 {
+            return self.b.vertex(vertex, time);
+        }
+        let (a, b) = (self.a.vertex(vertex, time), self.b.vertex(vertex, time));
+        let normal = a.normal.lerp(&b.normal, self.t);
+        Vertex {
+            position: a.position.lerp(&b.position, self.t),
+            normal: if normal.magnitude_squared() > 1e-12 {
+                normal.normalize()
+            } else {
+                Vec3::new(0.0, 1.0, 0.0)
+            },
+            ..a
+        }
+    }
+
+    fn suggested_camera_distance(&self) -> f32 {
+        let (a, b) = (
+            self.a.suggested_camera_distance(),
+            self.b.suggested_camera_distance(),
+        );
+        a + (b - a) * self.t
+    }
+
+    fn scale_factor(&self, time: f32) -> f32 {
+        let (a, b) = (self.a.scale_factor(time), self.b.scale_factor(time));
+        a + (b - a) * self.t
+    }
+
+    /// Usa la misma semilla en ambos shaders, para que la transición no cambie de patrón.
+    fn set_seed(&mut self, seed: u32) {
+        self.a.set_seed(seed);
+        self.b.set_seed(seed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra_glm::{Vec2, Vec4};
+
+    /// Shader de color y distancia de cámara constantes que puede descartar todo.
+    struct Solid {
+        color: Color,
+        distance: f32,
+        discard: bool,
+    }
+
+    impl StarShader for Solid {
+        fn fragment(&self, _input: &FragmentInput) -> Color {
+            self.color
+        }
+
+        fn fragment_or_discard(&self, input: &FragmentInput) -> Option<Color> {
+            (!self.discard).then(|| self.fragment(input))
+        }
+
+        fn suggested_camera_distance(&self) -> f32 {
+            self.distance
+        }
+    }
+
+    fn solid(color: Color, distance: f32, discard: bool) -> Box<dyn StarShader> {
+        Box::new(Solid {
+            color,
+            distance,
+            discard,
+        })
+    }
+
+    fn input() -> FragmentInput {
+        FragmentInput {
+            world_pos: Vec3::z(),
+            object_pos: Vec3::z(),
+            normal: Vec3::z(),
+            view_dir: Vec3::z(),
+            tangent: Vec4::zeros(),
+            time: 0.0,
+            view_depth: 0.0,
+            dpdx: Vec3::zeros(),
+            dpdy: Vec3::zeros(),
+            object_dpdx: Vec3::zeros(),
+            object_dpdy: Vec3::zeros(),
+            uv: Vec2::zeros(),
+            duvdx: Vec2::zeros(),
+            duvdy: Vec2::zeros(),
+            barycentric: Vec3::new(1.0, 0.0, 0.0),
+            triangle_id: 0,
+        }
+    }
+
+    fn blend(t: f32) -> BlendShader {
+        BlendShader::new(
+            solid(Color::rgba(0, 100, 200, 255), 4.0, false),
+            solid(Color::rgba(200, 100, 0, 55), 8.0, false),
+            t,
+        )
+    }
+
+    /// Shader que pinta su semilla en el canal rojo.
+    struct SeedColor(u32);
+
+    impl StarShader for SeedColor {
+        fn fragment(&self, _input: &FragmentInput) -> Color {
+            Color::new(self.0 as u8, 0, 0)
+        }
+
+        fn set_seed(&mut self, seed: u32) {
+            self.0 = seed;
+        }
+    }
+
+    #[test]
+    fn seed_reaches_both_shaders() {
+        let mut shader =
+            BlendShader::new(Box::new(SeedColor(1)), Box::new(SeedColor(2)), 0.0).with_seed(9);
+        assert_eq!(shader.fragment(&input()).r, 9);
+        shader.t = 1.0;
+        assert_eq!(shader.fragment(&input()).r, 9);
+    }
+
+    #[test]
+    fn blend_interpolates_color_and_framing() {
+        let rgba = |c: Color| [c.r, c.g, c.b, c.a];
+        assert_eq!(rgba(blend(-1.0).fragment(&input())), [0, 100, 200, 255]);
+        assert_eq!(rgba(blend(2.0).fragment(&input())), [200, 100, 0, 55]);
+        assert_eq!(rgba(blend(0.5).fragment(&input())), [100, 100, 100, 155]);
+        assert_eq!(rgba(blend(0.25).fragment(&input())), [50, 100, 150, 205]);
+
+        assert_eq!(blend(-1.0).t, 0.0, "t se ajusta a [0, 1]");
+        assert_eq!(blend(0.0).suggested_camera_distance(), 4.0);
+        assert_eq!(blend(0.75).suggested_camera_distance(), 7.0);
+    }
+
+    #[test]
+    fn discard_follows_the_heavier_shader() {
+        let red = Color::new(255, 0, 0);
+        let holes_in_a = |t| BlendShader::new(solid(red, 1.0, true), solid(red, 1.0, false), t);
+        assert!(holes_in_a(0.25).fragment_or_discard(&input()).is_none());
+        assert!(holes_in_a(0.5).fragment_or_discard(&input()).is_some());
+
+        let holes_in_b = |t| BlendShader::new(solid(red, 1.0, false), solid(red, 1.0, true), t);
+        assert!(holes_in_b(0.25).fragment_or_discard(&input()).is_some());
+        assert!(holes_in_b(0.5).fragment_or_discard(&input()).is_none());
+    }
+}
@@ -0,0 +1,197 @@
+//! Envoltorio que perfora la superficie de otro shader descartando fragmentos.
+//
+// `CutoutShader` evalúa un ruido Perlin sobre la posición del fragmento y descarta los que
+// quedan por debajo de un umbral; el resto se delega sin cambios al shader interno. Así
+// cualquier estrella puede convertirse en una variante perforada (o en bandas, con una
+// frecuencia anisótropa) sin reescribir su shader. Los huecos dejan ver lo que haya detrás,
+// incluida la cara interior de la propia esfera.
+
+use super::noise::{PermutationTable, perlin_noise};
+use super::{FragmentInput, StarShader};
+use crate::framebuffer::Color;
+use crate::mesh::Vertex;
+use nalgebra_glm::Vec3;
+
+/// Shader que descarta los fragmentos donde el ruido cae por debajo de `threshold`.
+pub struct CutoutShader {
+    /// Shader que colorea los fragmentos que no se descartan.
+    pub inner: Box<dyn StarShader>,
+    /// Umbral del ruido, en [0.0, 1.0]: 0.0 no perfora nada y 1.0 lo descarta todo.
+    pub threshold: f32,
+    /// Escala por eje de la posición antes de muestrear el ruido. Valores altos dan huecos
+    /// más pequeños; una escala dominante en un eje produce anillos perpendiculares a él.
+    pub frequency: Vec3,
+    /// Tabla de ruido que decide dónde caen los huecos.
+    pub noise: PermutationTable,
+}
+
+impl CutoutShader {
+    /// Envuelve `inner` con huecos de ruido isótropo de frecuencia 4.
+    pub fn new(inner: Box<dyn StarShader>, threshold: f32) -> Self {
+        CutoutShader {
+            inner,
+            threshold,
+            frequency: Vec3::new(4.0, 4.0, 4.0),
+            noise: PermutationTable::default(),
+        }
+    }
+
+    /// Valor del ruido de perforación en una posición, en [0.0, 1.0].
+    pub fn mask(&self, position: &Vec3) -> f32 {
+        let p = position.component_mul(&self.frequency);
+        perlin_noise(p.x, p.y, p.z, &self.noise)
+    }
+}
+
+impl StarShader for CutoutShader {
+    /// Color del shader interno; solo [`StarShader::fragment_or_discard`] perfora.
+    fn fragment(&self, input: &FragmentInput) -> Color {
+        self.inner.fragment(input)
+    }
+
+    fn fragment_or_discard(&self, input: &FragmentInput) -> Option<Color> {
+        if self.mask(&input.world_pos) < self.threshold {
+            return None;
+        }
+        self.inner.fragment_or_discard(input)
+    }
+
+    fn vertex(&self, vertex: &Vertex, time: f32) -> Vertex {
+        self.inner.vertex(vertex, time)
+    }
+
+    fn suggested_camera_distance(&self) -> f32 {
+        self.inner.suggested_camera_distance()
+    }
+
+    fn scale_factor(&self, time: f32) -> f32 {
+        self.inner.scale_factor(time)
+    }
+
+    /// Cambia la semilla de los huecos y la del shader interno.
+    fn set_seed(&mut self, seed: u32) {
+        self.noise = PermutationTable::new(seed);
+        self.inner.set_seed(seed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::Framebuffer;
+    use crate::mesh::ObjMesh;
+    use crate::renderer::Renderer;
+    use crate::shaders::ClassicSunShader;
+    use nalgebra_glm::{Mat4, Vec2, Vec4};
+
+    /// Shader de color constante.
+    struct Solid(Color);
+
+    impl StarShader for Solid {
+        fn fragment(&self, _input: &FragmentInput) -> Color {
+            self.0
+        }
+    }
+
+    /// Cuadrado que cubre toda la pantalla a la profundidad `z` (en NDC).
+    fn screen_quad(z: f32) -> ObjMesh {
+        ObjMesh {
+            vertices: [[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]]
+                .iter()
+                .map(|&[x, y]| Vertex {
+                    position: Vec3::new(x, y, z),
+                    normal: Vec3::z(),
+                    uv: Vec2::zeros(),
+                    tangent: Vec4::zeros(),
+                })
+                .collect(),
+            indices: vec![0, 1, 2, 0, 2, 3],
+            submeshes: Vec::new(),
+        }
+    }
+
+    /// Dibuja un fondo azul lejano y, delante, `shader` sobre un cuadrado a pantalla completa.
+    fn render_over_blue(shader: &dyn StarShader) -> Framebuffer {
+        let renderer = Renderer::new(24, 24).with_culling(false);
+        let mut framebuffer = Framebuffer::new(24, 24);
+        let identity = Mat4::identity();
+        let blue = Solid(Color::new(0, 0, 255));
+        for (mesh, shader) in [
+            (screen_quad(0.5), &blue as &dyn StarShader),
+            (screen_quad(0.0), shader),
+        ] {
+            renderer.render_mesh(
+                &mut framebuffer,
+                &mesh,
+                shader,
+                &identity,
+                &identity,
+                &identity,
+                0.0,
+            );
+        }
+        framebuffer
+    }
+
+    #[test]
+    fn discarded_fragments_reveal_what_is_behind() {
+        let red = || Box::new(Solid(Color::new(255, 0, 0)));
+        let count = |framebuffer: &Framebuffer, rgb: [u8; 3]| {
+            framebuffer
+                .buffer
+                .chunks_exact(4)
+                .filter(|p| p[..3] == rgb)
+                .count()
+        };
+
+        let solid = render_over_blue(&CutoutShader::new(red(), 0.0));
+        assert_eq!(count(&solid, [255, 0, 0]), 24 * 24);
+        let empty = render_over_blue(&CutoutShader::new(red(), 1.01));
+        assert_eq!(count(&empty, [0, 0, 255]), 24 * 24);
+        assert!(
+            empty.zbuffer.iter().all(|&d| (d - 0.5).abs() < 1e-5),
+            "sin escribir profundidad"
+        );
+
+        // Con un umbral intermedio, cada píxel muestra el frente o el fondo según la máscara.
+        let cutout = CutoutShader::new(red(), 0.5);
+        let framebuffer = render_over_blue(&cutout);
+        for y in 0..24 {
+            for x in 0..24 {
+                let position = Vec3::new(
+                    (x as f32 + 0.5) / 12.0 - 1.0,
+                    1.0 - (y as f32 + 0.5) / 12.0,
+                    0.0,
+                );
+                // Cerca del umbral, el redondeo de la interpolación puede decidir.
+                let mask = cutout.mask(&position);
+                if (mask - 0.5).abs() < 0.01 {
+                    continue;
+                }
+                let expected = if mask < 0.5 { 0 } else { 255 };
+                assert_eq!(framebuffer.buffer[(y * 24 + x) * 4], expected, "({x}, {y})");
+            }
+        }
+        assert!(count(&framebuffer, [255, 0, 0]) > 0 && count(&framebuffer, [0, 0, 255]) > 0);
+    }
+
+    #[test]
+    fn seed_moves_the_holes_and_reaches_the_inner_shader() {
+        let red = || Box::new(Solid(Color::new(255, 0, 0)));
+        let plain = render_over_blue(&CutoutShader::new(red(), 0.5));
+        let seeded = CutoutShader::new(red(), 0.5).with_seed(5);
+        assert_eq!(seeded.noise.seed(), 5);
+        assert_ne!(render_over_blue(&seeded).buffer, plain.buffer);
+
+        // Con umbral 0 no hay huecos: solo se ve el shader interno, con la misma semilla.
+        let sun = CutoutShader::new(Box::new(ClassicSunShader::default()), 0.0).with_seed(5);
+        assert_eq!(
+            render_over_blue(&sun).buffer,
+            render_over_blue(&ClassicSunShader::default().with_seed(5)).buffer
+        );
+        assert_ne!(
+            render_over_blue(&sun).buffer,
+            render_over_blue(&ClassicSunShader::default()).buffer
+        );
+    }
+}
@@ -3,26 +3,175 @@
 //! Módulo principal de shaders que organiza y re-exporta todos los componentes.
 
 use crate::framebuffer::Color;
-use nalgebra_glm::Vec3;
+use crate::mesh::Vertex;
+use nalgebra_glm::{Vec2, Vec3, Vec4};
 
 // Submódulos
-pub mod noise;      // Funciones de generación de ruido
-pub mod utils;      // Utilidades para shaders
+pub mod blend; // Transiciones entre dos shaders
+pub mod cutout; // Superficies perforadas por descarte de fragmentos
+pub mod noise; // Funciones de generación de ruido
+pub mod normal_map; // Relieve mediante mapas de normales
+pub mod phong; // Material iluminado Blinn-Phong
+pub mod utils; // Utilidades para shaders
 pub mod star_types; // Implementaciones de shaders de estrellas
 
+/// Datos de entrada de un fragmento, generados por el rasterizador.
+#[derive(Debug, Clone, Copy)]
+pub struct FragmentInput {
+    /// La posición del fragmento en espacio mundo.
+    pub world_pos: Vec3,
+    /// La posición del fragmento en espacio del objeto (antes de la matriz de modelo).
+    ///
+    /// Gira y se escala junto con el objeto, así que los patrones muestreados con ella
+    /// quedan fijos sobre la superficie en lugar de deslizarse cuando el objeto rota.
+    pub object_pos: Vec3,
+    /// La normal de la superficie en esa posición.
+    pub normal: Vec3,
+    /// Dirección normalizada desde el fragmento hacia la cámara, en espacio mundo. Con
+    /// proyección ortográfica es la misma en todos los fragmentos.
+    pub view_dir: Vec3,
+    /// Tangente interpolada en espacio mundo (`xyz`, sin normalizar) y orientación de la
+    /// bitangente (`w`). Junto con `normal` forma la base TBN del mapeo de normales.
+    pub tangent: Vec4,
+    /// El tiempo actual de la animación, para efectos dinámicos.
+    pub time: f32,
+    /// Variación aproximada de `world_pos` al avanzar un píxel en X (equivalente a `dFdx`).
+    pub dpdx: Vec3,
+    /// Variación aproximada de `world_pos` al avanzar un píxel en Y (equivalente a `dFdy`).
+    pub dpdy: Vec3,
+    /// Variación aproximada de `object_pos` al avanzar un píxel en X.
+    pub object_dpdx: Vec3,
+    /// Variación aproximada de `object_pos` al avanzar un píxel en Y.
+    pub object_dpdy: Vec3,
+    /// Coordenadas de textura interpoladas.
+    pub uv: Vec2,
+    /// Variación de `uv` al avanzar un píxel en X, usada para elegir el nivel de mipmap.
+    pub duvdx: Vec2,
+    /// Variación de `uv` al avanzar un píxel en Y, usada para elegir el nivel de mipmap.
+    pub duvdy: Vec2,
+    /// Profundidad lineal en espacio de vista: distancia desde la cámara a lo largo de su
+    /// eje. A diferencia de la Z en NDC, crece de forma proporcional a la distancia, por lo
+    /// que sirve para desvanecer efectos según lo lejos que esté la superficie.
+    pub view_depth: f32,
+    /// Coordenadas baricéntricas del fragmento respecto a los vértices del triángulo.
+    ///
+    /// Son las de pantalla, sin corrección de perspectiva (a diferencia del resto de
+    /// atributos), para que las distancias a las aristas se midan en píxeles.
+    ///
+    /// Cerca de una arista, la menor de las tres se aproxima a 0; útil para dibujar
+    /// wireframe directamente en el shader.
+    pub barycentric: Vec3,
+    /// Índice del triángulo dentro de la malla que se está dibujando.
+    pub triangle_id: u32,
+}
+
+impl FragmentInput {
+    /// Ancho del filtro de la posición en pantalla (equivalente a `fwidth`).
+    ///
+    /// Indica cuánto cambia la posición entre píxeles vecinos; sirve para suavizar
+    /// umbrales de patrones procedurales y evitar aliasing.
+    #[inline]
+    pub fn position_width(&self) -> f32 {
+        self.dpdx.magnitude() + self.dpdy.magnitude()
+    }
+
+    /// Como [`FragmentInput::position_width`], pero medido en espacio del objeto: no
+    /// depende de la posición ni de la escala del objeto en la escena.
+    #[inline]
+    pub fn object_position_width(&self) -> f32 {
+        self.object_dpdx.magnitude() + self.object_dpdy.magnitude()
+    }
+}
+
+/// Distancia de cámara por defecto de [`StarShader::suggested_camera_distance`].
+pub const DEFAULT_CAMERA_DISTANCE: f32 = 3.5;
+
 // Re-exportar el trait principal
-pub trait StarShader {
+//
+// Los shaders deben ser `Send + Sync` para poder evaluarse desde varios hilos.
+//
+// Contrato: `fragment` y `vertex` son funciones puras de sus argumentos. El tiempo llega
+// siempre como parámetro (`FragmentInput::time`); un shader no debe leer el reloj del
+// sistema ni guardar estado entre llamadas. Así, renderizar dos veces con el mismo `time`
+// produce framebuffers idénticos byte a byte, lo que permite comparar contra imágenes
+// de referencia.
+pub trait StarShader: Send + Sync {
     /// Calcula el color de un fragmento en una posición específica de la superficie.
     ///
     /// # Arguments
-    /// * `pos` - La posición del fragmento en el espacio del objeto.
-    /// * `normal` - La normal de la superficie en esa posición.
-    /// * `time` - El tiempo actual de la animación, para efectos dinámicos.
+    /// * `input` - Posición, normal, tiempo y derivadas en pantalla del fragmento.
     ///
     /// # Returns
     /// Devuelve el `Color` calculado para el fragmento.
-    fn fragment(&self, pos: &Vec3, normal: &Vec3, time: f32) -> Color;
+    fn fragment(&self, input: &FragmentInput) -> Color;
+
+    /// Etapa de fragmento con descarte: `None` deja el píxel intacto, sin escribir color
+    /// ni profundidad (como `discard` en GLSL).
+    ///
+    /// Es la que invoca el rasterizador. Por defecto nunca descarta y devuelve
+    /// [`StarShader::fragment`]; los envoltorios deben reenviarla a su shader interno para
+    /// conservar los descartes que éste haga.
+    fn fragment_or_discard(&self, input: &FragmentInput) -> Option<Color> {
+        Some(self.fragment(input))
+    }
+
+    /// Etapa de vértice opcional, aplicada en espacio del objeto antes de transformar.
+    ///
+    /// Permite desplazar la geometría (y ajustar su normal) en función del tiempo.
+    /// Por defecto devuelve el vértice sin cambios.
+    ///
+    /// # Arguments
+    /// * `vertex` - El vértice original de la malla.
+    /// * `time` - El tiempo actual de la animación.
+    ///
+    /// # Returns
+    /// El vértice, posiblemente desplazado.
+    fn vertex(&self, vertex: &Vertex, _time: f32) -> Vertex {
+        *vertex
+    }
+
+    /// Distancia de cámara con la que el shader se aprecia mejor, para la estrella de
+    /// escala 1.5 de la escena principal.
+    ///
+    /// Los shaders que desplazan la geometría o cuyo brillo se extiende más allá de la
+    /// esfera la aumentan para no salirse del encuadre. Por defecto devuelve
+    /// [`DEFAULT_CAMERA_DISTANCE`].
+    fn suggested_camera_distance(&self) -> f32 {
+        DEFAULT_CAMERA_DISTANCE
+    }
+
+    /// Factor animado que el objeto aplica sobre su propia escala en el instante `time`.
+    ///
+    /// A diferencia de la etapa de vértice, cambia la matriz de modelo, así que escala
+    /// también las normales dibujadas y el resaltado del objeto. Por defecto devuelve 1.0.
+    fn scale_factor(&self, _time: f32) -> f32 {
+        1.0
+    }
+
+    /// Cambia la semilla del ruido procedural: semillas distintas dan variantes distintas
+    /// del mismo shader y la semilla 0 conserva su patrón original.
+    ///
+    /// Por defecto no hace nada (shaders sin ruido); los envoltorios deben reenviarla a sus
+    /// shaders internos.
+    fn set_seed(&mut self, _seed: u32) {}
+
+    /// Variante de [`StarShader::set_seed`] que consume y devuelve el shader.
+    fn with_seed(mut self, seed: u32) -> Self
+    where
+        Self: Sized,
+    {
+        self.set_seed(seed);
+        self
+    }
 }
 
 // Re-exportar los shaders para facilitar su uso
-pub use star_types::{ClassicSunShader, PlasmaStarShader, PulsarShader, SupernovaShader};
\ No newline at end of file
+pub use blend::BlendShader;
+pub use cutout::CutoutShader;
+pub use noise::PermutationTable;
+pub use normal_map::NormalMapShader;
+pub use phong::PhongShader;
+pub use star_types::{
+    ClassicSunShader, DEFAULT_OCTAVES, MAX_OCTAVES, MIN_OCTAVES, PlasmaStarShader,
+    ProminenceSunShader, PulsarShader, SHOCKWAVE_PERIOD, ShockwaveShader, SupernovaShader,
+};
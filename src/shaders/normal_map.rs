@@ -0,0 +1,184 @@
+//! Envoltorio que añade relieve fino a otro shader mediante un mapa de normales.
+//
+// El mapa guarda normales en espacio tangente codificadas como color (`n * 0.5 + 0.5`). Cada
+// fragmento la decodifica, la lleva a espacio mundo con la base TBN interpolada (tangente,
+// bitangente y normal) y delega en el shader interno con la normal perturbada, de modo que
+// la iluminación y los términos dependientes de la vista reaccionan al relieve sin añadir
+// geometría.
+
+use super::{FragmentInput, StarShader};
+use crate::framebuffer::Color;
+use crate::mesh::Vertex;
+use crate::texture::Texture;
+use nalgebra_glm::{Vec3, Vec4};
+
+/// Shader que perturba la normal de otro con un mapa de normales en espacio tangente.
+pub struct NormalMapShader<'a> {
+    inner: &'a dyn StarShader,
+    normal_map: &'a Texture,
+    /// Intensidad del relieve: 0 conserva la normal original y 1 aplica el mapa tal cual.
+    pub strength: f32,
+    /// Repeticiones del mapa sobre las UV de la malla, para relieve más fino que la textura.
+    pub uv_scale: f32,
+}
+
+impl<'a> NormalMapShader<'a> {
+    /// Envuelve `inner` aplicando `normal_map` con intensidad 1 y sin repetición.
+    pub fn new(inner: &'a dyn StarShader, normal_map: &'a Texture) -> Self {
+        NormalMapShader {
+            inner,
+            normal_map,
+            strength: 1.0,
+            uv_scale: 1.0,
+        }
+    }
+}
+
+impl NormalMapShader<'_> {
+    /// Sustituye la normal del fragmento por la perturbada según el mapa.
+    fn perturb_input(&self, input: &FragmentInput) -> FragmentInput {
+        let sample = self.normal_map.sample_trilinear(
+            &(input.uv * self.uv_scale),
+            &(input.duvdx * self.uv_scale),
+            &(input.duvdy * self.uv_scale),
+        );
+        FragmentInput {
+            normal: perturb_normal(&input.normal, &input.tangent, &sample, self.strength),
+            ..*input
+        }
+    }
+}
+
+impl StarShader for NormalMapShader<'_> {
+    fn fragment(&self, input: &FragmentInput) -> Color {
+        self.inner.fragment(&self.perturb_input(input))
+    }
+
+    fn fragment_or_discard(&self, input: &FragmentInput) -> Option<Color> {
+        self.inner.fragment_or_discard(&self.perturb_input(input))
+    }
+
+    fn vertex(&self, vertex: &Vertex, time: f32) -> Vertex {
+        self.inner.vertex(vertex, time)
+    }
+
+    fn suggested_camera_distance(&self) -> f32 {
+        self.inner.suggested_camera_distance()
+    }
+
+    fn scale_factor(&self, time: f32) -> f32 {
+        self.inner.scale_factor(time)
+    }
+}
+
+/// Lleva a espacio mundo la normal codificada en `sample` usando la base TBN.
+///
+/// La tangente se ortogonaliza respecto a `normal` (la interpolación la desvía) y la
+/// bitangente es `cross(normal, tangente) * tangent.w`. Si la tangente es nula o paralela
+/// a la normal, o el texel no codifica una dirección válida, devuelve `normal` sin cambios.
+///
+/// # Argumentos
+/// * `normal` - Normal de la superficie en espacio mundo (unitaria).
+/// * `tangent` - Tangente en espacio mundo (`xyz`) y orientación de la bitangente (`w`).
+/// * `sample` - Color del mapa de normales en [0, 1]; (0.5, 0.5, 1.0) es una superficie plana.
+/// * `strength` - Escala de las componentes tangentes de la normal del mapa.
+pub fn perturb_normal(normal: &Vec3, tangent: &Vec4, sample: &Vec3, strength: f32) -> Vec3 {
+    let tangent_dir = tangent.xyz() - normal * normal.dot(&tangent.xyz());
+    if tangent_dir.magnitude_squared() < 1e-12 {
+        return *normal;
+    }
+    let t = tangent_dir.normalize();
+    let b = normal.cross(&t) * if tangent.w < 0.0 { -1.0 } else { 1.0 };
+
+    let local = sample * 2.0 - Vec3::new(1.0, 1.0, 1.0);
+    let local = Vec3::new(local.x * strength, local.y * strength, local.z);
+    let world = t * local.x + b * local.y + normal * local.z;
+    if world.iter().all(|c| c.is_finite()) && world.magnitude_squared() > 1e-12 {
+        world.normalize()
+    } else {
+        *normal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::Framebuffer;
+    use crate::mesh::ObjMesh;
+    use crate::renderer::Renderer;
+    use nalgebra_glm::{Mat4, Vec2};
+    use std::sync::Mutex;
+
+    #[test]
+    fn perturb_normal_uses_the_tbn_basis() {
+        let normal = Vec3::z();
+        let tangent = Vec4::new(1.0, 0.0, 0.0, 1.0);
+        let flat = Vec3::new(0.5, 0.5, 1.0);
+        assert!((perturb_normal(&normal, &tangent, &flat, 1.0) - normal).magnitude() < 1e-6);
+
+        // Un texel inclinado hacia +u gira la normal hacia la tangente.
+        let tilted = Vec3::new(1.0, 0.5, 0.5);
+        assert!((perturb_normal(&normal, &tangent, &tilted, 1.0) - Vec3::x()).magnitude() < 1e-6);
+        assert_eq!(perturb_normal(&normal, &tangent, &tilted, 0.0), normal);
+
+        // Hacia +v sigue a la bitangente, que cambia de signo con `w`.
+        let up = Vec3::new(0.5, 1.0, 0.5);
+        assert!((perturb_normal(&normal, &tangent, &up, 1.0) - Vec3::y()).magnitude() < 1e-6);
+        let mirrored = Vec4::new(1.0, 0.0, 0.0, -1.0);
+        assert!((perturb_normal(&normal, &mirrored, &up, 1.0) + Vec3::y()).magnitude() < 1e-6);
+
+        // Sin tangente útil la normal no cambia.
+        let parallel = Vec4::new(0.0, 0.0, 2.0, 1.0);
+        assert_eq!(perturb_normal(&normal, &parallel, &tilted, 1.0), normal);
+    }
+
+    /// Shader que registra la normal de cada fragmento.
+    #[derive(Default)]
+    struct NormalRecorder(Mutex<Vec<Vec3>>);
+
+    impl StarShader for NormalRecorder {
+        fn fragment(&self, input: &FragmentInput) -> Color {
+            self.0.lock().unwrap().push(input.normal);
+            Color::new(255, 255, 255)
+        }
+    }
+
+    #[test]
+    fn rendered_fragments_receive_the_mapped_normal() {
+        let mut mesh = ObjMesh {
+            vertices: [[-0.8, -0.8], [0.8, -0.8], [0.0, 0.8]]
+                .iter()
+                .map(|&[x, y]| Vertex {
+                    position: Vec3::new(x, y, 0.0),
+                    normal: Vec3::z(),
+                    uv: Vec2::new(x, y),
+                    tangent: Vec4::zeros(),
+                })
+                .collect(),
+            indices: vec![0, 1, 2],
+            submeshes: Vec::new(),
+        };
+        mesh.compute_tangents();
+
+        // Mapa uniforme que inclina todas las normales hacia +u.
+        let map = Texture::from_fn(4, 4, |_, _| Vec3::new(1.0, 0.5, 0.5));
+        let recorder = NormalRecorder::default();
+        let shader = NormalMapShader::new(&recorder, &map);
+        let renderer = Renderer::new(32, 32).with_culling(false);
+        let mut framebuffer = Framebuffer::new(32, 32);
+        let identity = Mat4::identity();
+        renderer.render_mesh(
+            &mut framebuffer,
+            &mesh,
+            &shader,
+            &identity,
+            &identity,
+            &identity,
+            0.0,
+        );
+
+        let normals = recorder.0.lock().unwrap();
+        assert!(normals.len() > 10);
+        assert!(normals.iter().all(|n| (n - Vec3::x()).magnitude() < 1e-4));
+    }
+}
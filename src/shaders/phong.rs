@@ -0,0 +1,108 @@
+//! `shaders/phong.rs`
+//!
+//! Material iluminado convencional (Blinn-Phong) para geometría que acompaña a las
+//! estrellas, como planetas o asteroides.
+
+use crate::framebuffer::Color;
+use nalgebra_glm::Vec3;
+
+use super::{FragmentInput, StarShader};
+
+/// Shader de iluminación Blinn-Phong con una luz direccional.
+///
+/// A diferencia de los shaders de estrellas, no emite luz propia: el color resulta de
+/// sumar los términos ambiental, difuso y especular de la luz sobre el `albedo`.
+pub struct PhongShader {
+    /// Dirección hacia la que viaja la luz (desde la fuente hacia la escena).
+    pub light_dir: Vec3,
+    /// Color (e intensidad) de la luz.
+    pub light_color: Vec3,
+    /// Color base de la superficie.
+    pub albedo: Vec3,
+    /// Coeficiente ambiental.
+    pub ambient: f32,
+    /// Coeficiente difuso.
+    pub diffuse: f32,
+    /// Coeficiente especular.
+    pub specular: f32,
+    /// Exponente del brillo especular; valores altos dan reflejos más pequeños.
+    pub shininess: f32,
+}
+
+impl PhongShader {
+    /// Crea un material blanco iluminado por una luz direccional.
+    ///
+    /// # Arguments
+    /// * `light_dir` - Dirección en la que viaja la luz (se normaliza).
+    /// * `light_color` - Color de la luz.
+    pub fn new(light_dir: Vec3, light_color: Vec3) -> Self {
+        PhongShader {
+            light_dir: light_dir.normalize(),
+            light_color,
+            albedo: Vec3::new(1.0, 1.0, 1.0),
+            ambient: 0.1,
+            diffuse: 0.8,
+            specular: 0.4,
+            shininess: 32.0,
+        }
+    }
+
+    /// Calcula el color iluminado en [0.0, 1.0] para una normal y una dirección hacia la
+    /// cámara en espacio mundo.
+    pub fn shade(&self, normal: &Vec3, view_dir: &Vec3) -> Vec3 {
+        let normal = normal.normalize();
+        let to_light = -self.light_dir.normalize();
+
+        let diffuse = normal.dot(&to_light).max(0.0);
+
+        // Blinn-Phong: el reflejo depende del vector medio entre luz y vista.
+        let specular = if diffuse > 0.0 {
+            let half = (to_light + view_dir).normalize();
+            normal.dot(&half).max(0.0).powf(self.shininess)
+        } else {
+            0.0
+        };
+
+        let lit = self.albedo * (self.ambient + self.diffuse * diffuse)
+            + Vec3::new(1.0, 1.0, 1.0) * (self.specular * specular);
+        lit.component_mul(&self.light_color)
+    }
+}
+
+impl StarShader for PhongShader {
+    fn fragment(&self, input: &FragmentInput) -> Color {
+        Color::from_vec3(self.shade(&input.normal, &input.view_dir))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phong_lights_facing_side_and_leaves_ambient_on_the_back() {
+        // Luz que viaja hacia -Z: ilumina las caras que miran a +Z.
+        let shader = PhongShader::new(Vec3::new(0.0, 0.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+        let to_camera = Vec3::z();
+
+        let back = shader.shade(&Vec3::new(0.0, 0.0, -1.0), &to_camera);
+        assert!((back - Vec3::repeat(shader.ambient)).magnitude() < 1e-6);
+
+        let front = shader.shade(&Vec3::new(0.0, 0.0, 1.0), &to_camera);
+        let expected = shader.ambient + shader.diffuse + shader.specular;
+        assert!((front - Vec3::repeat(expected)).magnitude() < 1e-5);
+
+        let grazing = shader.shade(&Vec3::new(1.0, 0.0, 1.0), &to_camera);
+        assert!(grazing.x > back.x && grazing.x < front.x);
+    }
+
+    #[test]
+    fn specular_highlight_follows_the_view_direction() {
+        let shader = PhongShader::new(Vec3::new(0.0, 0.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+        let normal = Vec3::z();
+        let aligned = shader.shade(&normal, &Vec3::z());
+        let grazing = shader.shade(&normal, &Vec3::x());
+        // El difuso no depende de la vista: mirando de lado solo se pierde el brillo.
+        assert!((aligned.x - grazing.x - shader.specular).abs() < 0.01);
+    }
+}
@@ -3,11 +3,25 @@
 //! Implementaciones de diferentes tipos de shaders de estrellas.
 
 use crate::framebuffer::Color;
+use crate::mesh::Vertex;
 use nalgebra_glm::Vec3;
-
-use super::noise::{cellular_noise, perlin_noise, simplex_noise, turbulence};
-use super::utils::{hue_to_rgb, mix_vec3, pulse_pow, smoothstep, temperature_to_color};
-use super::StarShader;
+use std::f32::consts::PI;
+
+use super::noise::{
+    PermutationTable, cellular_noise, perlin_noise, simplex_noise, turbulence_normalized,
+};
+use super::utils::{
+    fresnel, hue_to_rgb, limb_darkening, mix_vec3, pulse, pulse_pow, smoothstep,
+    temperature_to_color,
+};
+use super::{DEFAULT_CAMERA_DISTANCE, FragmentInput, StarShader};
+
+/// Número mínimo de octavas de turbulencia configurable en los shaders.
+pub const MIN_OCTAVES: i32 = 1;
+/// Número máximo de octavas de turbulencia configurable en los shaders.
+pub const MAX_OCTAVES: i32 = 8;
+/// Octavas de turbulencia por defecto.
+pub const DEFAULT_OCTAVES: i32 = 5;
 
 // ===================================================================================
 // ========== SHADER 1: SOL CLÁSICO (PERLIN NOISE) ==========
@@ -17,45 +31,114 @@ use super::StarShader;
 ///
 /// Características:
 /// - Usa ruido Perlin multi-octava para granulación solar
-/// - Manchas solares oscuras dinámicas
+/// - Manchas solares oscuras fijas sobre la superficie
+/// - Patrones muestreados en espacio del objeto: giran rígidamente con la estrella
 /// - Sistema de temperatura con gradiente realista
 /// - Pulsación suave sincronizada
+/// - Oscurecimiento del limbo en el borde del disco
 /// - Corona brillante en los bordes (efecto Fresnel)
-pub struct ClassicSunShader;
+pub struct ClassicSunShader {
+    /// Octavas de la turbulencia de la granulación, en [`MIN_OCTAVES`, `MAX_OCTAVES`].
+    pub octaves: i32,
+    /// Exponente del Fresnel de la corona: valores altos la concentran en una banda
+    /// delgada y nítida junto al borde; valores bajos la extienden hacia el centro.
+    pub rim_power: f32,
+    /// Intensidad de la corona en el borde del disco.
+    pub rim_intensity: f32,
+    /// Color de la corona (lineal, antes del tinte cálido final).
+    pub rim_color: Vec3,
+    /// Tabla de ruido de la granulación y las manchas; otra semilla da otro sol.
+    pub noise: PermutationTable,
+}
+
+impl ClassicSunShader {
+    /// Crea el shader con el número de octavas dado, ajustado al rango permitido, y la
+    /// corona por defecto.
+    pub fn new(octaves: i32) -> Self {
+        ClassicSunShader {
+            octaves: octaves.clamp(MIN_OCTAVES, MAX_OCTAVES),
+            rim_power: 3.0,
+            rim_intensity: 0.5,
+            rim_color: Vec3::new(1.0, 0.8, 0.3),
+            noise: PermutationTable::default(),
+        }
+    }
+}
+
+/// Ciclos por segundo del relevo entre las dos capas de la granulación.
+const GRANULATION_CHURN_RATE: f32 = 0.05;
+
+impl ClassicSunShader {
+    /// Granulación en la dirección `dir` (espacio del objeto), en [0.0, 1.0].
+    ///
+    /// Mezcla dos capas de turbulencia que se desplazan lentamente, desfasadas medio ciclo,
+    /// con pesos `sin²` y `cos²` (que suman 1). Cada capa salta al origen de su recorrido
+    /// justo cuando su peso es nulo, así que el patrón bulle en su sitio sin avanzar en
+    /// ninguna dirección y solo se mueve sobre la esfera si la estrella rota.
+    fn granulation(&self, dir: &Vec3, time: f32) -> f32 {
+        let phase = (time * GRANULATION_CHURN_RATE).rem_euclid(1.0);
+        let layer = |offset: f32| {
+            let progress = (phase + offset).fract();
+            let drift = Vec3::new(2.0, 1.0, 0.0) * (progress - 0.5);
+            let weight = (PI * progress).sin().powi(2);
+            turbulence_normalized(dir * 3.0 + drift, self.octaves, 0, &self.noise) * weight
+        };
+        layer(0.0) + layer(0.5)
+    }
+}
+
+impl Default for ClassicSunShader {
+    fn default() -> Self {
+        Self::new(DEFAULT_OCTAVES)
+    }
+}
 
 impl StarShader for ClassicSunShader {
-    fn fragment(&self, pos: &Vec3, normal: &Vec3, time: f32) -> Color {
-        let normalized_pos = pos.normalize();
+    fn fragment(&self, input: &FragmentInput) -> Color {
+        let (normal, time) = (&input.normal, input.time);
+        // El ruido se muestrea en espacio del objeto para que gire con la estrella.
+        let normalized_pos = input.object_pos.normalize();
 
-        // Turbulencia base animada
-        let turb_offset = Vec3::new(time * 0.1, time * 0.05, 0.0);
-        let turbulence_val = turbulence(normalized_pos * 3.0 + turb_offset, 5, 0);
+        // Granulación que bulle lentamente en su sitio
+        let turbulence_val = self.granulation(&normalized_pos, time);
 
         // Manchas solares (áreas más frías y oscuras)
         let spot_noise = perlin_noise(
-            normalized_pos.x * 8.0 + time * 0.2,
+            normalized_pos.x * 8.0,
             normalized_pos.y * 8.0,
             normalized_pos.z * 8.0,
+            &self.noise,
         );
-        let solar_spots = smoothstep(0.65, 0.75, spot_noise);
+        // Ensancha el borde de las manchas según el cambio del ruido entre píxeles
+        // vecinos, evitando bordes dentados cuando la estrella se ve pequeña. Ancho y
+        // radio se miden en espacio del objeto, sin importar dónde esté ni su escala.
+        let spot_aa =
+            (input.object_position_width() * 8.0 / input.object_pos.magnitude()).min(0.25);
+        let solar_spots = smoothstep(0.65 - spot_aa, 0.75 + spot_aa, spot_noise);
 
         // Temperatura base con variación
-        let base_temp = 0.7 + turbulence_val * 0.15 - solar_spots * 0.3;
+        let base_temp = 0.7 + turbulence_val * 0.29 - solar_spots * 0.3;
         let temp_color = temperature_to_color(base_temp);
 
-        // Emisión de luz pulsante
-        let pulse = (time * 2.0).sin() * 0.05 + 0.95;
-        let emission = temp_color * (1.5 + turbulence_val * 0.5) * pulse;
+        // Emisión de luz pulsante, atenuada hacia el limbo del disco
+        let view_dir = input.view_dir;
+        let pulse = pulse(time, 2.0, 0.9, 1.0);
+        let limb = limb_darkening(normal, &view_dir, 0.6);
+        let emission = temp_color * (1.5 + turbulence_val * 0.97) * pulse * limb;
 
-        // Efecto de corona brillante (Fresnel)
-        let view_dir = Vec3::new(0.0, 0.0, 1.0);
-        let fresnel = (1.0 - normal.dot(&view_dir).abs()).powf(3.0);
-        let corona = Vec3::new(1.0, 0.8, 0.3) * fresnel * 0.5;
+        // Efecto de corona brillante (Fresnel) sobre el disco oscurecido
+        let fresnel = fresnel(&view_dir, normal, self.rim_power);
+        let corona = self.rim_color * fresnel * self.rim_intensity;
 
         // Combina emisión y corona con tinte cálido
         let final_color = (emission + corona).component_mul(&Vec3::new(1.2, 1.0, 0.8));
         Color::from_vec3(final_color)
     }
+
+    /// Cambia la semilla del ruido de la superficie.
+    fn set_seed(&mut self, seed: u32) {
+        self.noise = PermutationTable::new(seed);
+    }
 }
 
 // ===================================================================================
@@ -70,10 +153,15 @@ impl StarShader for ClassicSunShader {
 /// - Bandas magnéticas animadas
 /// - Jets de energía en los polos
 /// - Colores azul-púrpura de alta energía
-pub struct PulsarShader;
+#[derive(Default)]
+pub struct PulsarShader {
+    /// Tabla de ruido del patrón rotatorio.
+    pub noise: PermutationTable,
+}
 
 impl StarShader for PulsarShader {
-    fn fragment(&self, pos: &Vec3, _normal: &Vec3, time: f32) -> Color {
+    fn fragment(&self, input: &FragmentInput) -> Color {
+        let (pos, time) = (&input.world_pos, input.time);
         let normalized_pos = pos.normalize();
 
         // Pulsación principal con curva exponencial
@@ -85,7 +173,12 @@ impl StarShader for PulsarShader {
         let rot_z = normalized_pos.x * angle.sin() + normalized_pos.z * angle.cos();
 
         // Patrón base usando Simplex Noise
-        let pattern = simplex_noise(rot_x * 5.0, normalized_pos.y * 5.0, rot_z * 5.0);
+        let pattern = simplex_noise(
+            rot_x * 5.0,
+            normalized_pos.y * 5.0,
+            rot_z * 5.0,
+            &self.noise,
+        );
 
         // Bandas de energía verticales
         let bands = (normalized_pos.y * 10.0 + time * 2.0).sin() * 0.5 + 0.5;
@@ -107,6 +200,16 @@ impl StarShader for PulsarShader {
         let final_color = emission + pole_burst;
         Color::from_vec3(final_color)
     }
+
+    /// Una estrella de neutrones es compacta y sin desplazamiento: se ve mejor de cerca.
+    fn suggested_camera_distance(&self) -> f32 {
+        3.0
+    }
+
+    /// Cambia la semilla del ruido del patrón.
+    fn set_seed(&mut self, seed: u32) {
+        self.noise = PermutationTable::new(seed);
+    }
 }
 
 // ===================================================================================
@@ -121,10 +224,15 @@ impl StarShader for PulsarShader {
 /// - Color iridiscente que cambia con el tiempo
 /// - Bordes eléctricos pulsantes
 /// - Múltiples capas de turbulencia
-pub struct PlasmaStarShader;
+#[derive(Default)]
+pub struct PlasmaStarShader {
+    /// Tabla de ruido de los vórtices y los filamentos.
+    pub noise: PermutationTable,
+}
 
 impl StarShader for PlasmaStarShader {
-    fn fragment(&self, pos: &Vec3, normal: &Vec3, time: f32) -> Color {
+    fn fragment(&self, input: &FragmentInput) -> Color {
+        let (pos, normal, time) = (&input.world_pos, &input.normal, input.time);
         let normalized_pos = pos.normalize();
 
         // Dos capas de vórtices con diferente escala y velocidad
@@ -132,12 +240,14 @@ impl StarShader for PlasmaStarShader {
             normalized_pos.x * 4.0 + time * 0.3,
             normalized_pos.y * 4.0,
             normalized_pos.z * 4.0 + time * 0.2,
+            &self.noise,
         );
 
         let vortex2 = simplex_noise(
             normalized_pos.x * 6.0 - time * 0.4,
             normalized_pos.y * 6.0 + time * 0.1,
             normalized_pos.z * 6.0,
+            &self.noise,
         );
 
         let plasma_pattern = (vortex1 + vortex2 * 0.5) / 1.5;
@@ -147,6 +257,7 @@ impl StarShader for PlasmaStarShader {
             normalized_pos.x * 10.0,
             normalized_pos.y * 10.0 + time * 2.0,
             normalized_pos.z * 10.0,
+            &self.noise,
         );
         let filament_boost = smoothstep(0.6, 0.8, filaments) * 1.5;
 
@@ -158,13 +269,18 @@ impl StarShader for PlasmaStarShader {
         let emission = plasma_color * (2.0 + plasma_pattern + filament_boost);
 
         // Borde eléctrico parpadeante
-        let view_dir = Vec3::new(0.0, 0.0, 1.0);
-        let edge = (1.0 - normal.dot(&view_dir).abs()).powf(2.0);
+        let view_dir = input.view_dir;
+        let edge = fresnel(&view_dir, normal, 2.0);
         let electric_edge = Vec3::new(0.5, 1.0, 1.0) * edge * (1.0 + (time * 10.0).sin() * 0.3);
 
         let final_color = emission + electric_edge;
         Color::from_vec3(final_color)
     }
+
+    /// Cambia la semilla del ruido del plasma.
+    fn set_seed(&mut self, seed: u32) {
+        self.noise = PermutationTable::new(seed);
+    }
 }
 
 // ===================================================================================
@@ -179,33 +295,63 @@ impl StarShader for PlasmaStarShader {
 /// - Núcleo denso, capa explosiva y fragmentos externos
 /// - Flares extremos con distorsión visual
 /// - Picos de energía radiales
-pub struct SupernovaShader;
+pub struct SupernovaShader {
+    /// Octavas de la turbulencia de la explosión, en [`MIN_OCTAVES`, `MAX_OCTAVES`].
+    /// El núcleo usa una octava menos, ya que su detalle queda cubierto por las capas externas.
+    pub octaves: i32,
+    /// Tabla de ruido compartida por las tres capas de la explosión.
+    pub noise: PermutationTable,
+}
+
+impl SupernovaShader {
+    /// Crea el shader con el número de octavas dado, ajustado al rango permitido.
+    pub fn new(octaves: i32) -> Self {
+        SupernovaShader {
+            octaves: octaves.clamp(MIN_OCTAVES, MAX_OCTAVES),
+            noise: PermutationTable::default(),
+        }
+    }
+}
+
+impl Default for SupernovaShader {
+    fn default() -> Self {
+        Self::new(DEFAULT_OCTAVES)
+    }
+}
 
 impl StarShader for SupernovaShader {
-    fn fragment(&self, pos: &Vec3, normal: &Vec3, time: f32) -> Color {
+    fn fragment(&self, input: &FragmentInput) -> Color {
+        let (pos, normal, time) = (&input.world_pos, &input.normal, input.time);
         let normalized_pos = pos.normalize();
 
         // Simulación de expansión de onda de choque
-        let expansion = (time * 0.5).sin() * 0.2 + 1.0;
+        let expansion = pulse(time, 0.5, 0.8, 1.2);
         let expanded_pos = normalized_pos * expansion;
 
         // CAPA 1: Núcleo interno denso (Perlin)
-        let core = turbulence(expanded_pos * 5.0, 4, 0);
-        let core_color = temperature_to_color(0.9 + core * 0.1);
+        let core = turbulence_normalized(
+            expanded_pos * 5.0,
+            (self.octaves - 1).max(MIN_OCTAVES),
+            0,
+            &self.noise,
+        );
+        let core_color = temperature_to_color(0.9 + core * 0.19);
 
         // CAPA 2: Explosión intermedia caótica (Simplex)
-        let explosion = turbulence(
+        let explosion = turbulence_normalized(
             expanded_pos * 3.0 + Vec3::new(time * 0.2, time * 0.15, time * 0.1),
-            5,
+            self.octaves,
             1,
+            &self.noise,
         );
-        let explosion_color = Vec3::new(1.0, 0.6, 0.2) * (1.0 + explosion * 2.0);
+        let explosion_color = Vec3::new(1.0, 0.6, 0.2) * (1.0 + explosion * 3.9);
 
         // CAPA 3: Fragmentos externos eyectados (Cellular)
         let fragments = cellular_noise(
             expanded_pos.x * 8.0 + time * 0.3,
             expanded_pos.y * 8.0,
             expanded_pos.z * 8.0 + time * 0.4,
+            &self.noise,
         );
         let fragment_color = Vec3::new(1.0, 0.3, 0.1) * fragments * 1.5;
 
@@ -215,9 +361,9 @@ impl StarShader for SupernovaShader {
         let final_blend = mix_vec3(mid_color, fragment_color, fragments * 0.4);
 
         // Flare extremo en los bordes
-        let view_dir = Vec3::new(0.0, 0.0, 1.0);
-        let flare = (1.0 - normal.dot(&view_dir).abs()).powf(1.5);
-        let flare_intensity = (time * 4.0).sin() * 0.3 + 0.7;
+        let view_dir = input.view_dir;
+        let flare = fresnel(&view_dir, normal, 1.5);
+        let flare_intensity = pulse(time, 4.0, 0.4, 1.0);
         let flare_color = Vec3::new(1.0, 0.9, 0.5) * flare * flare_intensity * 3.0;
 
         // Picos de energía radiales
@@ -227,4 +373,568 @@ impl StarShader for SupernovaShader {
         let final_color = final_blend + flare_color + burst_color;
         Color::from_vec3(final_color)
     }
-}
\ No newline at end of file
+
+    /// La expansión de la onda de choque y los flares desbordan el encuadre por defecto.
+    fn suggested_camera_distance(&self) -> f32 {
+        5.0
+    }
+
+    /// Cambia la semilla del ruido de la explosión.
+    fn set_seed(&mut self, seed: u32) {
+        self.noise = PermutationTable::new(seed);
+    }
+}
+
+/// Duración por defecto, en segundos, de cada expansión de [`ShockwaveShader`].
+pub const SHOCKWAVE_PERIOD: f32 = 3.0;
+
+/// Cáscara translúcida de la onda de choque que acompaña a la supernova.
+///
+/// Pensada para una esfera que envuelve al núcleo y se dibuja con mezcla aditiva: en cada
+/// periodo crece desde la escala del objeto hasta `max_scale` veces ese tamaño (rápido al
+/// principio y frenando al final) mientras su opacidad se desvanece. El borde de la
+/// silueta brilla más, como una capa de gas vista de canto.
+pub struct ShockwaveShader {
+    /// Duración de cada expansión, en segundos; al terminar vuelve a empezar.
+    pub period: f32,
+    /// Factor de escala alcanzado al final de la expansión.
+    pub max_scale: f32,
+    /// Color de la cáscara.
+    pub color: Vec3,
+    /// Tabla de ruido de los filamentos del gas.
+    pub noise: PermutationTable,
+}
+
+impl Default for ShockwaveShader {
+    fn default() -> Self {
+        ShockwaveShader {
+            period: SHOCKWAVE_PERIOD,
+            max_scale: 2.2,
+            color: Vec3::new(1.0, 0.55, 0.25),
+            noise: PermutationTable::default(),
+        }
+    }
+}
+
+impl ShockwaveShader {
+    /// Avance de la expansión actual en [0.0, 1.0).
+    pub fn progress(&self, time: f32) -> f32 {
+        (time / self.period.max(1e-3)).rem_euclid(1.0)
+    }
+
+    /// Opacidad de la cáscara: 1.0 al comenzar la expansión y 0.0 al terminarla.
+    pub fn alpha(&self, time: f32) -> f32 {
+        let remaining = 1.0 - self.progress(time);
+        remaining * remaining
+    }
+}
+
+impl StarShader for ShockwaveShader {
+    fn fragment(&self, input: &FragmentInput) -> Color {
+        let (pos, normal, time) = (&input.world_pos, &input.normal, input.time);
+
+        // Borde brillante: la cáscara se atraviesa en diagonal cerca de la silueta.
+        let view_dir = input.view_dir;
+        let rim = fresnel(&view_dir, normal, 3.0);
+
+        // Filamentos del gas eyectado, arrastrados por la expansión.
+        let filaments = simplex_noise(
+            pos.x * 3.0 + time * 0.5,
+            pos.y * 3.0,
+            pos.z * 3.0 - time * 0.5,
+            &self.noise,
+        ) * 0.5
+            + 0.5;
+
+        let intensity = (0.08 + 0.6 * rim) * (0.5 + 0.5 * filaments);
+        let alpha = self.alpha(time) * intensity.min(1.0);
+        Color {
+            a: (alpha * 255.0).round() as u8,
+            ..Color::from_vec3(self.color)
+        }
+    }
+
+    /// Escala de la expansión actual, de 1.0 a `max_scale` con frenado (salida cuadrática).
+    fn scale_factor(&self, time: f32) -> f32 {
+        let progress = self.progress(time);
+        let eased = 1.0 - (1.0 - progress) * (1.0 - progress);
+        1.0 + (self.max_scale - 1.0) * eased
+    }
+
+    /// Cambia la semilla del ruido de los filamentos.
+    fn set_seed(&mut self, seed: u32) {
+        self.noise = PermutationTable::new(seed);
+    }
+}
+
+// ===================================================================================
+// ========== SHADER 5: SOL CON PROTUBERANCIAS (VÉRTICE + FRAGMENTO) ==========
+// ===================================================================================
+
+/// Umbral de ruido a partir del cual la superficie se eleva formando protuberancias.
+const PROMINENCE_THRESHOLD: f32 = 0.66;
+/// Altura máxima de una protuberancia, relativa al radio de la estrella.
+const PROMINENCE_HEIGHT: f32 = 0.35;
+
+/// Calcula la altura de las protuberancias solares en una dirección de la superficie.
+///
+/// Usa ruido Perlin de baja frecuencia animado en el tiempo; solo donde supera el umbral
+/// la superficie se eleva, formando arcos que nacen y desaparecen.
+///
+/// # Arguments
+/// * `dir` - Dirección normalizada desde el centro de la estrella
+/// * `time` - Tiempo actual
+/// * `threshold` - Valor de ruido por debajo del cual no hay desplazamiento
+/// * `noise` - Tabla de ruido que decide dónde aparecen las protuberancias
+///
+/// # Returns
+/// Desplazamiento radial relativo: 0.0 bajo el umbral y positivo por encima
+pub fn prominence_height(dir: &Vec3, time: f32, threshold: f32, noise: &PermutationTable) -> f32 {
+    let noise = perlin_noise(
+        dir.x * 2.5 + time * 0.15,
+        dir.y * 2.5 + (time * 0.4).sin() * 0.3,
+        dir.z * 2.5 - time * 0.1,
+        noise,
+    );
+    let excess = ((noise - threshold) / (1.0 - threshold)).max(0.0);
+    excess * excess * PROMINENCE_HEIGHT
+}
+
+/// Un sol con protuberancias que se arquean fuera de la superficie.
+///
+/// Características:
+/// - Etapa de vértice que desplaza la malla hacia afuera donde el ruido supera un umbral
+/// - Normales recalculadas a partir del campo de alturas para mantener la iluminación
+/// - Superficie del `ClassicSunShader` con las protuberancias teñidas de rojo intenso
+#[derive(Default)]
+pub struct ProminenceSunShader {
+    /// Superficie base sobre la que se dibujan las protuberancias.
+    pub surface: ClassicSunShader,
+}
+
+impl ProminenceSunShader {
+    /// Crea el shader con el número de octavas dado para la superficie base.
+    pub fn new(octaves: i32) -> Self {
+        ProminenceSunShader {
+            surface: ClassicSunShader::new(octaves),
+        }
+    }
+}
+
+impl StarShader for ProminenceSunShader {
+    fn fragment(&self, input: &FragmentInput) -> Color {
+        let base = self.surface.fragment(input);
+
+        // Las zonas elevadas brillan con el tono rojizo del hidrógeno ionizado. La altura
+        // se evalúa en espacio del objeto, igual que en la etapa de vértice, para que el
+        // tinte siga a la geometría elevada cuando la estrella rota o se desplaza.
+        let dir = input.object_pos.normalize();
+        let height = prominence_height(&dir, input.time, PROMINENCE_THRESHOLD, &self.surface.noise)
+            / PROMINENCE_HEIGHT;
+        let base_color = base.to_vec3();
+        let flare_color = Vec3::new(1.0, 0.35, 0.15) * 1.6;
+
+        Color::from_vec3(mix_vec3(
+            base_color,
+            flare_color,
+            smoothstep(0.0, 0.6, height),
+        ))
+    }
+
+    fn vertex(&self, vertex: &Vertex, time: f32) -> Vertex {
+        let radius = vertex.position.magnitude();
+        if radius < 1e-6 {
+            return *vertex;
+        }
+        let dir = vertex.position / radius;
+        let height = prominence_height(&dir, time, PROMINENCE_THRESHOLD, &self.surface.noise);
+        if height <= 0.0 {
+            return *vertex;
+        }
+
+        // Base ortonormal tangente a la esfera en este punto.
+        let helper = if dir.y.abs() < 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let tangent = helper.cross(&dir).normalize();
+        let bitangent = dir.cross(&tangent);
+
+        // Gradiente del campo de alturas por diferencias finitas sobre la superficie.
+        let eps = 0.01;
+        let h_t = prominence_height(
+            &(dir + tangent * eps).normalize(),
+            time,
+            PROMINENCE_THRESHOLD,
+            &self.surface.noise,
+        );
+        let h_b = prominence_height(
+            &(dir + bitangent * eps).normalize(),
+            time,
+            PROMINENCE_THRESHOLD,
+            &self.surface.noise,
+        );
+        let slope_t = (h_t - height) / eps;
+        let slope_b = (h_b - height) / eps;
+
+        // La normal se inclina en contra de la pendiente del desplazamiento.
+        let normal = (dir * (1.0 + height) - tangent * slope_t - bitangent * slope_b).normalize();
+
+        Vertex {
+            position: vertex.position * (1.0 + height),
+            normal,
+            ..*vertex
+        }
+    }
+
+    /// Deja margen para las protuberancias, que elevan la superficie hasta un
+    /// `PROMINENCE_HEIGHT` del radio.
+    fn suggested_camera_distance(&self) -> f32 {
+        DEFAULT_CAMERA_DISTANCE * (1.0 + PROMINENCE_HEIGHT * 0.5)
+    }
+
+    /// Cambia la semilla del ruido de la superficie, que también sitúa las protuberancias.
+    fn set_seed(&mut self, seed: u32) {
+        self.surface.set_seed(seed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra_glm::{Mat4, Vec2, Vec4};
+    use std::f32::consts::PI;
+
+    /// Direcciones repartidas sobre la esfera unitaria (espiral de Fibonacci).
+    fn sphere_directions(count: usize) -> Vec<Vec3> {
+        (0..count)
+            .map(|i| {
+                let y = 1.0 - 2.0 * (i as f32 + 0.5) / count as f32;
+                let angle = i as f32 * PI * (3.0 - 5f32.sqrt());
+                let r = (1.0 - y * y).sqrt();
+                Vec3::new(r * angle.cos(), y, r * angle.sin())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn prominences_rise_only_above_threshold() {
+        let noise = PermutationTable::default();
+        let dirs = sphere_directions(800);
+        let heights: Vec<f32> = dirs
+            .iter()
+            .map(|d| prominence_height(d, 1.0, PROMINENCE_THRESHOLD, &noise))
+            .collect();
+        assert!(heights.iter().all(|&h| h >= 0.0));
+        assert!(heights.contains(&0.0));
+        assert!(heights.iter().any(|&h| h > 0.0));
+
+        // Un umbral más bajo solo puede elevar más la superficie; con 1.0 nada supera el umbral.
+        for (dir, &height) in dirs.iter().zip(&heights) {
+            assert!(prominence_height(dir, 1.0, 0.3, &noise) >= height);
+            assert_eq!(prominence_height(dir, 1.0, 1.0, &noise), 0.0);
+        }
+    }
+
+    /// Fragmento sobre la esfera unitaria en la dirección `dir`, sin derivadas.
+    fn fragment_at(dir: Vec3, time: f32) -> FragmentInput {
+        FragmentInput {
+            world_pos: dir,
+            object_pos: dir,
+            normal: dir,
+            view_dir: Vec3::z(),
+            tangent: Vec4::zeros(),
+            time,
+            view_depth: 0.0,
+            dpdx: Vec3::zeros(),
+            dpdy: Vec3::zeros(),
+            object_dpdx: Vec3::zeros(),
+            object_dpdy: Vec3::zeros(),
+            uv: Vec2::zeros(),
+            duvdx: Vec2::zeros(),
+            duvdy: Vec2::zeros(),
+            barycentric: Vec3::new(1.0, 0.0, 0.0),
+            triangle_id: 0,
+        }
+    }
+
+    /// Componentes de un color, para compararlos.
+    fn rgb(color: Color) -> [u8; 3] {
+        [color.r, color.g, color.b]
+    }
+
+    #[test]
+    fn octaves_are_clamped_and_change_the_surface() {
+        assert_eq!(ClassicSunShader::new(0).octaves, MIN_OCTAVES);
+        assert_eq!(SupernovaShader::new(99).octaves, MAX_OCTAVES);
+        assert_eq!(ClassicSunShader::default().octaves, DEFAULT_OCTAVES);
+        assert_eq!(ProminenceSunShader::new(3).surface.octaves, 3);
+
+        let coarse = ClassicSunShader::new(MIN_OCTAVES);
+        let fine = ClassicSunShader::new(MAX_OCTAVES);
+        let differing = sphere_directions(200)
+            .into_iter()
+            .filter(|&d| {
+                let input = fragment_at(d, 1.0);
+                rgb(coarse.fragment(&input)) != rgb(fine.fragment(&input))
+            })
+            .count();
+        assert!(
+            differing > 100,
+            "solo {differing} de 200 fragmentos cambian"
+        );
+    }
+
+    #[test]
+    fn suggested_camera_distance_frames_each_shader() {
+        assert_eq!(
+            ClassicSunShader::default().suggested_camera_distance(),
+            DEFAULT_CAMERA_DISTANCE
+        );
+        assert!(PulsarShader::default().suggested_camera_distance() < DEFAULT_CAMERA_DISTANCE);
+        assert!(SupernovaShader::default().suggested_camera_distance() > DEFAULT_CAMERA_DISTANCE);
+        assert!(
+            ProminenceSunShader::default().suggested_camera_distance() > DEFAULT_CAMERA_DISTANCE,
+            "las protuberancias necesitan margen"
+        );
+    }
+
+    #[test]
+    fn classic_sun_rim_parameters_only_affect_the_limb() {
+        let center = fragment_at(Vec3::z(), 0.5);
+        let limb = fragment_at(Vec3::new(1.0, 0.0, 0.1).normalize(), 0.5);
+
+        let mut sun = ClassicSunShader::default();
+        let (center_default, limb_default) = (sun.fragment(&center), sun.fragment(&limb));
+
+        sun.rim_intensity = 0.0;
+        let limb_off = sun.fragment(&limb);
+        assert!(
+            limb_off.g < limb_default.g,
+            "sin corona el borde se oscurece"
+        );
+        assert_eq!(rgb(sun.fragment(&center)), rgb(center_default));
+
+        sun.rim_intensity = 1.0;
+        sun.rim_color = Vec3::new(0.0, 0.0, 1.0);
+        let blue = sun.fragment(&limb);
+        assert_eq!(blue.g, limb_off.g);
+        assert!(blue.b > limb_off.b);
+
+        // Un exponente mayor estrecha la corona: a media inclinación aporta menos.
+        let mid = fragment_at(Vec3::new(1.0, 0.0, 1.0).normalize(), 0.5);
+        sun.rim_power = 1.0;
+        let wide = sun.fragment(&mid).b;
+        sun.rim_power = 8.0;
+        assert!(sun.fragment(&mid).b < wide);
+    }
+
+    #[test]
+    fn shockwave_expands_while_fading_and_repeats() {
+        let shockwave = ShockwaveShader::default();
+        let period = shockwave.period;
+        assert_eq!(shockwave.scale_factor(0.0), 1.0);
+        assert_eq!(shockwave.alpha(0.0), 1.0);
+
+        let mut previous = (shockwave.scale_factor(0.0), shockwave.alpha(0.0));
+        for step in 1..30 {
+            let time = period * step as f32 / 30.0;
+            let current = (shockwave.scale_factor(time), shockwave.alpha(time));
+            assert!(
+                current.0 > previous.0 && current.1 < previous.1,
+                "t = {time}"
+            );
+            previous = current;
+        }
+        assert!((previous.0 - shockwave.max_scale).abs() < 0.01);
+        assert!(previous.1 < 0.01);
+
+        // Cada periodo repite la misma expansión.
+        let time = 0.37 * period;
+        assert!(
+            (shockwave.scale_factor(time + 2.0 * period) - shockwave.scale_factor(time)).abs()
+                < 1e-4
+        );
+
+        // La silueta es más opaca que el centro del disco.
+        let center = shockwave.fragment(&fragment_at(Vec3::z(), 0.0));
+        let rim = shockwave.fragment(&fragment_at(Vec3::new(1.0, 0.0, 0.05).normalize(), 0.0));
+        assert!(rim.a > center.a);
+    }
+
+    #[test]
+    fn classic_sun_surface_is_fixed_in_object_space_and_churns_in_place() {
+        let sun = ClassicSunShader::default();
+        let dir = Vec3::new(0.3, 0.5, 0.81).normalize();
+
+        // Mover el punto en espacio mundo (con la misma normal y distancia) no cambia el
+        // patrón: solo cuenta la posición en espacio del objeto.
+        let moved = FragmentInput {
+            world_pos: Vec3::new(-dir.y, dir.x, dir.z),
+            ..fragment_at(dir, 2.0)
+        };
+        assert_eq!(
+            rgb(sun.fragment(&moved)),
+            rgb(sun.fragment(&fragment_at(dir, 2.0)))
+        );
+        let rotated_object = FragmentInput {
+            object_pos: Vec3::new(-dir.y, dir.x, dir.z),
+            ..fragment_at(dir, 2.0)
+        };
+        assert_ne!(
+            rgb(sun.fragment(&rotated_object)),
+            rgb(sun.fragment(&fragment_at(dir, 2.0)))
+        );
+
+        // La granulación cambia con el tiempo pero vuelve a su estado cada ciclo del relevo.
+        let period = 1.0 / GRANULATION_CHURN_RATE;
+        let mut changed = false;
+        for i in 0..32 {
+            let dir = Vec3::new(i as f32 * 0.37 - 6.0, 1.0, (i as f32).sin()).normalize();
+            let start = sun.granulation(&dir, 3.0);
+            assert!((start - sun.granulation(&dir, 3.0 + period)).abs() < 1e-3);
+            changed |= (start - sun.granulation(&dir, 3.0 + period * 0.3)).abs() > 0.01;
+        }
+        assert!(changed);
+    }
+
+    #[test]
+    fn spot_antialiasing_ignores_the_position_and_scale_of_the_star() {
+        let sun = ClassicSunShader::default();
+        // Derivadas de un píxel para una estrella de radio 1 vista pequeña.
+        let with_footprint = |dir: Vec3| FragmentInput {
+            dpdx: Vec3::new(0.04, 0.0, 0.0),
+            dpdy: Vec3::new(0.0, 0.04, 0.0),
+            object_dpdx: Vec3::new(0.04, 0.0, 0.0),
+            object_dpdy: Vec3::new(0.0, 0.04, 0.0),
+            ..fragment_at(dir, 1.0)
+        };
+        // La misma estrella con escala 0.35 en (2.4, 0.9, -1.5), como la compañera de la
+        // escena: el píxel cubre la misma fracción de su superficie.
+        let companion = |dir: Vec3| {
+            let scale = 0.35;
+            let input = with_footprint(dir);
+            FragmentInput {
+                world_pos: Vec3::new(2.4, 0.9, -1.5) + dir * scale,
+                dpdx: input.dpdx * scale,
+                dpdy: input.dpdy * scale,
+                ..input
+            }
+        };
+
+        let mut softened = false;
+        for dir in sphere_directions(400) {
+            let near = rgb(sun.fragment(&with_footprint(dir)));
+            assert_eq!(rgb(sun.fragment(&companion(dir))), near);
+            softened |= near != rgb(sun.fragment(&fragment_at(dir, 1.0)));
+        }
+        assert!(
+            softened,
+            "el ancho de píxel suaviza el borde de alguna mancha"
+        );
+    }
+
+    #[test]
+    fn seeded_shaders_draw_different_surfaces() {
+        let dir = Vec3::new(0.3, 0.5, 0.81).normalize();
+        let differ = |a: &dyn StarShader, b: &dyn StarShader| {
+            sphere_directions(64).iter().any(|&d| {
+                let input = fragment_at(d, 1.0);
+                rgb(a.fragment(&input)) != rgb(b.fragment(&input))
+            })
+        };
+        assert!(differ(
+            &ClassicSunShader::default(),
+            &ClassicSunShader::default().with_seed(7)
+        ));
+        assert!(differ(
+            &PulsarShader::default(),
+            &PulsarShader::default().with_seed(7)
+        ));
+        assert!(differ(
+            &PlasmaStarShader::default(),
+            &PlasmaStarShader::default().with_seed(7)
+        ));
+        // La supernova satura casi todo el disco; basta con que su ruido cambie de semilla.
+        assert_eq!(SupernovaShader::default().with_seed(7).noise.seed(), 7);
+        assert!(differ(
+            &ProminenceSunShader::default(),
+            &ProminenceSunShader::default().with_seed(7)
+        ));
+        // Con la misma semilla, el resultado no cambia.
+        let input = fragment_at(dir, 1.0);
+        assert_eq!(
+            rgb(ClassicSunShader::default().with_seed(7).fragment(&input)),
+            rgb(ClassicSunShader::default().with_seed(7).fragment(&input))
+        );
+    }
+
+    /// Envoltorio que guarda cada fragmento sombreado junto con su color.
+    struct FragmentRecorder<'a> {
+        inner: &'a dyn StarShader,
+        fragments: std::sync::Mutex<Vec<(FragmentInput, Color)>>,
+    }
+
+    impl StarShader for FragmentRecorder<'_> {
+        fn fragment(&self, input: &FragmentInput) -> Color {
+            let color = self.inner.fragment(input);
+            self.fragments.lock().unwrap().push((*input, color));
+            color
+        }
+
+        fn vertex(&self, vertex: &Vertex, time: f32) -> Vertex {
+            self.inner.vertex(vertex, time)
+        }
+    }
+
+    #[test]
+    fn prominence_tint_follows_the_raised_geometry_of_a_moved_star() {
+        use crate::framebuffer::Framebuffer;
+        use crate::mesh::ObjMesh;
+        use crate::renderer::Renderer;
+
+        let shader = ProminenceSunShader::default();
+        let recorder = FragmentRecorder {
+            inner: &shader,
+            fragments: std::sync::Mutex::new(Vec::new()),
+        };
+        // Estrella girada y desplazada del origen.
+        let model = nalgebra_glm::translate(&Mat4::identity(), &Vec3::new(0.6, -0.3, -0.5));
+        let model = nalgebra_glm::rotate(&model, 1.1, &Vec3::new(0.3, 1.0, 0.2).normalize());
+        let view = nalgebra_glm::look_at(&Vec3::new(0.0, 0.0, 4.0), &Vec3::zeros(), &Vec3::y());
+        let projection = nalgebra_glm::perspective(1.0, 1.0, 0.1, 100.0);
+        let time = 1.0;
+        Renderer::new(96, 96).render_mesh(
+            &mut Framebuffer::new(96, 96),
+            &ObjMesh::create_sphere(1.0, 48, 48),
+            &recorder,
+            &model,
+            &view,
+            &projection,
+            time,
+        );
+
+        // Sólo los fragmentos elevados por la etapa de vértice se tiñen, y lo hacen más
+        // cuanto más alta es la protuberancia.
+        let mut raised = 0;
+        for (input, color) in recorder.fragments.into_inner().unwrap() {
+            let dir = input.object_pos.normalize();
+            let height = prominence_height(&dir, time, PROMINENCE_THRESHOLD, &shader.surface.noise);
+            let [r, g, b] = rgb(color);
+            let [base_r, base_g, base_b] = rgb(shader.surface.fragment(&input));
+            if height == 0.0 {
+                assert_eq!([r, g, b], [base_r, base_g, base_b], "{dir}");
+            } else if height >= PROMINENCE_HEIGHT * 0.2 {
+                assert!(
+                    g < base_g && b <= base_b,
+                    "{dir}: {:?} vs {:?}",
+                    [r, g, b],
+                    [base_r, base_g, base_b]
+                );
+                raised += 1;
+            }
+        }
+        assert!(raised > 0, "alguna protuberancia queda a la vista");
+    }
+}
@@ -126,6 +126,25 @@ pub fn fresnel(view_dir: &Vec3, normal: &Vec3, power: f32) -> f32 {
     (1.0 - view_dir.dot(normal).abs()).powf(power)
 }
 
+/// Calcula el oscurecimiento del limbo (*limb darkening*) de un disco estelar.
+///
+/// Las estrellas se ven más tenues en el borde del disco porque ahí se observan capas
+/// más externas y frías. Usa la ley lineal `I(μ) = 1 - u·(1 - μ)`, donde `μ` es el
+/// coseno del ángulo entre la normal y la dirección de vista.
+///
+/// # Arguments
+/// * `normal` - Normal de la superficie normalizada
+/// * `view_dir` - Dirección de la cámara normalizada
+/// * `coefficient` - Coeficiente de oscurecimiento `u` [0.0, 1.0] (el Sol ronda 0.6)
+///
+/// # Returns
+/// Factor multiplicativo: 1.0 en el centro del disco, `1 - u` en el limbo
+#[inline]
+pub fn limb_darkening(normal: &Vec3, view_dir: &Vec3, coefficient: f32) -> f32 {
+    let mu = normal.dot(view_dir).abs().clamp(0.0, 1.0);
+    1.0 - coefficient.clamp(0.0, 1.0) * (1.0 - mu)
+}
+
 /// Genera una pulsación sinusoidal suavizada.
 ///
 /// # Arguments
@@ -154,4 +173,47 @@ pub fn pulse(time: f32, frequency: f32, min: f32, max: f32) -> f32 {
 #[inline]
 pub fn pulse_pow(time: f32, frequency: f32, power: f32) -> f32 {
     ((time * frequency).sin() * 0.5 + 0.5).powf(power)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limb_darkening_fades_from_center_to_limb() {
+        let view_dir = Vec3::new(0.0, 0.0, 1.0);
+        let at = |angle: f32| {
+            let normal = Vec3::new(angle.sin(), 0.0, angle.cos());
+            limb_darkening(&normal, &view_dir, 0.6)
+        };
+        assert_eq!(at(0.0), 1.0);
+        let samples: Vec<f32> = (0..=9).map(|i| at(i as f32 * 10f32.to_radians())).collect();
+        assert!(samples.windows(2).all(|w| w[1] < w[0]));
+        assert!((at(90f32.to_radians()) - 0.4).abs() < 1e-5);
+    }
+
+    #[test]
+    fn shared_helpers_match_the_inline_formulas_they_replace() {
+        for i in 0..50 {
+            let time = i as f32 * 0.37;
+            let close = |a: f32, b: f32| (a - b).abs() < 1e-5;
+            assert!(close(
+                pulse(time, 2.0, 0.9, 1.0),
+                (time * 2.0).sin() * 0.05 + 0.95
+            ));
+            assert!(close(
+                pulse(time, 0.5, 0.8, 1.2),
+                (time * 0.5).sin() * 0.2 + 1.0
+            ));
+            assert!(close(
+                pulse(time, 4.0, 0.4, 1.0),
+                (time * 4.0).sin() * 0.3 + 0.7
+            ));
+        }
+
+        let view_dir = Vec3::new(0.0, 0.0, 1.0);
+        let normal = Vec3::new(0.6, 0.0, 0.8);
+        assert_eq!(fresnel(&view_dir, &view_dir, 3.0), 0.0);
+        assert_eq!(fresnel(&view_dir, &Vec3::x(), 3.0), 1.0);
+        assert!((fresnel(&view_dir, &normal, 2.0) - 0.2f32.powf(2.0)).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,129 @@
+//! Módulo de mapas de sombra direccionales.
+//
+// Este archivo define el mapa de profundidad renderizado desde el punto de vista de una luz
+// y la consulta que decide si un punto en espacio mundo queda oculto de ella.
+
+use crate::framebuffer::Framebuffer; // Reutiliza el z-buffer del framebuffer como textura de profundidad.
+use nalgebra_glm::{Mat4, Vec3, Vec4}; // Matrices y vectores para proyectar al espacio de la luz.
+
+/// Mapa de sombras para una luz direccional.
+///
+/// Guarda la profundidad más cercana a la luz en cada texel, tal como la produce
+/// [`Renderer::render_depth_only`](crate::renderer::Renderer::render_depth_only).
+pub struct ShadowMap {
+    /// Destino de la pasada de profundidad; solo se usa su z-buffer.
+    pub depth: Framebuffer,
+    /// Matriz de vista de la luz.
+    pub light_view: Mat4,
+    /// Matriz de proyección de la luz (normalmente ortográfica).
+    pub light_proj: Mat4,
+    /// Margen de profundidad para evitar el *shadow acne* (autosombreado por precisión).
+    pub bias: f32,
+}
+
+impl ShadowMap {
+    /// Crea un mapa de sombras vacío de `width` x `height` texeles.
+    ///
+    /// # Argumentos
+    /// * `width`, `height` - Resolución del mapa.
+    /// * `light_view` - Matriz de vista desde la luz.
+    /// * `light_proj` - Matriz de proyección de la luz.
+    pub fn new(width: usize, height: usize, light_view: Mat4, light_proj: Mat4) -> Self {
+        ShadowMap {
+            depth: Framebuffer::new(width, height),
+            light_view,
+            light_proj,
+            bias: 0.005,
+        }
+    }
+
+    /// Borra las profundidades almacenadas antes de una nueva pasada.
+    pub fn clear(&mut self) {
+        self.depth.zbuffer.fill(f32::INFINITY);
+    }
+
+    /// Indica si un punto en espacio mundo está a la sombra de la luz.
+    ///
+    /// Los puntos que caen fuera del volumen de la luz se consideran iluminados.
+    pub fn is_shadowed(&self, world_pos: &Vec3) -> bool {
+        let clip = self.light_proj
+            * self.light_view
+            * Vec4::new(world_pos.x, world_pos.y, world_pos.z, 1.0);
+        if clip.w.abs() < 1e-6 {
+            return false;
+        }
+        let ndc = clip.xyz() / clip.w;
+        if ndc.x.abs() > 1.0 || ndc.y.abs() > 1.0 || ndc.z.abs() > 1.0 {
+            return false;
+        }
+
+        // Misma conversión a píxeles que usa el rasterizador.
+        let x = ((ndc.x + 1.0) * 0.5 * self.depth.width as f32) as usize;
+        let y = ((1.0 - ndc.y) * 0.5 * self.depth.height as f32) as usize;
+        let x = x.min(self.depth.width - 1);
+        let y = y.min(self.depth.height - 1);
+
+        ndc.z - self.bias > self.depth.zbuffer[y * self.depth.width + x]
+    }
+
+    /// Factor de visibilidad de la luz: 0.0 en sombra, 1.0 iluminado.
+    pub fn visibility(&self, world_pos: &Vec3) -> f32 {
+        if self.is_shadowed(world_pos) {
+            0.0
+        } else {
+            1.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::{ObjMesh, Vertex};
+    use crate::renderer::Renderer;
+    use nalgebra_glm::{Vec2, Vec4};
+
+    /// Triángulo que cubre el centro del volumen de la luz a la profundidad `z`.
+    fn occluder(z: f32) -> ObjMesh {
+        let corners = [(-0.5, -0.5), (0.5, -0.5), (0.0, 0.5)];
+        ObjMesh {
+            vertices: corners
+                .iter()
+                .map(|&(x, y)| Vertex {
+                    position: Vec3::new(x, y, z),
+                    normal: Vec3::z(),
+                    uv: Vec2::zeros(),
+                    tangent: Vec4::zeros(),
+                })
+                .collect(),
+            indices: vec![0, 1, 2],
+            submeshes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn points_behind_occluder_are_shadowed() {
+        // Con matrices identidad la luz mira a lo largo de Z y la profundidad es `z`.
+        let mut shadow_map = ShadowMap::new(32, 32, Mat4::identity(), Mat4::identity());
+        let renderer = Renderer::new(32, 32).with_culling(false);
+        renderer.render_depth_only(&mut shadow_map, &occluder(-0.5), &Mat4::identity());
+
+        assert!(shadow_map.is_shadowed(&Vec3::new(0.0, 0.0, 0.5)));
+        assert_eq!(shadow_map.visibility(&Vec3::new(0.0, 0.0, 0.5)), 0.0);
+        assert!(
+            !shadow_map.is_shadowed(&Vec3::new(0.0, 0.0, -0.8)),
+            "delante del oclusor"
+        );
+        assert!(
+            !shadow_map.is_shadowed(&Vec3::new(0.9, 0.9, 0.5)),
+            "fuera de su huella"
+        );
+        assert!(
+            !shadow_map.is_shadowed(&Vec3::new(0.0, 0.0, -0.5)),
+            "el bias evita el acné"
+        );
+
+        shadow_map.clear();
+        assert!(!shadow_map.is_shadowed(&Vec3::new(0.0, 0.0, 0.5)));
+    }
+}
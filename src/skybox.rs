@@ -0,0 +1,170 @@
+//! Módulo de fondo de entorno (skybox).
+//
+// Este archivo define el fondo que rodea a la estrella: un cubemap de seis texturas o, si no
+// hay texturas, un degradado procedural. Se muestrea por la dirección del rayo de vista de
+// cada píxel y se dibuja sin ocupar el z-buffer, de modo que la malla lo cubre.
+
+use crate::camera::screen_ray; // Rayo de vista por píxel.
+use crate::framebuffer::{Color, Framebuffer}; // Destino del fondo.
+use crate::texture::Texture; // Caras del cubemap.
+use nalgebra_glm::{Mat4, Vec2, Vec3}; // Matrices y vectores.
+
+/// Fondo de entorno muestreado por dirección.
+pub enum Skybox {
+    /// Degradado procedural según la altitud (componente Y) de la dirección.
+    Gradient {
+        /// Color en el horizonte.
+        horizon: Vec3,
+        /// Color en el cenit (y en el nadir).
+        zenith: Vec3,
+    },
+    /// Cubemap con seis caras en el orden +X, -X, +Y, -Y, +Z, -Z.
+    Cubemap(Box<[Texture; 6]>),
+}
+
+impl Default for Skybox {
+    /// Degradado de azul oscuro en el horizonte a negro en el cenit.
+    fn default() -> Self {
+        Skybox::Gradient {
+            horizon: Vec3::new(0.04, 0.05, 0.16),
+            zenith: Vec3::new(0.0, 0.0, 0.0),
+        }
+    }
+}
+
+impl Skybox {
+    /// Devuelve el color del entorno en la dirección `dir` (normalizada).
+    pub fn sample(&self, dir: &Vec3) -> Vec3 {
+        match self {
+            Skybox::Gradient { horizon, zenith } => {
+                let altitude = dir.y.abs().clamp(0.0, 1.0);
+                horizon * (1.0 - altitude) + zenith * altitude
+            }
+            Skybox::Cubemap(faces) => {
+                let (face, uv) = cube_face_uv(dir);
+                let texture = &faces[face];
+
+                // Evita que el filtrado bilineal repita la cara opuesta en los bordes.
+                let margin_u = 0.5 / texture.width() as f32;
+                let margin_v = 0.5 / texture.height() as f32;
+                let uv = Vec2::new(
+                    uv.x.clamp(margin_u, 1.0 - margin_u),
+                    uv.y.clamp(margin_v, 1.0 - margin_v),
+                );
+                texture.sample_bilinear(&uv, 0)
+            }
+        }
+    }
+
+    /// Dibuja el fondo en todo el framebuffer sin modificar el z-buffer.
+    ///
+    /// Debe llamarse después de limpiar y antes de renderizar las mallas: como la
+    /// profundidad queda en infinito, cualquier geometría lo sobrescribe.
+    pub fn draw(
+        &self,
+        framebuffer: &mut Framebuffer,
+        view_matrix: &Mat4,
+        projection_matrix: &Mat4,
+    ) {
+        let Some(inverse) = (projection_matrix * view_matrix).try_inverse() else {
+            return;
+        };
+        let (width, height) = (framebuffer.width as f32, framebuffer.height as f32);
+
+        for y in 0..framebuffer.height {
+            for x in 0..framebuffer.width {
+                let dir = screen_ray(x as f32, y as f32, width, height, &inverse);
+                let color = Color::from_vec3(self.sample(&dir));
+                let idx = (y * framebuffer.width + x) * 4;
+                framebuffer.buffer[idx] = color.r;
+                framebuffer.buffer[idx + 1] = color.g;
+                framebuffer.buffer[idx + 2] = color.b;
+            }
+        }
+    }
+}
+
+/// Selecciona la cara del cubemap según el eje dominante de `dir` y calcula sus UV.
+///
+/// Sigue la convención habitual de los cubemaps (la de OpenGL), con `v` creciendo hacia abajo.
+fn cube_face_uv(dir: &Vec3) -> (usize, Vec2) {
+    let abs = dir.abs();
+    let (face, major, u, v) = if abs.x >= abs.y && abs.x >= abs.z {
+        if dir.x > 0.0 {
+            (0, abs.x, -dir.z, -dir.y)
+        } else {
+            (1, abs.x, dir.z, -dir.y)
+        }
+    } else if abs.y >= abs.z {
+        if dir.y > 0.0 {
+            (2, abs.y, dir.x, dir.z)
+        } else {
+            (3, abs.y, dir.x, -dir.z)
+        }
+    } else if dir.z > 0.0 {
+        (4, abs.z, dir.x, -dir.y)
+    } else {
+        (5, abs.z, -dir.x, -dir.y)
+    };
+
+    let major = major.max(f32::EPSILON);
+    (
+        face,
+        Vec2::new(u / major * 0.5 + 0.5, v / major * 0.5 + 0.5),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gradient_blends_from_horizon_to_zenith() {
+        let horizon = Vec3::new(0.2, 0.4, 0.6);
+        let sky = Skybox::Gradient {
+            horizon,
+            zenith: Vec3::zeros(),
+        };
+        assert_eq!(sky.sample(&Vec3::new(1.0, 0.0, 0.0)), horizon);
+        assert_eq!(sky.sample(&Vec3::new(0.0, 1.0, 0.0)), Vec3::zeros());
+        assert_eq!(sky.sample(&Vec3::new(0.0, -1.0, 0.0)), Vec3::zeros());
+        assert!((sky.sample(&Vec3::new(0.0, 0.5, 0.0)) - horizon * 0.5).magnitude() < 1e-6);
+    }
+
+    #[test]
+    fn cube_faces_follow_dominant_axis() {
+        let axes = [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(-1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, -1.0),
+        ];
+        for (expected, axis) in axes.iter().enumerate() {
+            let (face, uv) = cube_face_uv(axis);
+            assert_eq!(face, expected);
+            assert_eq!(
+                uv,
+                Vec2::new(0.5, 0.5),
+                "el eje cae en el centro de su cara"
+            );
+        }
+        let (face, uv) = cube_face_uv(&Vec3::new(0.9, 0.9, 1.0));
+        assert_eq!(face, 4);
+        assert!(uv.x > 0.9 && uv.y < 0.1);
+    }
+
+    #[test]
+    fn draw_fills_background_without_depth() {
+        let mut framebuffer = Framebuffer::new(8, 8);
+        let view = nalgebra_glm::look_at(&Vec3::new(0.0, 0.0, 3.0), &Vec3::zeros(), &Vec3::y());
+        let projection = nalgebra_glm::perspective(1.0, 1.0, 0.1, 100.0);
+        Skybox::default().draw(&mut framebuffer, &view, &projection);
+
+        // El centro mira al horizonte: azul oscuro.
+        let idx = (4 * 8 + 4) * 4;
+        assert!(framebuffer.buffer[idx + 2] > 0);
+        assert!(framebuffer.zbuffer.iter().all(|&z| z == f32::INFINITY));
+    }
+}
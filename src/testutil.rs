@@ -0,0 +1,128 @@
+//! Utilidades para comprobar la estabilidad temporal de los shaders.
+//
+// Un shader que parpadea entre cuadros (por ejemplo, por un hash de ruido sensible al
+// redondeo) es difícil de detectar a simple vista. Este archivo ofrece una medida de la
+// diferencia entre dos cuadros y un render mínimo de un shader aislado, para comparar
+// cuadros del mismo instante (que deben ser idénticos) y de instantes muy próximos (que
+// deben diferir poco).
+
+use crate::framebuffer::{Color, Framebuffer};
+use crate::mesh::ObjMesh;
+use crate::renderer::Renderer;
+use crate::scene::SceneCamera;
+use crate::shaders::StarShader;
+use nalgebra_glm::{Mat4, Vec3};
+
+/// Campo de visión vertical de [`render_shader`], en grados.
+const FOV_Y_DEG: f32 = 60.0;
+
+/// Diferencia absoluta media entre dos cuadros, en niveles de 8 bits por canal.
+///
+/// Promedia `|a - b|` sobre los canales R, G y B de todos los píxeles (el alfa se ignora),
+/// así que 0.0 indica cuadros idénticos y 255.0 el máximo posible.
+///
+/// # Panics
+/// Si los framebuffers no tienen las mismas dimensiones.
+pub fn frame_diff(a: &Framebuffer, b: &Framebuffer) -> f64 {
+    assert!(
+        a.width == b.width && a.height == b.height,
+        "Los framebuffers deben tener las mismas dimensiones"
+    );
+    let channels = a.width * a.height * 3;
+    if channels == 0 {
+        return 0.0;
+    }
+
+    let total: u64 = a
+        .as_bytes()
+        .chunks_exact(4)
+        .zip(b.as_bytes().chunks_exact(4))
+        .flat_map(|(pa, pb)| (0..3).map(move |c| pa[c].abs_diff(pb[c]) as u64))
+        .sum();
+    total as f64 / channels as f64
+}
+
+/// Renderiza una esfera con `shader` al instante `time`, sobre fondo negro y sin
+/// post-procesado.
+///
+/// La cámara mira al origen desde la distancia sugerida por el shader, con la esfera de
+/// escala 1.5 de la escena principal y sin rotación, de modo que dos llamadas con el mismo
+/// `time` solo pueden diferir si el shader no es determinista.
+pub fn render_shader(
+    shader: &dyn StarShader,
+    time: f32,
+    width: usize,
+    height: usize,
+) -> Framebuffer {
+    let mesh = ObjMesh::create_sphere(1.0, 32, 32);
+    let camera = SceneCamera::orbit(shader.suggested_camera_distance(), FOV_Y_DEG, false);
+    let aspect = width as f32 / height.max(1) as f32;
+    let model_matrix = nalgebra_glm::scale(&Mat4::identity(), &Vec3::new(1.5, 1.5, 1.5));
+
+    let mut framebuffer = Framebuffer::new(width, height);
+    framebuffer.clear(Color::new(0, 0, 0));
+    Renderer::new(width, height).render_mesh(
+        &mut framebuffer,
+        &mesh,
+        shader,
+        &model_matrix,
+        &camera.view_matrix(),
+        &camera.projection_matrix(aspect),
+        time,
+    );
+    framebuffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shaders::{
+        ClassicSunShader, PlasmaStarShader, ProminenceSunShader, PulsarShader, SupernovaShader,
+    };
+
+    #[test]
+    fn frame_diff_averages_rgb_differences() {
+        let mut a = Framebuffer::new(2, 1);
+        let mut b = Framebuffer::new(2, 1);
+        a.clear(Color::new(10, 20, 30));
+        b.clear(Color::new(10, 20, 30));
+        assert_eq!(frame_diff(&a, &b), 0.0);
+
+        b.set_pixel(1, 0, Color::rgba(70, 20, 30, 0), 0.5);
+        assert_eq!(frame_diff(&a, &b), 10.0, "el alfa no cuenta");
+    }
+
+    /// Los cinco shaders de la escena principal.
+    fn shaders() -> Vec<Box<dyn StarShader>> {
+        vec![
+            Box::new(ClassicSunShader::default()),
+            Box::new(PulsarShader::default()),
+            Box::new(PlasmaStarShader::default()),
+            Box::new(SupernovaShader::default()),
+            Box::new(ProminenceSunShader::default()),
+        ]
+    }
+
+    #[test]
+    fn same_time_frames_are_identical() {
+        for shader in shaders() {
+            let a = render_shader(shader.as_ref(), 2.5, 64, 48);
+            let b = render_shader(shader.as_ref(), 2.5, 64, 48);
+            assert_eq!(frame_diff(&a, &b), 0.0);
+        }
+    }
+
+    #[test]
+    fn near_time_frames_differ_little() {
+        for shader in shaders() {
+            let a = render_shader(shader.as_ref(), 2.5, 64, 48);
+            let b = render_shader(shader.as_ref(), 2.5 + 1.0 / 240.0, 64, 48);
+            let near = frame_diff(&a, &b);
+            assert!(near < 1.0, "un cuarto de cuadro cambia {near:.3} niveles");
+
+            // Con más tiempo de por medio la animación se aleja más.
+            let far = frame_diff(&a, &render_shader(shader.as_ref(), 4.0, 64, 48));
+            assert!(far >= near, "{near:.3} > {far:.3}");
+        }
+    }
+}
@@ -0,0 +1,264 @@
+//! Módulo de texturas para el renderizado por software.
+//
+// Este archivo define una textura RGB en punto flotante con su cadena de mipmaps, junto con
+// el muestreo bilineal y trilineal usado por los shaders para evitar aliasing al reducirla.
+
+use nalgebra_glm::{Vec2, Vec3}; // Coordenadas UV y colores en punto flotante.
+
+/// Filtro usado al muestrear una textura sin derivadas (por ejemplo, al copiarla a pantalla).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SampleFilter {
+    /// Texel más cercano: conserva los bordes duros, útil para imágenes pixeladas.
+    Nearest,
+    /// Interpolación bilineal entre los cuatro texeles más cercanos.
+    #[default]
+    Bilinear,
+}
+
+/// Un nivel de la cadena de mipmaps: una imagen RGB de `width` x `height` texeles.
+#[derive(Debug, Clone)]
+struct MipLevel {
+    width: usize,
+    height: usize,
+    texels: Vec<Vec3>,
+}
+
+impl MipLevel {
+    /// Lee un texel repitiendo la imagen fuera de sus bordes (modo *repeat*).
+    #[inline]
+    fn texel(&self, x: i64, y: i64) -> Vec3 {
+        let x = x.rem_euclid(self.width as i64) as usize;
+        let y = y.rem_euclid(self.height as i64) as usize;
+        self.texels[y * self.width + x]
+    }
+
+    /// Devuelve el texel que contiene las coordenadas `uv` (filtrado *nearest*).
+    fn sample_nearest(&self, uv: &Vec2) -> Vec3 {
+        let x = (uv.x * self.width as f32).floor() as i64;
+        let y = (uv.y * self.height as f32).floor() as i64;
+        self.texel(x, y)
+    }
+
+    /// Muestrea el nivel con filtrado bilineal en las coordenadas `uv`.
+    fn sample_bilinear(&self, uv: &Vec2) -> Vec3 {
+        // Centra el muestreo en los texeles (el texel i cubre [i, i+1)).
+        let x = uv.x * self.width as f32 - 0.5;
+        let y = uv.y * self.height as f32 - 0.5;
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let tx = x - x0;
+        let ty = y - y0;
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top = self.texel(x0, y0) * (1.0 - tx) + self.texel(x0 + 1, y0) * tx;
+        let bottom = self.texel(x0, y0 + 1) * (1.0 - tx) + self.texel(x0 + 1, y0 + 1) * tx;
+        top * (1.0 - ty) + bottom * ty
+    }
+
+    /// Genera el siguiente nivel promediando bloques de 2x2 texeles.
+    fn downsample(&self) -> MipLevel {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let mut texels = Vec::with_capacity(width * height);
+
+        for y in 0..height {
+            for x in 0..width {
+                // En dimensiones impares o de tamaño 1, repite el último texel disponible.
+                let x0 = (x * 2).min(self.width - 1);
+                let x1 = (x * 2 + 1).min(self.width - 1);
+                let y0 = (y * 2).min(self.height - 1);
+                let y1 = (y * 2 + 1).min(self.height - 1);
+
+                let sum = self.texels[y0 * self.width + x0]
+                    + self.texels[y0 * self.width + x1]
+                    + self.texels[y1 * self.width + x0]
+                    + self.texels[y1 * self.width + x1];
+                texels.push(sum * 0.25);
+            }
+        }
+
+        MipLevel {
+            width,
+            height,
+            texels,
+        }
+    }
+}
+
+/// Textura RGB con soporte de mipmaps.
+#[derive(Debug, Clone)]
+pub struct Texture {
+    /// Niveles de detalle; el nivel 0 es la imagen original.
+    levels: Vec<MipLevel>,
+}
+
+impl Texture {
+    /// Crea una textura a partir de sus texeles, ordenados por filas.
+    ///
+    /// # Argumentos
+    /// * `width` - Ancho en texeles (mayor que 0).
+    /// * `height` - Alto en texeles (mayor que 0).
+    /// * `texels` - Colores en el rango [0.0, 1.0], `width * height` elementos.
+    pub fn new(width: usize, height: usize, texels: Vec<Vec3>) -> Self {
+        assert!(
+            width > 0 && height > 0,
+            "Texture dimensions must be non-zero"
+        );
+        assert_eq!(texels.len(), width * height, "Texel count mismatch");
+
+        Texture {
+            levels: vec![MipLevel {
+                width,
+                height,
+                texels,
+            }],
+        }
+    }
+
+    /// Crea una textura a partir de una imagen RGBA de 8 bits por canal, ordenada por filas
+    /// de arriba hacia abajo (el formato del framebuffer y de las imágenes decodificadas).
+    ///
+    /// Los canales se escalan a [0.0, 1.0] sin convertir el espacio de color, de modo que
+    /// copiar la textura de vuelta al framebuffer reproduce los bytes originales. El alfa se
+    /// descarta.
+    ///
+    /// # Panics
+    /// Si alguna dimensión es 0 o `rgba` no tiene `width * height * 4` bytes.
+    pub fn from_rgba8(width: usize, height: usize, rgba: &[u8]) -> Self {
+        assert_eq!(rgba.len(), width * height * 4, "RGBA buffer size mismatch");
+        let texels = rgba
+            .chunks_exact(4)
+            .map(|p| Vec3::new(p[0] as f32, p[1] as f32, p[2] as f32) / 255.0)
+            .collect();
+        Texture::new(width, height, texels)
+    }
+
+    /// Crea una textura evaluando `f(x, y)` para cada texel.
+    pub fn from_fn(width: usize, height: usize, f: impl Fn(usize, usize) -> Vec3) -> Self {
+        let texels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| f(x, y))
+            .collect();
+        Texture::new(width, height, texels)
+    }
+
+    /// Ancho del nivel base en texeles.
+    pub fn width(&self) -> usize {
+        self.levels[0].width
+    }
+
+    /// Alto del nivel base en texeles.
+    pub fn height(&self) -> usize {
+        self.levels[0].height
+    }
+
+    /// Número de niveles de detalle disponibles (1 si no se generaron mipmaps).
+    pub fn mip_count(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Dimensiones `(ancho, alto)` del nivel `level`, si existe.
+    pub fn mip_size(&self, level: usize) -> Option<(usize, usize)> {
+        self.levels.get(level).map(|l| (l.width, l.height))
+    }
+
+    /// Genera la cadena completa de mipmaps, reduciendo a la mitad hasta llegar a 1x1.
+    ///
+    /// Reemplaza cualquier cadena generada previamente.
+    pub fn generate_mipmaps(&mut self) {
+        self.levels.truncate(1);
+        while let Some(last) = self.levels.last() {
+            if last.width == 1 && last.height == 1 {
+                break;
+            }
+            let next = last.downsample();
+            self.levels.push(next);
+        }
+    }
+
+    /// Muestrea un nivel concreto con filtrado bilineal.
+    ///
+    /// Niveles fuera de rango se ajustan al último disponible.
+    pub fn sample_bilinear(&self, uv: &Vec2, level: usize) -> Vec3 {
+        let level = level.min(self.levels.len() - 1);
+        self.levels[level].sample_bilinear(uv)
+    }
+
+    /// Muestrea un nivel concreto con el filtro indicado. Ver [`Texture::sample_bilinear`].
+    pub fn sample(&self, uv: &Vec2, level: usize, filter: SampleFilter) -> Vec3 {
+        let level = &self.levels[level.min(self.levels.len() - 1)];
+        match filter {
+            SampleFilter::Nearest => level.sample_nearest(uv),
+            SampleFilter::Bilinear => level.sample_bilinear(uv),
+        }
+    }
+
+    /// Calcula el nivel de detalle a partir de las derivadas en pantalla de las UV.
+    ///
+    /// El LOD es el logaritmo en base 2 de cuántos texeles del nivel base abarca un píxel.
+    pub fn lod(&self, duvdx: &Vec2, duvdy: &Vec2) -> f32 {
+        let size = Vec2::new(self.width() as f32, self.height() as f32);
+        let rho = duvdx
+            .component_mul(&size)
+            .magnitude()
+            .max(duvdy.component_mul(&size).magnitude());
+
+        if rho <= 1.0 {
+            0.0
+        } else {
+            rho.log2().min((self.levels.len() - 1) as f32)
+        }
+    }
+
+    /// Muestrea con filtrado trilineal: bilineal en los dos niveles más cercanos al LOD
+    /// calculado de las derivadas, interpolando linealmente entre ellos.
+    ///
+    /// # Argumentos
+    /// * `uv` - Coordenadas de textura.
+    /// * `duvdx`, `duvdy` - Variación de las UV por píxel en X e Y de pantalla.
+    pub fn sample_trilinear(&self, uv: &Vec2, duvdx: &Vec2, duvdy: &Vec2) -> Vec3 {
+        let lod = self.lod(duvdx, duvdy);
+        let lower = lod.floor() as usize;
+        let upper = (lower + 1).min(self.levels.len() - 1);
+        let t = lod - lower as f32;
+
+        let a = self.levels[lower].sample_bilinear(uv);
+        if upper == lower || t <= 0.0 {
+            return a;
+        }
+        let b = self.levels[upper].sample_bilinear(uv);
+        a * (1.0 - t) + b * t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mip_chain_halves_down_to_one_texel() {
+        let mut texture = Texture::from_fn(8, 4, |_, _| Vec3::zeros());
+        texture.generate_mipmaps();
+        let sizes: Vec<_> = (0..texture.mip_count())
+            .map(|level| texture.mip_size(level).unwrap())
+            .collect();
+        assert_eq!(sizes, [(8, 4), (4, 2), (2, 1), (1, 1)]);
+        assert_eq!(texture.mip_size(4), None);
+    }
+
+    #[test]
+    fn uniform_texture_samples_same_color_at_every_lod() {
+        let color = Vec3::new(0.2, 0.5, 0.9);
+        let mut texture = Texture::from_fn(16, 16, |_, _| color);
+        texture.generate_mipmaps();
+        let uv = Vec2::new(0.37, 0.81);
+        for scale in [0.0, 0.01, 0.1, 0.3, 1.0, 4.0] {
+            let derivative = Vec2::new(scale, 0.0);
+            let sample = texture.sample_trilinear(&uv, &derivative, &derivative.yx());
+            assert!((sample - color).magnitude() < 1e-5, "escala {scale}");
+        }
+        for level in 0..texture.mip_count() {
+            assert!((texture.sample_bilinear(&uv, level) - color).magnitude() < 1e-5);
+        }
+    }
+}